@@ -0,0 +1,41 @@
+use chers::{fen, utils, Color, Position, Square};
+use criterion::{black_box, criterion_group, BatchSize, Criterion};
+
+fn legal_moves_100(c: &mut Criterion) {
+    let positions: Vec<Position> = utils::fen::RANDOM_FENS
+        .iter()
+        .map(|fen| Position::from_fen(fen).unwrap())
+        .collect();
+
+    c.bench_function("legal moves 100", |b| {
+        b.iter_batched(
+            || positions.clone(),
+            |positions| {
+                for mut pos in positions {
+                    black_box(pos.generate_legal_moves());
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn is_attacked_kiwipete(c: &mut Criterion) {
+    let pos = Position::from_fen(fen::KIWIPETE).unwrap();
+
+    c.bench_function("is attacked kiwipete", |b| {
+        b.iter(|| {
+            for color in Color::ALL {
+                for square in [Square::A1, Square::E4, Square::E8, Square::H5] {
+                    black_box(pos.is_attacked(black_box(square), color));
+                }
+            }
+        })
+    });
+}
+
+criterion_group!(
+    name = attack_benches;
+    config = Criterion::default();
+    targets = legal_moves_100, is_attacked_kiwipete
+);
@@ -1,10 +1,14 @@
 use criterion::criterion_main;
 
+mod attack;
+mod fen;
 mod make_move;
 mod perft;
 mod search;
 
 criterion_main!(
+    attack::attack_benches,
+    fen::fen_benches,
     perft::perft_benches,
     make_move::make_move_benches,
     search::search_benches
@@ -0,0 +1,18 @@
+use chers::{utils, Position};
+use criterion::{black_box, criterion_group, Criterion};
+
+fn from_fen_100(c: &mut Criterion) {
+    c.bench_function("from fen 100", |b| {
+        b.iter(|| {
+            for fen in utils::fen::RANDOM_FENS {
+                black_box(Position::from_fen(black_box(fen)).unwrap());
+            }
+        })
+    });
+}
+
+criterion_group!(
+    name = fen_benches;
+    config = Criterion::default();
+    targets = from_fen_100
+);
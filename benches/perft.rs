@@ -1,5 +1,5 @@
-use chers::{perft, utils, Position};
-use criterion::{black_box, criterion_group, Criterion};
+use chers::{fen, perft, Position};
+use criterion::{black_box, criterion_group, Criterion, Throughput};
 
 fn perft_starting_postion(c: &mut Criterion) {
     let mut pos = Position::new();
@@ -10,15 +10,73 @@ fn perft_starting_postion(c: &mut Criterion) {
 }
 
 fn perft_kiwipete(c: &mut Criterion) {
-    let mut pos = Position::from_fen(utils::fen::KIWIPETE).unwrap();
+    let mut pos = Position::from_fen(fen::KIWIPETE).unwrap();
 
     c.bench_function("perft 3 kiwipete", |b| {
         b.iter(|| perft(&mut pos, black_box(3)))
     });
 }
 
+/// Perft the way it used to be implemented: cloning the position for every move instead of
+/// undoing it. Only used to compare against [`perft`].
+fn perft_clone(pos: &mut Position, depth: u16) -> u64 {
+    match depth {
+        0 => 1,
+        1 => pos.generate_legal_moves().len() as u64,
+        _ => {
+            let mut count = 0;
+            for m in pos.generate_legal_moves() {
+                let mut new_pos = pos.clone();
+                new_pos.make_bit_move(m);
+                count += perft_clone(&mut new_pos, depth - 1);
+            }
+            count
+        }
+    }
+}
+
+fn perft_make_undo_vs_clone(c: &mut Criterion) {
+    let mut pos = Position::from_fen(fen::KIWIPETE).unwrap();
+
+    let mut group = c.benchmark_group("perft 4 kiwipete");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(4_085_603));
+    group.bench_function("make/undo", |b| b.iter(|| perft(&mut pos, black_box(4))));
+    group.bench_function("clone", |b| b.iter(|| perft_clone(&mut pos, black_box(4))));
+    group.finish();
+}
+
+fn perft_suite(c: &mut Criterion) {
+    // node counts at depth 4, see https://www.chessprogramming.org/Perft_Results
+    let suite = [
+        (fen::STARTING_FEN, 197_281),
+        (fen::KIWIPETE, 4_085_603),
+        (fen::POSITION_3, 43_238),
+        (fen::POSITION_4, 422_333),
+        (fen::POSITION_5, 2_103_487),
+        (fen::POSITION_6, 3_894_594),
+    ];
+    let mut positions: Vec<(Position, u64)> = suite
+        .iter()
+        .map(|&(fen, nodes)| (Position::from_fen(fen).unwrap(), nodes))
+        .collect();
+    let total: u64 = suite.iter().map(|&(_, nodes)| nodes).sum();
+
+    let mut group = c.benchmark_group("perft 4 suite");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(total));
+    group.bench_function("all positions", |b| {
+        b.iter(|| {
+            for (pos, nodes) in &mut positions {
+                assert_eq!(perft(pos, black_box(4)), *nodes);
+            }
+        })
+    });
+    group.finish();
+}
+
 criterion_group!(
     name = perft_benches;
     config = Criterion::default().sample_size(50);
-    targets = perft_starting_postion, perft_kiwipete
+    targets = perft_starting_postion, perft_kiwipete, perft_make_undo_vs_clone, perft_suite
 );
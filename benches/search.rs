@@ -1,8 +1,8 @@
-use chers::{utils, Position};
+use chers::{fen, Position};
 use criterion::{black_box, criterion_group, Criterion};
 
 fn search_kiwipete(c: &mut Criterion) {
-    let mut pos = Position::from_fen(utils::fen::KIWIPETE).unwrap();
+    let mut pos = Position::from_fen(fen::KIWIPETE).unwrap();
 
     c.bench_function("search 2 kiwipete", |b| b.iter(|| pos.search(black_box(2))));
 }
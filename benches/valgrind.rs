@@ -1,4 +1,4 @@
-use chers::{perft, utils, BitMove, Position};
+use chers::{fen, perft, BitMove, Position};
 use iai::black_box;
 
 fn perft_starting_position_3() -> u64 {
@@ -7,12 +7,12 @@ fn perft_starting_position_3() -> u64 {
 }
 
 fn perft_kiwipete_3() -> u64 {
-    let mut pos = Position::from_fen(utils::fen::KIWIPETE).unwrap();
+    let mut pos = Position::from_fen(fen::KIWIPETE).unwrap();
     perft(&mut pos, black_box(3))
 }
 
-fn search_kiwipete_2() -> BitMove {
-    let mut pos = Position::from_fen(utils::fen::KIWIPETE).unwrap();
+fn search_kiwipete_2() -> Option<BitMove> {
+    let mut pos = Position::from_fen(fen::KIWIPETE).unwrap();
     pos.search(black_box(2))
 }
 
@@ -1,10 +1,16 @@
+use arrayvec::ArrayVec;
+
 use crate::position::{
     BISHOP_OFFSETS, BLACK_PAWN_CAPTURE_OFFSETS, KING_OFFSETS, KNIGHT_OFFSETS, ROOK_OFFSETS,
     WHITE_PAWN_CAPTURE_OFFSETS,
 };
+use crate::BitMove;
 use crate::Color;
+use crate::File;
 use crate::Piece;
+use crate::PieceType;
 use crate::Position;
+use crate::Rank;
 use crate::Square;
 
 impl Position {
@@ -22,72 +28,131 @@ impl Position {
     /// assert!(!position.is_attacked(Square::E3, Color::BLACK));
     /// ```
     pub fn is_attacked(&self, square: Square, attacker: Color) -> bool {
-        let index = square.to_usize();
+        is_attacked_on(&self.pieces, square, attacker)
+    }
 
-        // pawns
-        for offset in &attacker.map(BLACK_PAWN_CAPTURE_OFFSETS, WHITE_PAWN_CAPTURE_OFFSETS) {
-            if self.pieces[(index as i8 + offset) as usize]
-                == attacker.map(Piece::W_PAWN, Piece::B_PAWN)
-            {
-                return true;
+    /// Returns a bitboard of all squares attacked by at least one piece of a given `Color`.
+    ///
+    /// Bit `n` corresponds to the square with index `n` where a1 = 0, b1 = 1, ..., h8 = 63.
+    /// Squares occupied by pieces of the attacking side are included if they are defended.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Color, Position};
+    ///
+    /// let position = Position::new();
+    /// let attacks = position.attacked_squares(Color::WHITE);
+    ///
+    /// assert_eq!(attacks & 0xff_0000, 0xff_0000); // the whole third rank
+    /// assert_eq!(attacks & (1 << 0), 0); // a1
+    /// ```
+    pub fn attacked_squares(&self, color: Color) -> u64 {
+        let mut attacks = 0;
+        for i in 0..8 {
+            for j in 0..8 {
+                let square = Square::new(File::new(i), Rank::new(j));
+                let piece = self.pieces[square];
+                if piece.is_piece() && piece.is_color(color) {
+                    attacks |= self.attacks_from(square);
+                }
             }
         }
+        attacks
+    }
 
-        // knights
-        for offset in &KNIGHT_OFFSETS {
-            if self.pieces[(index as i8 + offset) as usize]
-                == attacker.map(Piece::W_KNIGHT, Piece::B_KNIGHT)
-            {
-                return true;
-            }
-        }
-
-        // bishops and queens
-        for offset in &BISHOP_OFFSETS {
-            let mut target = (index as i8 + offset) as usize;
-            let mut piece = self.pieces[target];
-            while piece != Piece::OFF_BOARD {
-                if piece != Piece::EMPTY {
-                    if piece == attacker.map(Piece::W_BISHOP, Piece::B_BISHOP)
-                        || piece == attacker.map(Piece::W_QUEEN, Piece::B_QUEEN)
-                    {
-                        return true;
-                    }
-                    break;
+    /// Returns the mobility of a given `Color`, i.e. the number of pseudo-legal moves of all its
+    /// pieces except pawns. Castling is not counted either.
+    ///
+    /// Unlike [`Position::generate_pseudo_legal_moves`] this works for both sides, no matter
+    /// whose turn it is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Color, Position};
+    ///
+    /// let position = Position::new();
+    ///
+    /// // only the knights can move
+    /// assert_eq!(position.mobility(Color::WHITE), 4);
+    /// assert_eq!(position.mobility(Color::BLACK), 4);
+    /// ```
+    pub fn mobility(&self, color: Color) -> i32 {
+        let mut own_pieces = 0;
+        for i in 0..8 {
+            for j in 0..8 {
+                let square = Square::new(File::new(i), Rank::new(j));
+                let piece = self.pieces[square];
+                if piece.is_piece() && piece.is_color(color) {
+                    own_pieces |= 1 << square.to_u8_0_63();
                 }
-                target = (target as i8 + offset) as usize;
-                piece = self.pieces[target];
             }
         }
 
-        // rooks and queens
-        for offset in &ROOK_OFFSETS {
-            let mut target = (index as i8 + offset) as usize;
-            let mut piece = self.pieces[target];
-            while piece != Piece::OFF_BOARD {
-                if piece != Piece::EMPTY {
-                    if piece == attacker.map(Piece::W_ROOK, Piece::B_ROOK)
-                        || piece == attacker.map(Piece::W_QUEEN, Piece::B_QUEEN)
-                    {
-                        return true;
-                    }
-                    break;
+        let mut mobility = 0;
+        for i in 0..8 {
+            for j in 0..8 {
+                let square = Square::new(File::new(i), Rank::new(j));
+                let piece = self.pieces[square];
+                if piece.is_piece() && piece.is_color(color) && !piece.is_type(PieceType::PAWN) {
+                    mobility += (self.attacks_from(square) & !own_pieces).count_ones() as i32;
                 }
-                target = (target as i8 + offset) as usize;
-                piece = self.pieces[target];
             }
         }
+        mobility
+    }
 
-        // king
-        for offset in &KING_OFFSETS {
-            if self.pieces[(index as i8 + offset) as usize]
-                == attacker.map(Piece::W_KING, Piece::B_KING)
-            {
-                return true;
+    /// Returns a bitboard of all squares attacked by the piece on a given `Square`.
+    pub(crate) fn attacks_from(&self, square: Square) -> u64 {
+        let piece = self.pieces[square];
+        let index = square.to_i8();
+        let mut attacks = 0;
+
+        let mut add_steps = |offsets: &[i8]| {
+            for offset in offsets {
+                let target = (index + offset) as usize;
+                if self.pieces[target] != Piece::OFF_BOARD {
+                    attacks |= 1 << Square::from_index(target).to_u8_0_63();
+                }
             }
+        };
+        match piece.piece_type() {
+            PieceType::PAWN => add_steps(
+                &piece
+                    .color()
+                    .map(WHITE_PAWN_CAPTURE_OFFSETS, BLACK_PAWN_CAPTURE_OFFSETS),
+            ),
+            PieceType::KNIGHT => add_steps(&KNIGHT_OFFSETS),
+            PieceType::KING => add_steps(&KING_OFFSETS),
+            _ => {}
         }
 
-        false
+        let mut add_rays = |offsets: &[i8]| {
+            for offset in offsets {
+                let mut target = (index + offset) as usize;
+                let mut p = self.pieces[target];
+                while p != Piece::OFF_BOARD {
+                    attacks |= 1 << Square::from_index(target).to_u8_0_63();
+                    if p != Piece::EMPTY {
+                        break;
+                    }
+                    target = (target as i8 + offset) as usize;
+                    p = self.pieces[target];
+                }
+            }
+        };
+        match piece.piece_type() {
+            PieceType::BISHOP => add_rays(&BISHOP_OFFSETS),
+            PieceType::ROOK => add_rays(&ROOK_OFFSETS),
+            PieceType::QUEEN => {
+                add_rays(&BISHOP_OFFSETS);
+                add_rays(&ROOK_OFFSETS);
+            }
+            _ => {}
+        }
+
+        attacks
     }
 
     /// Returns wether the side to move is in check.
@@ -122,6 +187,424 @@ impl Position {
     pub fn in_check(&self, side: Color) -> bool {
         self.is_attacked(self.king_square[side], !side)
     }
+
+    /// Returns the squares of all enemy pieces giving check to the side to move. There are two
+    /// of them in case of a double check, in which case only the king can move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Position, Square};
+    ///
+    /// let pos = Position::from_fen("4k3/8/3N4/8/8/8/8/4RK2 b - - 0 1").unwrap();
+    ///
+    /// assert_eq!(pos.checkers().as_slice(), [Square::D6, Square::E1]);
+    /// assert!(Position::new().checkers().is_empty());
+    /// ```
+    pub fn checkers(&self) -> ArrayVec<Square, 16> {
+        attackers_on(
+            &self.pieces,
+            self.king_square[self.side_to_move],
+            !self.side_to_move,
+        )
+    }
+
+    /// Returns whether the king of `side` would not be attacked on a given `Square`.
+    ///
+    /// The king is removed from its current square first, so a king can't hide behind itself
+    /// when stepping away from a checking slider along the line of the check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Color, Position, Square};
+    ///
+    /// let pos = Position::from_fen("8/8/8/8/4k3/8/8/K3R3 b - - 0 1").unwrap();
+    ///
+    /// assert!(!pos.is_attacked(Square::E5, Color::WHITE)); // the king blocks the rook
+    /// assert!(!pos.square_safe_for_king(Square::E5, Color::BLACK));
+    /// assert!(pos.square_safe_for_king(Square::D5, Color::BLACK));
+    /// ```
+    pub fn square_safe_for_king(&self, square: Square, side: Color) -> bool {
+        let mut pieces = self.pieces;
+        pieces[self.king_square[side]] = Piece::EMPTY;
+        !is_attacked_on(&pieces, square, !side)
+    }
+
+    /// Returns whether a pseudo-legal move attacks the enemy king after it has been made, be it
+    /// directly, by uncovering a slider (also by an en passant capture), by promoting or by the
+    /// rook of a castling move.
+    ///
+    /// The move is only applied to a copy of the board, which is then checked for attacks on the
+    /// enemy king. This is cheaper than [`Position::make_bit_move`] and
+    /// [`Position::undo_move`] since no state has to be saved and restored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{BitMove, Position, Square};
+    ///
+    /// let pos = Position::from_fen("6k1/8/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    ///
+    /// assert!(pos.gives_check(BitMove::new_quiet(Square::A1, Square::A8)));
+    /// assert!(!pos.gives_check(BitMove::new_quiet(Square::A1, Square::A7)));
+    /// ```
+    pub fn gives_check(&self, m: BitMove) -> bool {
+        let us = self.pieces[m.origin()].color();
+        is_attacked_on(&self.pieces_after(m), self.king_square[!us], us)
+    }
+
+    /// Returns whether a move gives a discovered check, i.e. moving the piece away uncovers an
+    /// attack of another bishop, rook or queen on the enemy king. Direct checks by the moved
+    /// piece are not taken into account.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{BitMove, Position, Square};
+    ///
+    /// let pos = Position::from_fen("8/7k/8/8/8/8/2N5/1B2K3 w - - 0 1").unwrap();
+    ///
+    /// assert!(pos.gives_discovered_check(BitMove::new_quiet(Square::C2, Square::E3)));
+    /// ```
+    pub fn gives_discovered_check(&self, m: BitMove) -> bool {
+        let us = self.pieces[m.origin()].color();
+        let king_square = self.king_square[!us];
+
+        let mut pieces = self.pieces;
+        let vacated = if m.is_en_passant() {
+            Square::new(m.target().file(), m.origin().rank())
+        } else {
+            m.origin()
+        };
+        pieces[m.target()] = pieces[m.origin()];
+        pieces[m.origin()] = Piece::EMPTY;
+        pieces[vacated] = Piece::EMPTY;
+
+        let index = king_square.to_i8();
+        for (offsets, slider) in [
+            (&BISHOP_OFFSETS, PieceType::BISHOP),
+            (&ROOK_OFFSETS, PieceType::ROOK),
+        ] {
+            for offset in offsets {
+                let mut uncovered = false;
+                let mut target = (index + offset) as usize;
+                while pieces[target] == Piece::EMPTY {
+                    uncovered |= target == m.origin().to_usize() || target == vacated.to_usize();
+                    target = (target as i8 + offset) as usize;
+                }
+                let piece = pieces[target];
+                if uncovered
+                    && target != m.target().to_usize()
+                    && piece.is_piece()
+                    && piece.is_color(us)
+                    && (piece.is_type(slider) || piece.is_type(PieceType::QUEEN))
+                {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Returns the squares of all pieces of `color` that are absolutely pinned to their own king,
+    /// i.e. there is exactly one piece of `color` between the king and an enemy bishop, rook or
+    /// queen attacking along that line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Color, Position, Square};
+    ///
+    /// let pos = Position::from_fen("4k3/4r3/8/8/4N3/8/3B4/r3K3 w - - 0 1").unwrap();
+    ///
+    /// assert_eq!(pos.pinned_pieces(Color::WHITE).as_slice(), [Square::E4]);
+    /// assert!(pos.pinned_pieces(Color::BLACK).is_empty());
+    /// ```
+    pub fn pinned_pieces(&self, color: Color) -> ArrayVec<Square, 8> {
+        let index = self.king_square[color].to_i8();
+        let mut pinned = ArrayVec::new();
+
+        for (offsets, slider) in [
+            (&BISHOP_OFFSETS, PieceType::BISHOP),
+            (&ROOK_OFFSETS, PieceType::ROOK),
+        ] {
+            for offset in offsets {
+                let mut target = (index + offset) as usize;
+                while self.pieces[target] == Piece::EMPTY {
+                    target = (target as i8 + offset) as usize;
+                }
+                if !self.pieces[target].is_piece() || !self.pieces[target].is_color(color) {
+                    continue;
+                }
+                let blocker = target;
+
+                target = (target as i8 + offset) as usize;
+                while self.pieces[target] == Piece::EMPTY {
+                    target = (target as i8 + offset) as usize;
+                }
+                let piece = self.pieces[target];
+                if piece.is_piece()
+                    && piece.is_color(!color)
+                    && (piece.is_type(slider) || piece.is_type(PieceType::QUEEN))
+                {
+                    pinned.push(Square::from_index(blocker));
+                }
+            }
+        }
+        pinned
+    }
+
+    /// Returns for every square the number of white attackers minus the number of black
+    /// attackers, counted like in [`Position::control`].
+    ///
+    /// The map is indexed from 0 to 63 where a1 = 0, b1 = 1, ..., h8 = 63.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// let map = Position::new().control_map();
+    ///
+    /// assert_eq!(map[21], 3); // f3: e2, g2 and g1
+    /// assert_eq!(map[28], 0); // e4
+    /// assert_eq!(map[45], -3); // f6: e7, g7 and g8
+    /// ```
+    pub fn control_map(&self) -> [i8; 64] {
+        let mut map = [0; 64];
+        for i in 0..8 {
+            for j in 0..8 {
+                let square = Square::new(File::new(i), Rank::new(j));
+                let piece = self.pieces[square];
+                if piece.is_piece() {
+                    let sign = piece.color().map(1, -1);
+                    let mut attacks = self.attacks_from(square);
+                    while attacks != 0 {
+                        map[attacks.trailing_zeros() as usize] += sign;
+                        attacks &= attacks - 1;
+                    }
+                }
+            }
+        }
+        map
+    }
+
+    /// Returns how many white and black pieces attack a given `Square`, in that order.
+    ///
+    /// Only direct attacks are counted, a piece behind another slider on the same line is not.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Position, Square};
+    ///
+    /// let position = Position::new();
+    ///
+    /// assert_eq!(position.control(Square::F3), (3, 0)); // e2, g2 and g1
+    /// assert_eq!(position.control(Square::E5), (0, 0));
+    /// ```
+    pub fn control(&self, square: Square) -> (u8, u8) {
+        let index = square.to_i8();
+        let mut count = [0; 2];
+        let mut add = |piece: Piece| count[piece.color()] += 1;
+
+        // pawns
+        for offset in &BLACK_PAWN_CAPTURE_OFFSETS {
+            if self.pieces[(index + offset) as usize] == Piece::W_PAWN {
+                add(Piece::W_PAWN);
+            }
+        }
+        for offset in &WHITE_PAWN_CAPTURE_OFFSETS {
+            if self.pieces[(index + offset) as usize] == Piece::B_PAWN {
+                add(Piece::B_PAWN);
+            }
+        }
+
+        // knights and king
+        for offset in &KNIGHT_OFFSETS {
+            let piece = self.pieces[(index + offset) as usize];
+            if piece.is_type(PieceType::KNIGHT) {
+                add(piece);
+            }
+        }
+        for offset in &KING_OFFSETS {
+            let piece = self.pieces[(index + offset) as usize];
+            if piece.is_type(PieceType::KING) {
+                add(piece);
+            }
+        }
+
+        // sliding pieces
+        for (offsets, piece_type) in [
+            (&BISHOP_OFFSETS, PieceType::BISHOP),
+            (&ROOK_OFFSETS, PieceType::ROOK),
+        ] {
+            for offset in offsets {
+                let mut target = (index + offset) as usize;
+                while self.pieces[target] == Piece::EMPTY {
+                    target = (target as i8 + offset) as usize;
+                }
+                let piece = self.pieces[target];
+                if piece.is_type(piece_type) || piece.is_type(PieceType::QUEEN) {
+                    add(piece);
+                }
+            }
+        }
+
+        (count[Color::WHITE], count[Color::BLACK])
+    }
+}
+
+/// Returns wether a given `Square` is attacked by any piece of a given `Color` on a board.
+#[inline]
+pub(crate) fn is_attacked_on(pieces: &[Piece; 120], square: Square, attacker: Color) -> bool {
+    if attacker == Color::WHITE {
+        is_attacked_by::<true>(pieces, square)
+    } else {
+        is_attacked_by::<false>(pieces, square)
+    }
+}
+
+/// Specialized for the attacking color at compile time so the pieces to look for don't have to be
+/// selected in the loops.
+#[inline(always)]
+fn is_attacked_by<const WHITE: bool>(pieces: &[Piece; 120], square: Square) -> bool {
+    let (pawn_offsets, pawn, knight, bishop, rook, queen, king) = if WHITE {
+        (
+            BLACK_PAWN_CAPTURE_OFFSETS,
+            Piece::W_PAWN,
+            Piece::W_KNIGHT,
+            Piece::W_BISHOP,
+            Piece::W_ROOK,
+            Piece::W_QUEEN,
+            Piece::W_KING,
+        )
+    } else {
+        (
+            WHITE_PAWN_CAPTURE_OFFSETS,
+            Piece::B_PAWN,
+            Piece::B_KNIGHT,
+            Piece::B_BISHOP,
+            Piece::B_ROOK,
+            Piece::B_QUEEN,
+            Piece::B_KING,
+        )
+    };
+    let index = square.to_usize();
+
+    // pawns
+    for offset in &pawn_offsets {
+        if pieces[(index as i8 + offset) as usize] == pawn {
+            return true;
+        }
+    }
+
+    // knights
+    for offset in &KNIGHT_OFFSETS {
+        if pieces[(index as i8 + offset) as usize] == knight {
+            return true;
+        }
+    }
+
+    // bishops and queens
+    for offset in &BISHOP_OFFSETS {
+        let mut target = (index as i8 + offset) as usize;
+        let mut piece = pieces[target];
+        while piece != Piece::OFF_BOARD {
+            if piece != Piece::EMPTY {
+                if piece == bishop || piece == queen {
+                    return true;
+                }
+                break;
+            }
+            target = (target as i8 + offset) as usize;
+            piece = pieces[target];
+        }
+    }
+
+    // rooks and queens
+    for offset in &ROOK_OFFSETS {
+        let mut target = (index as i8 + offset) as usize;
+        let mut piece = pieces[target];
+        while piece != Piece::OFF_BOARD {
+            if piece != Piece::EMPTY {
+                if piece == rook || piece == queen {
+                    return true;
+                }
+                break;
+            }
+            target = (target as i8 + offset) as usize;
+            piece = pieces[target];
+        }
+    }
+
+    // king
+    for offset in &KING_OFFSETS {
+        if pieces[(index as i8 + offset) as usize] == king {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Returns the squares of all pieces of a given `Color` that attack a given `Square` on a board.
+///
+/// Works like [`is_attacked_on`] but collects the attackers instead of stopping at the first one.
+/// At most one piece can attack along each of the eight lines and from each of the eight knight
+/// squares, so there can't be more than 16 attackers.
+pub(crate) fn attackers_on(
+    pieces: &[Piece; 120],
+    square: Square,
+    attacker: Color,
+) -> ArrayVec<Square, 16> {
+    let pawn_offsets = attacker.map(BLACK_PAWN_CAPTURE_OFFSETS, WHITE_PAWN_CAPTURE_OFFSETS);
+    let piece = |piece_type| Piece::new(piece_type, attacker);
+    let index = square.to_i8();
+    let mut attackers = ArrayVec::new();
+
+    // pawns
+    for offset in &pawn_offsets {
+        let target = (index + offset) as usize;
+        if pieces[target] == piece(PieceType::PAWN) {
+            attackers.push(Square::from_index(target));
+        }
+    }
+
+    // knights
+    for offset in &KNIGHT_OFFSETS {
+        let target = (index + offset) as usize;
+        if pieces[target] == piece(PieceType::KNIGHT) {
+            attackers.push(Square::from_index(target));
+        }
+    }
+
+    // sliding pieces
+    for (offsets, slider) in [
+        (&BISHOP_OFFSETS, PieceType::BISHOP),
+        (&ROOK_OFFSETS, PieceType::ROOK),
+    ] {
+        for offset in offsets {
+            let mut target = (index + offset) as usize;
+            while pieces[target] == Piece::EMPTY {
+                target = (target as i8 + offset) as usize;
+            }
+            if pieces[target] == piece(slider) || pieces[target] == piece(PieceType::QUEEN) {
+                attackers.push(Square::from_index(target));
+            }
+        }
+    }
+
+    // king
+    for offset in &KING_OFFSETS {
+        let target = (index + offset) as usize;
+        if pieces[target] == piece(PieceType::KING) {
+            attackers.push(Square::from_index(target));
+        }
+    }
+
+    attackers
 }
 
 #[cfg(test)]
@@ -129,15 +612,14 @@ mod tests {
     use test_case::test_case;
 
     use super::*;
-    use crate::File;
-    use crate::Rank;
 
+    use crate::fen;
     use crate::utils;
 
-    #[test_case(utils::fen::STARTING_POSITION, Color::WHITE, &[Square::A3, Square::B3, Square::C3, Square::D3, Square::E3, Square::F3, Square::G3, Square::H3, Square::A2, Square::B2, Square::C2, Square::D2, Square::E2, Square::F2, Square::G2, Square::H2, Square::B1, Square::C1, Square::D1, Square::E1, Square::F1, Square::G1]; "starting position white")]
-    #[test_case(utils::fen::STARTING_POSITION, Color::BLACK, &[Square::A6, Square::B6, Square::C6, Square::D6, Square::E6, Square::F6, Square::G6, Square::H6, Square::A7, Square::B7, Square::C7, Square::D7, Square::E7, Square::F7, Square::G7, Square::H7, Square::B8, Square::C8, Square::D8, Square::E8, Square::F8, Square::G8]; "starting position black")]
-    #[test_case(utils::fen::KIWIPETE, Color::WHITE, &[Square::B1, Square::C1, Square::D1, Square::E1, Square::F1, Square::G1, Square::A2, Square::D2, Square::E2, Square::F2, Square::G2, Square::H2, Square::A3, Square::B3, Square::C3, Square::D3, Square::E3, Square::F3, Square::G3, Square::H3, Square::A4, Square::C4, Square::E4, Square::F4, Square::G4, Square::B5, Square::D5, Square::F5, Square::A6, Square::C6, Square::E6, Square::F6, Square::G5, Square::H5, Square::G6, Square::H6, Square::D7, Square::F7]; "kiwipete white")]
-    #[test_case(utils::fen::KIWIPETE, Color::BLACK, &[Square::E2, Square::G2, Square::A3, Square::C3, Square::D3, Square::H3, Square::A4, Square::B4, Square::C4, Square::E4, Square::G4, Square::H4, Square::B5, Square::C5, Square::D5, Square::F5, Square::H5, Square::B6, Square::C6, Square::D6, Square::E6, Square::F6, Square::G6, Square::H6, Square::A7, Square::B7, Square::D7, Square::E7, Square::F7, Square::H7, Square::A8, Square::B8, Square::C8, Square::D8, Square::E8, Square::F8, Square::G8, Square::H8]; "kiwipete black")]
+    #[test_case(fen::STARTING_FEN, Color::WHITE, &[Square::A3, Square::B3, Square::C3, Square::D3, Square::E3, Square::F3, Square::G3, Square::H3, Square::A2, Square::B2, Square::C2, Square::D2, Square::E2, Square::F2, Square::G2, Square::H2, Square::B1, Square::C1, Square::D1, Square::E1, Square::F1, Square::G1]; "starting position white")]
+    #[test_case(fen::STARTING_FEN, Color::BLACK, &[Square::A6, Square::B6, Square::C6, Square::D6, Square::E6, Square::F6, Square::G6, Square::H6, Square::A7, Square::B7, Square::C7, Square::D7, Square::E7, Square::F7, Square::G7, Square::H7, Square::B8, Square::C8, Square::D8, Square::E8, Square::F8, Square::G8]; "starting position black")]
+    #[test_case(fen::KIWIPETE, Color::WHITE, &[Square::B1, Square::C1, Square::D1, Square::E1, Square::F1, Square::G1, Square::A2, Square::D2, Square::E2, Square::F2, Square::G2, Square::H2, Square::A3, Square::B3, Square::C3, Square::D3, Square::E3, Square::F3, Square::G3, Square::H3, Square::A4, Square::C4, Square::E4, Square::F4, Square::G4, Square::B5, Square::D5, Square::F5, Square::A6, Square::C6, Square::E6, Square::F6, Square::G5, Square::H5, Square::G6, Square::H6, Square::D7, Square::F7]; "kiwipete white")]
+    #[test_case(fen::KIWIPETE, Color::BLACK, &[Square::E2, Square::G2, Square::A3, Square::C3, Square::D3, Square::H3, Square::A4, Square::B4, Square::C4, Square::E4, Square::G4, Square::H4, Square::B5, Square::C5, Square::D5, Square::F5, Square::H5, Square::B6, Square::C6, Square::D6, Square::E6, Square::F6, Square::G6, Square::H6, Square::A7, Square::B7, Square::D7, Square::E7, Square::F7, Square::H7, Square::A8, Square::B8, Square::C8, Square::D8, Square::E8, Square::F8, Square::G8, Square::H8]; "kiwipete black")]
     fn test_position_is_attacked(fen: &str, color: Color, expected_squares: &[Square]) {
         let position = Position::from_fen(fen).expect("valid position");
         for i in 0..8 {
@@ -153,4 +635,226 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_position_attacked_squares_starting_position() {
+        let position = Position::new();
+        // second and third rank and b1 to g1
+        let expected = 0x0000_0000_00ff_ff7e;
+
+        pretty_assertions::assert_eq!(position.attacked_squares(Color::WHITE), expected);
+    }
+
+    #[test_case(fen::STARTING_FEN; "starting position")]
+    #[test_case(fen::KIWIPETE; "kiwipete")]
+    #[test_case("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1"; "position 4")]
+    fn test_position_attacked_squares(fen: &str) {
+        let position = Position::from_fen(fen).expect("valid position");
+        for color in Color::ALL {
+            let attacks = position.attacked_squares(color);
+            for i in 0..8 {
+                for j in 0..8 {
+                    let square = Square::new(File::new(i), Rank::new(j));
+                    pretty_assertions::assert_eq!(
+                        attacks >> square.to_u8_0_63() & 1 == 1,
+                        position.is_attacked(square, color),
+                        "Failed at {:?}",
+                        square
+                    );
+                }
+            }
+        }
+    }
+
+    #[test_case(fen::STARTING_FEN; "starting position")]
+    #[test_case(fen::KIWIPETE; "kiwipete")]
+    #[test_case("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1"; "position 4")]
+    fn test_position_mobility(fen: &str) {
+        let position = Position::from_fen(fen).expect("valid position");
+        for color in Color::ALL {
+            let flipped;
+            let pos = if position.side_to_move == color {
+                &position
+            } else {
+                flipped = position.with_flipped_side();
+                &flipped
+            };
+            let expected = pos
+                .generate_pseudo_legal_moves(false)
+                .iter()
+                .filter(|m| !pos.pieces[m.origin()].is_type(PieceType::PAWN) && !m.is_castle())
+                .count();
+
+            pretty_assertions::assert_eq!(position.mobility(color), expected as i32);
+        }
+    }
+
+    #[test_case(fen::STARTING_FEN, Square::F3, (3, 0); "starting position f3")]
+    #[test_case(fen::STARTING_FEN, Square::D4, (0, 0); "starting position d4")]
+    #[test_case("4k3/pp3ppp/3p4/3N4/2P1P3/8/PP3PPP/4K3 w - - 0 1", Square::D5, (2, 0); "knight outpost")]
+    #[test_case("3rk3/8/8/3p4/4P3/8/3R4/3QK3 w - - 0 1", Square::D5, (2, 1); "sliders and pawns")]
+    fn test_position_control(fen: &str, square: Square, expected: (u8, u8)) {
+        let position = Position::from_fen(fen).expect("valid position");
+
+        pretty_assertions::assert_eq!(position.control(square), expected);
+    }
+
+    #[test_case(fen::STARTING_FEN, &[(Square::D3, 2), (Square::E4, 0), (Square::D6, -2)]; "starting position")]
+    #[test_case("3rk3/8/8/3p4/4P3/8/3R4/3QK3 w - - 0 1", &[(Square::D5, 1), (Square::D4, 1), (Square::E5, 0)]; "sliders and pawns")]
+    fn test_position_control_map(fen: &str, expected: &[(Square, i8)]) {
+        let position = Position::from_fen(fen).expect("valid position");
+        let map = position.control_map();
+
+        for (square, value) in expected {
+            pretty_assertions::assert_eq!(map[square.to_u8_0_63() as usize], *value, "{}", square);
+        }
+    }
+
+    #[test_case(fen::STARTING_FEN; "starting position")]
+    #[test_case(fen::KIWIPETE; "kiwipete")]
+    fn test_position_control_map_matches_control(fen: &str) {
+        let position = Position::from_fen(fen).expect("valid position");
+        let map = position.control_map();
+
+        for i in 0..8 {
+            for j in 0..8 {
+                let square = Square::new(File::new(i), Rank::new(j));
+                let (white, black) = position.control(square);
+                pretty_assertions::assert_eq!(
+                    map[square.to_u8_0_63() as usize],
+                    white as i8 - black as i8,
+                    "{}",
+                    square
+                );
+            }
+        }
+    }
+
+    #[test_case("8/7k/8/8/8/8/2N5/1B2K3 w - - 0 1", "c2e3", true; "knight uncovers bishop")]
+    #[test_case("8/7k/8/8/8/8/2N5/1B2K3 w - - 0 1", "c2d4", true; "knight uncovers bishop other square")]
+    #[test_case("8/7k/8/8/8/8/2Q5/1B2K3 w - - 0 1", "c2d3", false; "piece stays on the line")]
+    #[test_case("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "a1a8", false; "direct check")]
+    #[test_case("4k3/8/8/8/4N3/8/8/4RK2 w - - 0 1", "e4f6", true; "rook behind knight with check")]
+    #[test_case("4k3/8/8/8/4N3/8/8/4BK2 w - - 0 1", "e4f6", false; "wrong slider")]
+    #[test_case("8/8/8/K2pP2k/8/8/8/8 w - d6 0 1", "e5d6", false; "en passant without slider")]
+    #[test_case("8/8/8/1k1pP2Q/8/8/8/4K3 w - d6 0 1", "e5d6", true; "en passant uncovers queen")]
+    #[test_case("8/8/8/1k1pP2Q/8/8/8/4K3 w - - 0 1", "e5e6", false; "pawn push keeps blocking")]
+    fn test_position_gives_discovered_check(fen: &str, m: &str, expected: bool) {
+        let mut position = Position::from_fen(fen).expect("valid position");
+        let m = position
+            .find_move(m.parse().expect("valid move"))
+            .expect("legal move");
+
+        pretty_assertions::assert_eq!(position.gives_discovered_check(m), expected);
+    }
+
+    #[test_case("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "a1a8", true; "direct check")]
+    #[test_case("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "a1a7", false; "no check")]
+    #[test_case("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1", "d4f5", false; "knight next to the king")]
+    #[test_case("4k3/8/8/8/4N3/8/8/4K3 w - - 0 1", "e4d6", true; "knight check")]
+    #[test_case("8/7k/8/8/8/8/2N5/1B2K3 w - - 0 1", "c2e3", true; "discovered check")]
+    #[test_case("4k3/8/8/8/4N3/8/8/4RK2 w - - 0 1", "e4f6", true; "double check")]
+    #[test_case("8/8/8/1k1pP2Q/8/8/8/4K3 w - d6 0 1", "e5d6", true; "en passant discovered check")]
+    #[test_case("8/8/2k5/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6", false; "en passant next to the king")]
+    #[test_case("8/8/4k3/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6", false; "en passant pawn protects the king diagonally")]
+    #[test_case("3k4/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8q", true; "promotion check")]
+    #[test_case("3k4/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8n", false; "underpromotion without check")]
+    #[test_case("8/1P6/4k3/8/8/8/8/4K3 w - - 0 1", "b7b8b", false; "bishop promotion without check")]
+    #[test_case("5k2/8/8/8/8/8/8/4K2R w K - 0 1", "e1g1", true; "castling check")]
+    fn test_position_gives_check(fen: &str, m: &str, expected: bool) {
+        let mut position = Position::from_fen(fen).expect("valid position");
+        let m = position
+            .find_move(m.parse().expect("valid move"))
+            .expect("legal move");
+
+        pretty_assertions::assert_eq!(position.gives_check(m), expected);
+    }
+
+    #[test]
+    fn test_position_gives_check_random_positions() {
+        for fen in utils::fen::RANDOM_FENS {
+            let mut pos = Position::from_fen(fen).expect("valid position");
+            for m in pos.generate_legal_moves() {
+                let gives_check = pos.gives_check(m);
+                pos.make_bit_move(m);
+                pretty_assertions::assert_eq!(gives_check, pos.is_check(), "{} {}", fen, m);
+                pos.undo_move();
+            }
+        }
+    }
+
+    #[test_case("4k3/8/8/8/8/8/4N3/r3K2r w - - 0 1", Color::WHITE, &[]; "no pins")]
+    #[test_case("4k3/4r3/8/8/4N3/8/8/4K3 w - - 0 1", Color::WHITE, &[Square::E4]; "file")]
+    #[test_case("4k3/8/8/8/8/8/8/r1N1K3 w - - 0 1", Color::WHITE, &[Square::C1]; "rank")]
+    #[test_case("4k3/8/8/b7/8/8/3P4/4K3 w - - 0 1", Color::WHITE, &[Square::D2]; "diagonal")]
+    #[test_case("4k3/8/8/8/7q/8/5R2/4K3 w - - 0 1", Color::WHITE, &[Square::F2]; "anti-diagonal")]
+    #[test_case("4k3/4r3/8/4P3/4N3/8/8/4K3 w - - 0 1", Color::WHITE, &[]; "two blockers")]
+    #[test_case("4k3/4r3/8/4p3/4N3/8/8/4K3 w - - 0 1", Color::WHITE, &[]; "enemy blocker")]
+    #[test_case("4k3/4b3/8/8/4N3/8/8/4K3 w - - 0 1", Color::WHITE, &[]; "wrong slider")]
+    #[test_case("4k3/4q3/8/8/4N3/8/3B4/r3K2q w - - 0 1", Color::WHITE, &[Square::E4]; "only own pieces")]
+    #[test_case("4k3/3n4/2B5/8/8/8/8/4K3 b - - 0 1", Color::BLACK, &[Square::D7]; "black")]
+    #[test_case("q3r3/1P2R3/1k6/r1N1K1Br/8/2N3N1/4Q2b/b3r2q w - - 0 1", Color::WHITE, &[Square::C5, Square::G5, Square::E7, Square::G3, Square::C3, Square::E2]; "all directions")]
+    fn test_position_pinned_pieces(fen: &str, color: Color, expected: &[Square]) {
+        let position = Position::from_fen(fen).expect("valid position");
+        let mut pinned = position.pinned_pieces(color).to_vec();
+        pinned.sort();
+
+        let mut expected = expected.to_vec();
+        expected.sort();
+        pretty_assertions::assert_eq!(pinned, expected);
+    }
+
+    #[test_case(fen::STARTING_FEN, &[]; "starting position")]
+    #[test_case(fen::KIWIPETE, &[]; "kiwipete")]
+    #[test_case("4k3/8/3N4/8/8/8/8/4K3 b - - 0 1", &[Square::D6]; "knight")]
+    #[test_case("4k3/3P4/8/8/8/8/8/4K3 b - - 0 1", &[Square::D7]; "pawn")]
+    #[test_case("4k3/8/8/8/8/8/8/4RK2 b - - 0 1", &[Square::E1]; "rook")]
+    #[test_case("4k3/8/8/8/Q7/8/8/4K3 b - - 0 1", &[Square::A4]; "queen")]
+    #[test_case("4k3/8/8/8/8/8/8/4K3 w - - 0 1", &[]; "kings only")]
+    #[test_case("4k3/8/8/8/B7/8/8/4RK2 b - - 0 1", &[Square::A4, Square::E1]; "rook and bishop")]
+    #[test_case("4k3/8/3N4/8/8/8/8/4RK2 b - - 0 1", &[Square::D6, Square::E1]; "rook and knight")]
+    #[test_case("4k3/4p3/8/8/B7/8/8/4RK2 b - - 0 1", &[Square::A4]; "blocked rook")]
+    #[test_case("4k3/8/8/8/8/8/8/r3K3 w - - 0 1", &[Square::A1]; "white in check")]
+    fn test_position_checkers(fen: &str, expected: &[Square]) {
+        let position = Position::from_fen(fen).expect("valid position");
+        let mut checkers = position.checkers().to_vec();
+        checkers.sort();
+
+        let mut expected = expected.to_vec();
+        expected.sort();
+        pretty_assertions::assert_eq!(checkers, expected);
+    }
+
+    #[test]
+    fn test_position_checkers_random_positions() {
+        for fen in utils::fen::RANDOM_FENS {
+            let position = Position::from_fen(fen).expect("valid position");
+            pretty_assertions::assert_eq!(
+                !position.checkers().is_empty(),
+                position.is_check(),
+                "{}",
+                fen
+            );
+        }
+    }
+
+    #[test_case("8/8/8/8/4k3/8/8/K3R3 b - - 0 1", Color::BLACK, &[Square::D3, Square::D4, Square::D5, Square::F3, Square::F4, Square::F5]; "rook check")]
+    #[test_case("8/8/8/8/4k3/8/8/K6B b - - 0 1", Color::BLACK, &[Square::D3, Square::D4, Square::E3, Square::E5, Square::F4, Square::F5]; "bishop check")]
+    #[test_case("K7/8/8/8/8/8/8/r6k w - - 0 1", Color::WHITE, &[Square::B8, Square::B7]; "king in the corner")]
+    fn test_position_square_safe_for_king(fen: &str, side: Color, expected: &[Square]) {
+        let position = Position::from_fen(fen).expect("valid position");
+        let king = position.king_square[side];
+        let mut safe: Vec<Square> = KING_OFFSETS
+            .iter()
+            .map(|offset| (king.to_i8() + offset) as usize)
+            .filter(|&i| position.pieces[i] != Piece::OFF_BOARD)
+            .map(Square::from_index)
+            .filter(|&s| position.square_safe_for_king(s, side))
+            .collect();
+        safe.sort();
+
+        let mut expected = expected.to_vec();
+        expected.sort();
+        pretty_assertions::assert_eq!(safe, expected);
+    }
 }
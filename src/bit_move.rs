@@ -9,10 +9,26 @@ use crate::Square;
 /// Additional information for a move.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum MoveFlags {
-    Promotion { capture: bool, piece: PieceType },
-    Capture { en_passant: bool },
+    /// A pawn promotion.
+    Promotion {
+        /// Whether the pawn captures a piece while promoting.
+        capture: bool,
+        /// The piece the pawn is promoted to.
+        piece: PieceType,
+    },
+    /// A capture.
+    Capture {
+        /// Whether the capture is en passant.
+        en_passant: bool,
+    },
+    /// A pawn moving two squares forward from its starting rank.
     DoublePawnPush,
-    Castle { kingside: bool },
+    /// A castle.
+    Castle {
+        /// Whether the king castles to the king side.
+        kingside: bool,
+    },
+    /// Any other move.
     QuietMove,
 }
 
@@ -41,16 +57,37 @@ pub enum MoveFlags {
 /// |  15  |     1     |    1    |     1     |     1     | queen-promo capture  |
 ///
 /// The advantage of this flags is that we can order the moves in a reasonable way simply by
-/// interpreting the move as a 16-bit number(promotion > captures > quiet).
-#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+/// comparing the flags (promotion > captures > quiet), see [`BitMove::order_key`].
+///
+/// The bits of the word are laid out as follows, where squares are stored as their index in the
+/// range `0..64` (a1 = 0, b1 = 1, ..., h8 = 63). This layout is stable, so the raw value can be
+/// stored and read back with [`BitMove::to_u16`] and [`BitMove::from_u16`].
+///
+/// | bits  | content       |
+/// |-------|---------------|
+/// | 0-5   | origin square |
+/// | 6-11  | target square |
+/// | 12-15 | flags         |
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
 pub struct BitMove(u16);
 
 impl BitMove {
     /// Null move.
     ///
+    /// The null move has no meaning as a board move, it is only used as a sentinel, e.g. for
+    /// the previous move of a position without history.
+    ///
     /// # Saftey
     ///
     /// This move should never be played.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::BitMove;
+    ///
+    /// assert!(BitMove::NULL.is_null());
+    /// ```
     pub const NULL: Self = Self(0);
 
     const QUIET: u16 = 0;
@@ -82,6 +119,46 @@ impl BitMove {
         Self::from_flag_bits(origin, target, flag_bits)
     }
 
+    /// Returns the raw 16 bit representation of the move, see [`BitMove`] for the layout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{BitMove, Square};
+    ///
+    /// let m = BitMove::new_pawn_push(Square::E2, Square::E4);
+    ///
+    /// assert_eq!(m.to_u16(), 0b0001_011100_001100);
+    /// assert_eq!(BitMove::from_u16(m.to_u16()), m);
+    /// ```
+    #[inline]
+    pub fn to_u16(self) -> u16 {
+        self.0
+    }
+
+    /// Creates a `BitMove` from its raw 16 bit representation as returned by
+    /// [`BitMove::to_u16`].
+    ///
+    /// # Panics
+    ///
+    /// In debug builds this panics if the flags are not one of the combinations listed in the
+    /// table of [`BitMove`] (i.e. they are 6 or 7).
+    #[inline]
+    pub fn from_u16(raw: u16) -> Self {
+        debug_assert!(
+            !matches!(raw >> 12, 6 | 7),
+            "invalid move flags {:04b}",
+            raw >> 12
+        );
+        Self(raw)
+    }
+
+    /// Returns `true` if the move is the [`BitMove::NULL`] sentinel.
+    #[inline]
+    pub fn is_null(self) -> bool {
+        self == Self::NULL
+    }
+
     /// Creates a new quiet move (i.e no capture, promotion, castle, or double pawn push).
     #[inline]
     pub fn new_quiet(origin: Square, target: Square) -> Self {
@@ -196,6 +273,28 @@ impl BitMove {
         self.0 >> 12
     }
 
+    /// Returns a key for move ordering that only depends on the kind of move, not on the squares.
+    ///
+    /// Promotions have the highest keys (queen promotions first), followed by captures, castling,
+    /// double pawn pushes and quiet moves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{BitMove, PieceType, Square};
+    ///
+    /// let quiet = BitMove::new_quiet(Square::H8, Square::H7);
+    /// let capture = BitMove::new_capture(Square::A1, Square::A2);
+    /// let promotion = BitMove::new_promotion(Square::A7, Square::A8, PieceType::QUEEN);
+    ///
+    /// assert!(quiet.order_key() < capture.order_key());
+    /// assert!(capture.order_key() < promotion.order_key());
+    /// ```
+    #[inline]
+    pub fn order_key(self) -> u8 {
+        self.flags() as u8
+    }
+
     /// Returns if the move is a capture.
     #[inline]
     pub fn is_capture(self) -> bool {
@@ -255,20 +354,54 @@ impl BitMove {
         Self::piece_from_code(self.flags() & 0b0011)
     }
 
-    // TODO: move_type
+    /// Returns the kind of the move as [`MoveFlags`], i.e. the flags the move was created with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{BitMove, MoveFlags, PieceType, Square};
+    ///
+    /// let m = BitMove::new_promotion_capture(Square::B7, Square::A8, PieceType::KNIGHT);
+    ///
+    /// match m.move_type() {
+    ///     MoveFlags::Promotion { capture, piece } => {
+    ///         assert!(capture);
+    ///         assert_eq!(piece, PieceType::KNIGHT);
+    ///     }
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    #[inline]
+    pub fn move_type(self) -> MoveFlags {
+        match self.flags() {
+            Self::QUIET => MoveFlags::QuietMove,
+            Self::DOUBLE_PAWN_PUSH => MoveFlags::DoublePawnPush,
+            Self::KING_SIDE_CASTLE => MoveFlags::Castle { kingside: true },
+            Self::QUEEN_SIDE_CASTLE => MoveFlags::Castle { kingside: false },
+            Self::CAPTURE => MoveFlags::Capture { en_passant: false },
+            Self::EN_PASSANT => MoveFlags::Capture { en_passant: true },
+            _ => MoveFlags::Promotion {
+                capture: self.is_capture(),
+                piece: self.promotion_piece(),
+            },
+        }
+    }
 }
 
 impl PartialEq<ParsedMove> for BitMove {
     fn eq(&self, other: &ParsedMove) -> bool {
-        let promotion_piece = if self.is_promotion() {
-            Some(self.promotion_piece())
+        ParsedMove::from(*self) == *other
+    }
+}
+
+impl From<BitMove> for ParsedMove {
+    fn from(m: BitMove) -> Self {
+        let promotion_piece = if m.is_promotion() {
+            Some(m.promotion_piece())
         } else {
             None
         };
-
-        self.origin() == other.origin
-            && self.target() == other.target
-            && promotion_piece == other.promotion_piece
+        Self::new(m.origin(), m.target(), promotion_piece)
     }
 }
 
@@ -287,6 +420,7 @@ mod tests {
     use test_case::test_case;
 
     use super::*;
+    use crate::Position;
 
     use MoveFlags::*;
 
@@ -297,6 +431,7 @@ mod tests {
     #[test_case(Square::F7, Square::F8, Promotion { piece: PieceType::BISHOP, capture: false })]
     #[test_case(Square::F7, Square::F8, Promotion { piece: PieceType::QUEEN, capture: false })]
     #[test_case(Square::F7, Square::G8, Promotion { piece: PieceType::QUEEN, capture: true })]
+    #[test_case(Square::F7, Square::E8, Promotion { piece: PieceType::KNIGHT, capture: true })]
     #[test_case(Square::C5, Square::D4, Capture { en_passant: false })]
     #[test_case(Square::D4, Square::C3, Capture { en_passant: true })]
     #[test_case(Square::E1, Square::G1, Castle { kingside: true })]
@@ -306,6 +441,8 @@ mod tests {
     fn bitmove_new(origin: Square, target: Square, flags: MoveFlags) {
         let bm = BitMove::new(origin, target, flags);
 
+        assert_eq!(BitMove::from_u16(bm.to_u16()), bm);
+        assert_eq!(flags, bm.move_type());
         assert_eq!(origin, bm.origin());
         assert_eq!(target, bm.target());
 
@@ -354,6 +491,25 @@ mod tests {
         assert_eq!(double_push, bm.is_double_push());
     }
 
+    #[test_case("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1g1"; "castle")]
+    #[test_case("8/1P6/8/8/8/8/8/k1K5 w - - 0 1", "b7b8n"; "promotion")]
+    #[test_case("r1n5/1P6/8/8/8/8/8/k1K5 w - - 0 1", "b7a8q"; "promotion capture")]
+    #[test_case("8/8/8/3pP3/8/8/8/k1K5 w - d6 0 1", "e5d6"; "en passant")]
+    fn parsedmove_from_bitmove(fen: &str, m: &str) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let parsed = ParsedMove::from_coordinate_notation(m).expect("valid move");
+        let bm = pos.find_move(parsed).expect("legal move");
+
+        assert_eq!(ParsedMove::from(bm), parsed);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn bitmove_from_u16_invalid_flags() {
+        BitMove::from_u16(0b0110 << 12);
+    }
+
     #[test]
     fn bitmove_new_quiet() {
         let expected = BitMove::new(Square::E2, Square::E3, QuietMove);
@@ -427,4 +583,18 @@ mod tests {
             BitMove::new_castle_queenside(Square::E1, Square::C1)
         );
     }
+
+    #[test]
+    fn bitmove_order_key() {
+        let quiet = BitMove::new_quiet(Square::H8, Square::H7);
+        let capture = BitMove::new_capture(Square::A1, Square::A2);
+        let promotion = BitMove::new_promotion(Square::A7, Square::A8, PieceType::KNIGHT);
+        let promotion_capture =
+            BitMove::new_promotion_capture(Square::A7, Square::B8, PieceType::QUEEN);
+
+        let mut moves = vec![quiet, capture, promotion_capture, promotion];
+        moves.sort_by_key(|m| std::cmp::Reverse(m.order_key()));
+
+        assert_eq!(moves, [promotion_capture, promotion, capture, quiet]);
+    }
 }
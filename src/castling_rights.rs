@@ -1,9 +1,12 @@
 use std::fmt;
 
+use crate::Color;
+use crate::File;
+use crate::Rank;
 use crate::Square;
 
-/// Compressed representation of the castling_rights of both players in just 4 bits. This way they
-/// can be easily updated with a castle mask.
+/// The castling rights of both players. The rights themselves are stored in just 4 bits, this way
+/// they can be easily compared and hashed.
 ///
 /// | bin  | dec | castling rights                         |
 /// |------|-----|-----------------------------------------|
@@ -12,23 +15,99 @@ use crate::Square;
 /// | 0100 |  4  | black king can castle to the king side  |
 /// | 1000 |  8  | black king can castle to the queen side |
 ///
-#[derive(Debug, PartialEq, Clone, Copy)]
-pub struct CastlingRights(u8);
+/// To support [Chess960] the files of the kings and the castling rooks are stored as well. They
+/// are the standard ones (e, h and a) for every right that isn't held, so two `CastlingRights`
+/// only differ if a player could castle differently.
+///
+/// [Chess960]: https://en.wikipedia.org/wiki/Fischer_random_chess
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct CastlingRights {
+    rights: u8,
+    king_files: [u8; 2],
+    rook_files: [u8; 4],
+}
 
 impl CastlingRights {
-    /// Creates a new `CastlingRights`.
+    const STANDARD_ROOK_FILES: [u8; 4] = [7, 0, 7, 0];
+    const STANDARD_KING_FILE: u8 = 4;
+
+    /// Creates a new `CastlingRights` with the kings and rooks on their standard files.
     pub fn new(
         white_king_side: bool,
         white_queen_side: bool,
         black_king_side: bool,
         black_queen_side: bool,
     ) -> Self {
-        Self(
-            white_king_side as u8
+        Self {
+            rights: white_king_side as u8
                 | (white_queen_side as u8) << 1
                 | (black_king_side as u8) << 2
                 | (black_queen_side as u8) << 3,
-        )
+            king_files: [Self::STANDARD_KING_FILE; 2],
+            rook_files: Self::STANDARD_ROOK_FILES,
+        }
+    }
+
+    /// Returns the castling rights with the additional right of `color` to castle with the king
+    /// on `king_file` and the rook on `rook_file`. Castling is to the king side if the rook is on
+    /// a higher file than the king and to the queen side otherwise.
+    ///
+    /// This is needed for [Chess960], where the king and rooks can start on any file.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `color` already has a castling right with the king on a different file or if
+    /// `king_file` and `rook_file` are equal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{CastlingRights, Color, File};
+    ///
+    /// let castling_rights = CastlingRights::new(false, false, false, false)
+    ///     .with_rook(Color::WHITE, File::B, File::G)
+    ///     .with_rook(Color::BLACK, File::E, File::A);
+    ///
+    /// assert!(castling_rights.white_king_side());
+    /// assert!(castling_rights.black_queen_side());
+    /// assert_eq!(castling_rights.to_string(), "Gq");
+    /// ```
+    ///
+    /// [Chess960]: https://en.wikipedia.org/wiki/Fischer_random_chess
+    pub fn with_rook(mut self, color: Color, king_file: File, rook_file: File) -> Self {
+        assert!(
+            king_file != rook_file,
+            "the king and rook must be on different files"
+        );
+        let kingside = rook_file.to_u8() > king_file.to_u8();
+        assert!(
+            !self.has_right(color, !kingside)
+                || self.king_files[color.to_usize()] == king_file.to_u8(),
+            "the king can't be on two files"
+        );
+
+        let index = Self::index(color, kingside);
+        self.rights |= 1 << index;
+        self.king_files[color.to_usize()] = king_file.to_u8();
+        self.rook_files[index] = rook_file.to_u8();
+        self
+    }
+
+    /// Returns the index of the bit of a castling right.
+    #[inline]
+    fn index(color: Color, kingside: bool) -> usize {
+        color.to_usize() * 2 + !kingside as usize
+    }
+
+    /// Removes a castling right and resets its files to the standard ones.
+    #[inline]
+    fn remove(&mut self, color: Color, kingside: bool) {
+        let index = Self::index(color, kingside);
+        self.rights &= !(1 << index);
+        self.rook_files[index] = Self::STANDARD_ROOK_FILES[index];
+        if !self.has_right(color, !kingside) {
+            self.king_files[color.to_usize()] = Self::STANDARD_KING_FILE;
+        }
     }
 }
 
@@ -36,43 +115,93 @@ impl CastlingRights {
     /// Returns wether the white king can castle kingside.
     #[inline]
     pub fn white_king_side(self) -> bool {
-        self.0 & 1 != 0
+        self.rights & 1 != 0
     }
 
     /// Returns wether the white king can castle queenside.
     #[inline]
     pub fn white_queen_side(self) -> bool {
-        self.0 & 2 != 0
+        self.rights & 2 != 0
     }
 
     /// Returns wether the black king can castle kingside.
     #[inline]
     pub fn black_king_side(self) -> bool {
-        self.0 & 4 != 0
+        self.rights & 4 != 0
     }
 
     /// Returns wether the black king can castle queenside.
     #[inline]
     pub fn black_queen_side(self) -> bool {
-        self.0 & 8 != 0
+        self.rights & 8 != 0
+    }
+
+    /// Returns wether the king of `color` can castle to the king side (or the queen side if
+    /// `kingside` is `false`).
+    #[inline]
+    pub fn has_right(self, color: Color, kingside: bool) -> bool {
+        self.rights & 1 << Self::index(color, kingside) != 0
+    }
+
+    /// Returns the file the king of `color` has to stand on to castle.
+    #[inline]
+    pub fn king_file(self, color: Color) -> File {
+        File::new(self.king_files[color.to_usize()])
+    }
+
+    /// Returns the file of the rook the king of `color` castles with to the king side (or the
+    /// queen side if `kingside` is `false`).
+    #[inline]
+    pub fn rook_file(self, color: Color, kingside: bool) -> File {
+        File::new(self.rook_files[Self::index(color, kingside)])
     }
 
-    /// Update the castling rights with a castling mask.
+    /// Returns wether a king or castling rook isn't on its standard file, which is only possible
+    /// in [Chess960].
+    ///
+    /// [Chess960]: https://en.wikipedia.org/wiki/Fischer_random_chess
+    pub fn is_chess960(self) -> bool {
+        self.king_files != [Self::STANDARD_KING_FILE; 2]
+            || self.rook_files != Self::STANDARD_ROOK_FILES
+    }
+
+    /// Returns the origin and target square of the rook when the king of `color` castles to the
+    /// king side (or the queen side if `kingside` is `false`).
+    #[inline]
+    pub(crate) fn rook_squares(self, color: Color, kingside: bool) -> (Square, Square) {
+        let rank = color.map(Rank::FIRST, Rank::EIGHTH);
+        let target = if kingside { File::F } else { File::D };
+        (
+            Square::new(self.rook_file(color, kingside), rank),
+            Square::new(target, rank),
+        )
+    }
+
+    /// Returns the raw bits of the castling rights (see the table above).
+    #[inline]
+    pub(crate) fn to_usize(self) -> usize {
+        self.rights as usize
+    }
+
+    /// Returns a table with the castling rights that are kept when a piece moves from or to a
+    /// square, so updating them after a move is just a bitwise and.
     ///
     /// | move                      | castling right | move update | new castling right |
     /// |---------------------------|----------------|-------------|--------------------|
     /// | king & rooks didn't move: | 1111           |  & 1111     |  =  1111    (15)   |
-    /// |                           |                |             |
+    /// |                           |                |             |                    |
     /// | white king  moved:        | 1111           |  & 1100     |  =  1100    (12)   |
     /// | white king's rook moved:  | 1111           |  & 1110     |  =  1110    (14)   |
     /// | white queen's rook moved: | 1111           |  & 1101     |  =  1101    (13)   |
     /// |                           |                |             |                    |
-    /// | black king moved:         | 1111           |  & 0011     |  =  1011    (3)    |
+    /// | black king moved:         | 1111           |  & 0011     |  =  0011    (3)    |
     /// | black king's rook moved:  | 1111           |  & 1011     |  =  1011    (11)   |
     /// | black queen's rook moved: | 1111           |  & 0111     |  =  0111    (7)    |
     ///
-    #[inline]
-    pub fn update(&mut self, sq: Square) {
+    /// The table only depends on the files of the kings and rooks, which don't change while
+    /// playing moves. It only has to be rebuilt when the castling rights are set and is a constant
+    /// in standard chess.
+    pub(crate) fn castle_mask(self) -> [u8; 120] {
         #[rustfmt::skip]
         const CASTLE_MASK: [u8; 120] = [
             15, 15, 15, 15, 15, 15, 15, 15, 15, 15,
@@ -89,32 +218,95 @@ impl CastlingRights {
             15, 15, 15, 15, 15, 15, 15, 15, 15, 15,
         ];
 
-        self.0 &= CASTLE_MASK[sq];
+        if !self.is_chess960() {
+            return CASTLE_MASK;
+        }
+        let mut castle_mask = [15; 120];
+        for sq in Square::iter() {
+            castle_mask[sq] = self.mask(sq);
+        }
+        castle_mask
+    }
+
+    /// Returns the castling rights that are kept when a piece moves from or to `sq`.
+    fn mask(self, sq: Square) -> u8 {
+        let color = if sq.rank() == Rank::FIRST {
+            Color::WHITE
+        } else if sq.rank() == Rank::EIGHTH {
+            Color::BLACK
+        } else {
+            return 15;
+        };
+        let file = sq.file().to_u8();
+        let mut mask = 15;
+        for kingside in [true, false] {
+            let index = Self::index(color, kingside);
+            if file == self.king_files[color.to_usize()] || file == self.rook_files[index] {
+                mask &= !(1 << index);
+            }
+        }
+        mask
+    }
+
+    /// Updates the castling rights after a piece moved from or to `sq`.
+    ///
+    /// A player loses both castling rights if their king leaves its square and the right to
+    /// castle with a rook if that rook leaves its square or is captured on it.
+    #[inline]
+    pub fn update(&mut self, sq: Square) {
+        self.apply_mask(self.mask(sq));
+    }
+
+    /// Keeps only the castling rights in `mask`, usually an entry of
+    /// [`CastlingRights::castle_mask`].
+    #[inline]
+    pub(crate) fn apply_mask(&mut self, mask: u8) {
+        let removed = self.rights & !mask;
+        if removed == 0 {
+            return;
+        }
+        for color in Color::ALL {
+            for kingside in [true, false] {
+                if removed & 1 << Self::index(color, kingside) != 0 {
+                    self.remove(color, kingside);
+                }
+            }
+        }
     }
 }
 
 impl Default for CastlingRights {
     fn default() -> Self {
-        Self(15)
+        Self::new(true, true, true, true)
     }
 }
 
 impl fmt::Display for CastlingRights {
+    /// Formats the castling rights like in a FEN. If a king or castling rook isn't on its standard
+    /// file, the file of the rook is written instead of `K` or `Q` like in Shredder-FEN.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if *self == CastlingRights::new(false, false, false, false) {
+        if self.rights == 0 {
             return write!(f, "-");
         }
-        if self.white_king_side() {
-            write!(f, "K")?;
-        }
-        if self.white_queen_side() {
-            write!(f, "Q")?;
-        }
-        if self.black_king_side() {
-            write!(f, "k")?;
-        }
-        if self.black_queen_side() {
-            write!(f, "q")?;
+        for color in Color::ALL {
+            for kingside in [true, false] {
+                if !self.has_right(color, kingside) {
+                    continue;
+                }
+                let index = Self::index(color, kingside);
+                let c = if self.king_files[color.to_usize()] == Self::STANDARD_KING_FILE
+                    && self.rook_files[index] == Self::STANDARD_ROOK_FILES[index]
+                {
+                    if kingside {
+                        'k'
+                    } else {
+                        'q'
+                    }
+                } else {
+                    self.rook_file(color, kingside).to_char()
+                };
+                write!(f, "{}", color.map(c.to_ascii_uppercase(), c))?;
+            }
         }
 
         Ok(())
@@ -1,6 +1,9 @@
+use std::convert::TryFrom;
 use std::fmt;
 use std::ops;
 
+use crate::error::ParseCharError;
+
 /// The color of a player or a piece.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub struct Color(bool); // using bool instead of u8 allows easier match statements and possibly further optimizations
@@ -11,6 +14,60 @@ impl Color {
     /// Black
     pub const BLACK: Self = Self(true);
 
+    /// Both colors in the order white, black.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Color;
+    ///
+    /// let mut letters = String::new();
+    /// for c in Color::ALL {
+    ///     letters.push(c.to_char());
+    /// }
+    ///
+    /// assert_eq!(letters, "wb");
+    /// ```
+    pub const ALL: [Self; 2] = [Self::WHITE, Self::BLACK];
+
+    /// Creates a `Color` from its index (`0` for white and `1` for black).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not in the range `0..=1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Color;
+    ///
+    /// assert_eq!(Color::from_index(0), Color::WHITE);
+    /// assert_eq!(Color::from_index(1), Color::BLACK);
+    /// ```
+    #[inline]
+    pub fn from_index(index: usize) -> Self {
+        assert!(index < 2);
+        Self(index == 1)
+    }
+
+    /// Returns the index of the `Color` (`0` for white and `1` for black). This is useful for
+    /// indexing arrays.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Color;
+    ///
+    /// let material = [39, 31];
+    ///
+    /// assert_eq!(material[Color::WHITE.to_index()], 39);
+    /// assert_eq!(material[Color::BLACK.to_index()], 31);
+    /// ```
+    #[inline]
+    pub const fn to_index(self) -> usize {
+        self.to_usize()
+    }
+
     /// Creates a `Color` from its english letter or returns `None`.
     ///
     /// # Examples
@@ -104,6 +161,27 @@ impl<T> ops::IndexMut<Color> for [T; 2] {
     }
 }
 
+impl TryFrom<char> for Color {
+    type Error = ParseCharError;
+
+    /// Converts a character to a `Color`, see [`Color::from_char`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    ///
+    /// use chers::error::ParseCharError;
+    /// use chers::Color;
+    ///
+    /// assert_eq!(Color::try_from('b'), Ok(Color::BLACK));
+    /// assert_eq!(Color::try_from('x'), Err(ParseCharError::InvalidColor('x')));
+    /// ```
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        Self::from_char(c).ok_or(ParseCharError::InvalidColor(c))
+    }
+}
+
 impl fmt::Display for Color {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.map("white", "black"))
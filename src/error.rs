@@ -6,6 +6,8 @@
 
 use thiserror::Error;
 
+use crate::{CastlingRights, Color, Square};
+
 /// Error returned by [`Position::from_fen`](crate::Position::from_fen).
 #[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ParseFenError<'a> {
@@ -21,6 +23,9 @@ pub enum ParseFenError<'a> {
     #[error("wrong number of files")]
     /// Wrong number of files in the first field of the fen
     WrongNumberOfFiles,
+    #[error("wrong number of ranks")]
+    /// Wrong number of ranks in the first field of the fen
+    WrongNumberOfRanks,
     /// Invalid castling rights
     #[error("invalid castling rights (unexpected charater {0})")]
     InvalidCastlingRights(&'a str),
@@ -33,9 +38,80 @@ pub enum ParseFenError<'a> {
     /// Invalid fullmove number
     #[error("invalid fullmove number")]
     InvalidFullmoveNumber(&'a str),
+    /// The FEN is well-formed, but describes a position that cannot occur in a game
+    #[error("illegal position ({0})")]
+    IllegalPosition(#[from] InvalidPosition),
 }
 
-/// Error returned by [`ParsedMove::from_coordinate_notation`](crate::ParsedMove::from_coordinate_notation).
+/// Error returned when parsing a [`Position`](crate::Position) with [`str::parse`].
+///
+/// It has the same variants as [`ParseFenError`], but owns the parts of the FEN instead of
+/// borrowing them, which is required by [`FromStr`](std::str::FromStr).
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ParsePositionError {
+    /// FEN too short
+    #[error("too short")]
+    TooShort,
+    /// Invalid piece character
+    #[error("invalid piece ({0})")]
+    InvalidPiece(char),
+    /// Invalid color character
+    #[error("invalid color ({0})")]
+    InvalidColor(char),
+    /// Wrong number of files in the first field of the fen
+    #[error("wrong number of files")]
+    WrongNumberOfFiles,
+    /// Wrong number of ranks in the first field of the fen
+    #[error("wrong number of ranks")]
+    WrongNumberOfRanks,
+    /// Invalid castling rights
+    #[error("invalid castling rights (unexpected charater {0})")]
+    InvalidCastlingRights(String),
+    /// Invalid en passant square
+    #[error("invalid en passant square")]
+    InvalidEnPassantSquare(ParseSquareError),
+    /// Invalid halfmove clock
+    #[error("invalid halfmove clock")]
+    InvalidHalfmoveClock(String),
+    /// Invalid fullmove number
+    #[error("invalid fullmove number")]
+    InvalidFullmoveNumber(String),
+    /// The FEN is well-formed, but describes a position that cannot occur in a game
+    #[error("illegal position ({0})")]
+    IllegalPosition(InvalidPosition),
+}
+
+impl From<ParseFenError<'_>> for ParsePositionError {
+    fn from(error: ParseFenError<'_>) -> Self {
+        match error {
+            ParseFenError::TooShort => Self::TooShort,
+            ParseFenError::InvalidPiece(c) => Self::InvalidPiece(c),
+            ParseFenError::InvalidColor(c) => Self::InvalidColor(c),
+            ParseFenError::WrongNumberOfFiles => Self::WrongNumberOfFiles,
+            ParseFenError::WrongNumberOfRanks => Self::WrongNumberOfRanks,
+            ParseFenError::InvalidCastlingRights(s) => Self::InvalidCastlingRights(s.to_string()),
+            ParseFenError::InvalidEnPassantSquare(e) => Self::InvalidEnPassantSquare(e),
+            ParseFenError::InvalidHalfmoveClock(s) => Self::InvalidHalfmoveClock(s.to_string()),
+            ParseFenError::InvalidFullmoveNumber(s) => Self::InvalidFullmoveNumber(s.to_string()),
+            ParseFenError::IllegalPosition(e) => Self::IllegalPosition(e),
+        }
+    }
+}
+
+/// Error returned when converting a character to a [`Color`], [`PieceType`](crate::PieceType)
+/// or [`Piece`](crate::Piece) with [`TryFrom`](std::convert::TryFrom).
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseCharError {
+    /// Invalid color character
+    #[error("invalid color ({0})")]
+    InvalidColor(char),
+    /// Invalid piece character
+    #[error("invalid piece ({0})")]
+    InvalidPiece(char),
+}
+
+/// Error returned by [`ParsedMove::from_coordinate_notation`](crate::ParsedMove::from_coordinate_notation)
+/// and [`Position::uci_to_san`](crate::Position::uci_to_san).
 #[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ParseMoveError {
     /// Move too short
@@ -47,6 +123,50 @@ pub enum ParseMoveError {
     /// Invalid promotion piece character
     #[error("invalid promotion piece ({0})")]
     InvalidPromotionPiece(char),
+    /// The move is not legal in the position
+    #[error("illegal move")]
+    IllegalMove,
+}
+
+/// Error returned by [`Position::parse_san`](crate::Position::parse_san).
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseSanError {
+    /// The string is not in standard algebraic notation
+    #[error("invalid syntax")]
+    InvalidSyntax,
+    /// No legal move matches the notation
+    #[error("illegal move")]
+    IllegalMove,
+    /// More than one legal move matches the notation
+    #[error("ambiguous move")]
+    AmbiguousMove,
+}
+
+/// Error returned by [`Game::from_pgn`](crate::pgn::Game::from_pgn).
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParsePgnError<'a> {
+    /// Malformed tag pair
+    #[error("invalid tag pair ({0})")]
+    InvalidTag(&'a str),
+    /// Invalid FEN in the `FEN` tag
+    #[error("invalid FEN tag ({0})")]
+    InvalidFen(ParseFenError<'a>),
+    /// Comment without a closing brace
+    #[error("unterminated comment")]
+    UnterminatedComment,
+    /// Parentheses of a variation that don't match
+    #[error("unbalanced variation")]
+    UnbalancedVariation,
+    /// A move that could not be parsed or is not legal in the position
+    #[error("invalid move {san} at move {move_number} ({error})")]
+    InvalidMove {
+        /// The fullmove number of the move
+        move_number: u16,
+        /// The move as written in the PGN
+        san: &'a str,
+        /// Why the move could not be played
+        error: ParseSanError,
+    },
 }
 
 /// Error returned by [`Square::from_algebraic_notation`](crate::Square::from_algebraic_notation).
@@ -62,3 +182,26 @@ pub enum ParseSquareError {
     #[error("invalid rank ({0})")]
     InvalidRank(char),
 }
+
+/// Error returned by [`PositionBuilder::build`](crate::PositionBuilder::build).
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidPosition {
+    /// A side does not have exactly one king
+    #[error("{0} does not have exactly one king")]
+    WrongNumberOfKings(Color),
+    /// Pawn on the first or last rank
+    #[error("pawn on the first or last rank ({0})")]
+    PawnOnBackRank(Square),
+    /// Castling rights that don't match the position of the kings and rooks
+    #[error("invalid castling rights ({0})")]
+    InvalidCastlingRights(CastlingRights),
+    /// En passant square without a pawn that could have just made a double push
+    #[error("invalid en passant square ({0})")]
+    InvalidEnPassantSquare(Square),
+    /// The side not to move is in check
+    #[error("the side not to move is in check")]
+    OpponentInCheck,
+    /// Fullmove number that is 0 or too large
+    #[error("invalid fullmove number ({0})")]
+    InvalidFullmoveNumber(u16),
+}
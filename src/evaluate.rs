@@ -1,6 +1,7 @@
 use crate::Color;
 use crate::File;
 use crate::Piece;
+use crate::PieceType;
 use crate::Position;
 use crate::Rank;
 use crate::Square;
@@ -223,8 +224,72 @@ const END_GAME_TABLE: [[i32; 120]; 6] = [
 
 const GAMEPHASE_INC: [i32; 6] = [0, 1, 1, 2, 4, 0];
 
+/// The contribution of one evaluation term, separately for the middle game and the end game.
+/// Both fields can be indexed by [`Color`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EvalTerm {
+    /// Middle game score of white and black
+    pub middle_game: [i32; 2],
+    /// End game score of white and black
+    pub end_game: [i32; 2],
+}
+
+/// Breakdown of the evaluation of a position, returned by [`Position::evaluate_trace`].
+///
+/// The evaluation consists of material and piece-square tables, both of which are interpolated
+/// between their middle game and end game values according to the game phase.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EvalTrace {
+    /// Value of the pieces
+    pub material: EvalTerm,
+    /// Bonus or malus for the squares the pieces stand on
+    pub piece_square: EvalTerm,
+    /// Game phase between 0 (end game) and 24 (middle game)
+    pub game_phase: i32,
+    /// Factor the end game score is scaled with, see [`Position::draw_scale`]
+    pub draw_scale: i32,
+}
+
+impl EvalTrace {
+    /// Returns the total evaluation from white's point of view.
+    pub fn total(&self) -> i32 {
+        let score = |f: fn(&EvalTerm) -> [i32; 2]| {
+            let material = f(&self.material);
+            let piece_square = f(&self.piece_square);
+            material[Color::WHITE] + piece_square[Color::WHITE]
+                - material[Color::BLACK]
+                - piece_square[Color::BLACK]
+        };
+        let middle_game_score = score(|t| t.middle_game);
+        let end_game_score = score(|t| t.end_game) * self.draw_scale / Position::MAX_DRAW_SCALE;
+        (middle_game_score * self.game_phase + end_game_score * (24 - self.game_phase)) / 24
+    }
+}
+
 impl Position {
-    pub(crate) fn evaluate(&mut self) -> i32 {
+    /// The [`Position::draw_scale`] of positions that are not drawish.
+    pub const MAX_DRAW_SCALE: i32 = 128;
+
+    /// Returns the static evaluation of the position in centipawns from the point of view of the
+    /// side to move.
+    ///
+    /// The evaluation consists of the material and piece-square tables for each piece type,
+    /// tapered between the middle game and the end game by the remaining material (see
+    /// [`Position::evaluate_trace`]). It is symmetric, so mirroring the position and swapping
+    /// the colors doesn't change the score.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// assert_eq!(Position::new().evaluate(), 0);
+    ///
+    /// // white is a queen up
+    /// let pos = Position::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+    /// assert!(pos.evaluate() > 900);
+    /// ```
+    pub fn evaluate(&self) -> i32 {
         let mut middle_game_white = 0;
         let mut middle_game_black = 0;
         let mut end_game_white = 0;
@@ -253,11 +318,329 @@ impl Position {
         }
 
         let middle_game_score = middle_game_white - middle_game_black;
-        let end_game_score = end_game_white - end_game_black;
+        let end_game_score =
+            (end_game_white - end_game_black) * self.draw_scale() / Self::MAX_DRAW_SCALE;
         let middle_game_phase = if game_phase > 24 { 24 } else { game_phase };
         let end_game_phase = 24 - middle_game_phase;
         self.side_to_move.map(1, -1)
             * (middle_game_score * middle_game_phase + end_game_score * end_game_phase)
             / 24
     }
+
+    /// Returns the individual terms of the evaluation of the position, see [`EvalTrace`].
+    ///
+    /// This is slower than the evaluation used by the search and only meant for debugging and
+    /// tuning.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Color, Position};
+    ///
+    /// let trace = Position::new().evaluate_trace();
+    ///
+    /// assert_eq!(trace.material.middle_game[Color::WHITE], trace.material.middle_game[Color::BLACK]);
+    /// assert_eq!(trace.game_phase, 24);
+    /// assert_eq!(trace.total(), 0);
+    /// ```
+    pub fn evaluate_trace(&self) -> EvalTrace {
+        let mut trace = EvalTrace::default();
+
+        for i in 0..8 {
+            for j in 0..8 {
+                let square = Square::new(File::new(i), Rank::new(j));
+                let piece = self.pieces[square];
+                if piece != Piece::EMPTY {
+                    let t = piece.piece_type();
+                    let c = piece.color();
+                    let table_square = c.map(square, square.flip());
+                    trace.material.middle_game[c] += MIDDLE_GAME_PIECE_VALUE[t];
+                    trace.material.end_game[c] += END_GAME_PIECE_VALUE[t];
+                    trace.piece_square.middle_game[c] += MIDDLE_GAME_TABLE[t][table_square];
+                    trace.piece_square.end_game[c] += END_GAME_TABLE[t][table_square];
+                    trace.game_phase += GAMEPHASE_INC[t];
+                }
+            }
+        }
+        trace.game_phase = trace.game_phase.min(24);
+        trace.draw_scale = self.draw_scale();
+
+        trace
+    }
+
+    /// Returns the factor between 0 and [`Position::MAX_DRAW_SCALE`] (128) the end game part of
+    /// the evaluation is multiplied with (and divided by 128), so that advantages in drawish
+    /// endings count less. 128 means no scaling and 0 means a dead draw.
+    ///
+    /// The following endings are recognized:
+    ///
+    /// - bishop and rook pawns where the bishop doesn't control the promotion square and the
+    ///   defending king is next to it: 0
+    /// - the stronger side has no pawns and at most a bishop more: 16
+    /// - opposite colored bishops without other pieces: 64
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// assert_eq!(Position::new().draw_scale(), Position::MAX_DRAW_SCALE);
+    ///
+    /// let wrong_bishop = Position::from_fen("7k/8/7P/8/8/8/8/1B2K3 w - - 0 1").unwrap();
+    /// assert_eq!(wrong_bishop.draw_scale(), 0);
+    /// ```
+    pub fn draw_scale(&self) -> i32 {
+        let count = self.material_count();
+        let non_pawn_value = |c: Color| {
+            count.knights[c] as i32 * END_GAME_PIECE_VALUE[PieceType::KNIGHT]
+                + count.bishops[c] as i32 * END_GAME_PIECE_VALUE[PieceType::BISHOP]
+                + count.rooks[c] as i32 * END_GAME_PIECE_VALUE[PieceType::ROOK]
+                + count.queens[c] as i32 * END_GAME_PIECE_VALUE[PieceType::QUEEN]
+        };
+        let value = |c: Color| {
+            non_pawn_value(c) + count.pawns[c] as i32 * END_GAME_PIECE_VALUE[PieceType::PAWN]
+        };
+        let strong = if value(Color::WHITE) >= value(Color::BLACK) {
+            Color::WHITE
+        } else {
+            Color::BLACK
+        };
+        let weak = !strong;
+        let only_bishops =
+            |c: Color| count.knights[c] == 0 && count.rooks[c] == 0 && count.queens[c] == 0;
+
+        if count.pawns[strong] > 0
+            && count.bishops[strong] == 1
+            && only_bishops(strong)
+            && non_pawn_value(weak) == 0
+            && self.is_wrong_rook_pawn_fortress(strong)
+        {
+            return 0;
+        }
+        if count.pawns[strong] == 0
+            && non_pawn_value(strong) - non_pawn_value(weak)
+                <= END_GAME_PIECE_VALUE[PieceType::BISHOP]
+        {
+            return 16;
+        }
+        if count.bishops == [1, 1]
+            && only_bishops(Color::WHITE)
+            && only_bishops(Color::BLACK)
+            && self.bishop_square_color(Color::WHITE) != self.bishop_square_color(Color::BLACK)
+        {
+            return 64;
+        }
+        Self::MAX_DRAW_SCALE
+    }
+
+    /// Returns whether all pawns of the `strong` side are on the same rook file, its bishop
+    /// doesn't control the promotion square and the defending king is next to it.
+    fn is_wrong_rook_pawn_fortress(&self, strong: Color) -> bool {
+        let mut pawn_files = [false; 8];
+        for i in 0..8 {
+            for j in 0..8 {
+                let square = Square::new(File::new(i), Rank::new(j));
+                if self.pieces[square] == Piece::new(PieceType::PAWN, strong) {
+                    pawn_files[i as usize] = true;
+                }
+            }
+        }
+        let file = match pawn_files {
+            [true, false, false, false, false, false, false, false] => File::A,
+            [false, false, false, false, false, false, false, true] => File::H,
+            _ => return false,
+        };
+        let promotion_square = Square::new(file, strong.map(Rank::EIGHTH, Rank::FIRST));
+        let king = self.king_square[!strong];
+
+        Some(promotion_square.is_light()) != self.bishop_square_color(strong)
+            && king.file().distance(file) <= 1
+            && king.rank().distance(promotion_square.rank()) <= 1
+    }
+
+    /// Returns whether the (first) bishop of a color stands on a light square.
+    fn bishop_square_color(&self, color: Color) -> Option<bool> {
+        let bishop = Piece::new(PieceType::BISHOP, color);
+        for i in 0..8 {
+            for j in 0..8 {
+                let square = Square::new(File::new(i), Rank::new(j));
+                if self.pieces[square] == bishop {
+                    return Some(square.is_light());
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the number of king moves needed to get from a `Square` to the nearest edge of the
+    /// board.
+    ///
+    /// Together with [`Position::corner_distance`] this is used to drive the defending king to
+    /// the edge in endgames like KQvK or KRvK.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Position, Square};
+    ///
+    /// assert_eq!(Position::edge_distance(Square::D4), 3);
+    /// assert_eq!(Position::edge_distance(Square::E5), 3);
+    /// assert_eq!(Position::edge_distance(Square::B7), 1);
+    /// assert_eq!(Position::edge_distance(Square::A1), 0);
+    /// assert_eq!(Position::edge_distance(Square::E8), 0);
+    /// ```
+    pub fn edge_distance(square: Square) -> u8 {
+        let file = square
+            .file()
+            .distance(File::A)
+            .min(square.file().distance(File::H));
+        let rank = square
+            .rank()
+            .distance(Rank::FIRST)
+            .min(square.rank().distance(Rank::EIGHTH));
+        file.min(rank)
+    }
+
+    /// Returns the number of king moves needed to get from a `Square` to the nearest corner of
+    /// the board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Position, Square};
+    ///
+    /// assert_eq!(Position::corner_distance(Square::D4), 3);
+    /// assert_eq!(Position::corner_distance(Square::E8), 3);
+    /// assert_eq!(Position::corner_distance(Square::B7), 1);
+    /// assert_eq!(Position::corner_distance(Square::A1), 0);
+    /// assert_eq!(Position::corner_distance(Square::H8), 0);
+    /// ```
+    pub fn corner_distance(square: Square) -> u8 {
+        let file = square
+            .file()
+            .distance(File::A)
+            .min(square.file().distance(File::H));
+        let rank = square
+            .rank()
+            .distance(Rank::FIRST)
+            .min(square.rank().distance(Rank::EIGHTH));
+        file.max(rank)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+    use crate::fen;
+    use crate::ParsedMove;
+
+    #[test_case(fen::STARTING_FEN; "starting position")]
+    #[test_case(fen::KIWIPETE; "kiwipete")]
+    #[test_case("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"; "position 3")]
+    #[test_case("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1"; "position 4 black")]
+    #[test_case("8/8/4k3/8/8/4K3/4R3/8 w - - 0 1"; "endgame")]
+    fn test_position_evaluate_trace(fen: &str) {
+        let pos = Position::from_fen(fen).expect("valid position");
+        let trace = pos.evaluate_trace();
+
+        pretty_assertions::assert_eq!(pos.side_to_move.map(1, -1) * trace.total(), pos.evaluate());
+    }
+
+    #[test_case(fen::STARTING_FEN, Position::MAX_DRAW_SCALE; "starting position")]
+    #[test_case("8/8/4k3/8/8/4K3/4R3/8 w - - 0 1", Position::MAX_DRAW_SCALE; "rook")]
+    #[test_case("7k/8/7P/8/8/8/8/1B2K3 w - - 0 1", 0; "wrong colored bishop")]
+    #[test_case("8/8/8/8/8/p7/8/K2b3k b - - 0 1", 0; "wrong colored bishop black")]
+    #[test_case("7k/8/7P/8/8/8/8/2B1K3 w - - 0 1", Position::MAX_DRAW_SCALE; "right colored bishop")]
+    #[test_case("8/8/7P/8/8/8/8/1B2K2k w - - 0 1", Position::MAX_DRAW_SCALE; "king away from the corner")]
+    #[test_case("8/8/4k3/8/8/4K3/4N3/8 w - - 0 1", 16; "lone knight")]
+    #[test_case("8/8/4k3/4b3/8/4K3/4R3/8 w - - 0 1", 16; "rook against bishop")]
+    #[test_case("8/8/4k3/4b3/8/4K3/4Q3/8 w - - 0 1", Position::MAX_DRAW_SCALE; "queen against bishop")]
+    #[test_case("8/4b3/4k3/1p3p2/1P3P2/4K3/4B3/8 w - - 0 1", 64; "opposite colored bishops")]
+    #[test_case("8/5b2/4k3/1p3p2/1P3P2/4K3/4B3/8 w - - 0 1", Position::MAX_DRAW_SCALE; "same colored bishops")]
+    fn test_position_draw_scale(fen: &str, expected: i32) {
+        let pos = Position::from_fen(fen).expect("valid position");
+
+        pretty_assertions::assert_eq!(pos.draw_scale(), expected);
+    }
+
+    #[test]
+    fn test_position_evaluate_wrong_colored_bishop() {
+        let fortress = Position::from_fen("7k/8/7P/8/8/8/8/1B2K3 w - - 0 1").unwrap();
+        let winning = Position::from_fen("7k/8/7P/8/8/8/8/2B1K3 w - - 0 1").unwrap();
+
+        assert!(fortress.evaluate().abs() < 100);
+        assert!(winning.evaluate() > 300);
+    }
+
+    /// Mirrors the board vertically and swaps the colors of all pieces and the side to move.
+    fn mirror_fen(fen: &str) -> String {
+        let fields: Vec<_> = fen.split_whitespace().collect();
+        let swap_case = |s: &str| -> String {
+            s.chars()
+                .map(|c| {
+                    if c.is_ascii_uppercase() {
+                        c.to_ascii_lowercase()
+                    } else {
+                        c.to_ascii_uppercase()
+                    }
+                })
+                .collect()
+        };
+        let pieces: Vec<_> = fields[0].split('/').rev().map(swap_case).collect();
+        let side = if fields[1] == "w" { "b" } else { "w" };
+        let mut castling: Vec<_> = swap_case(fields[2]).chars().collect();
+        castling.sort_by_key(|c| "KQkq-".find(*c));
+        let castling: String = castling.into_iter().collect();
+        let ep = fields[3]
+            .replace('3', "x")
+            .replace('6', "3")
+            .replace('x', "6");
+        format!(
+            "{} {} {} {} {} {}",
+            pieces.join("/"),
+            side,
+            castling,
+            ep,
+            fields[4],
+            fields[5]
+        )
+    }
+
+    #[test_case(fen::STARTING_FEN; "starting position")]
+    #[test_case(fen::KIWIPETE; "kiwipete")]
+    #[test_case(fen::POSITION_3; "position 3")]
+    #[test_case(fen::POSITION_4; "position 4")]
+    #[test_case(fen::POSITION_5; "position 5")]
+    #[test_case(fen::POSITION_6; "position 6")]
+    #[test_case("7k/8/7P/8/8/8/8/1B2K3 w - - 0 1"; "wrong colored bishop")]
+    fn test_position_evaluate_symmetric(fen: &str) {
+        let pos = Position::from_fen(fen).expect("valid position");
+        let mirrored = Position::from_fen(&mirror_fen(fen)).expect("valid position");
+
+        pretty_assertions::assert_eq!(mirrored.evaluate(), pos.evaluate());
+        pretty_assertions::assert_eq!(
+            mirrored.evaluate_trace().total(),
+            -pos.evaluate_trace().total()
+        );
+    }
+
+    #[test]
+    fn test_position_evaluate_starting_position() {
+        assert!(Position::new().evaluate().abs() < 50);
+    }
+
+    #[test_case("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1", "d1d5"; "rook takes queen")]
+    #[test_case("rnb1kbnr/pppp1ppp/8/4p1q1/3PP3/8/PPP2PPP/RNBQKBNR w KQkq - 1 3", "c1g5"; "bishop takes queen")]
+    fn test_position_evaluate_free_queen(fen: &str, m: &str) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let before = pos.evaluate();
+        assert!(pos.make_move(ParsedMove::from_coordinate_notation(m).unwrap()));
+        // the side to move changed
+        let after = -pos.evaluate();
+
+        let gain = after - before;
+        assert!((800..=1100).contains(&gain), "{}", gain);
+    }
 }
@@ -1,6 +1,10 @@
-use arrayvec::ArrayVec;
+//! Parsing and formatting of [FEN] strings and well-known test positions.
+//!
+//! [FEN]: https://en.wikipedia.org/wiki/Forsyth%E2%80%93Edwards_Notation
 
-use crate::position_state::PositionState;
+use std::str::FromStr;
+
+use crate::error::ParsePositionError;
 use crate::Color;
 use crate::File;
 use crate::Piece;
@@ -10,10 +14,49 @@ use crate::Rank;
 use crate::Square;
 use crate::{castling_rights::CastlingRights, error::ParseFenError};
 
+/// The starting position.
+///
+/// # Examples
+///
+/// ```
+/// use chers::{fen, Position};
+///
+/// assert_eq!(Position::from_fen(fen::STARTING_FEN).unwrap(), Position::new());
+/// ```
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// Position 2 of the [perft results] on the chess programming wiki, known as "Kiwipete". It
+/// contains many tricky moves like castling, en passant and promotions.
+///
+/// [perft results]: https://www.chessprogramming.org/Perft_Results
+pub const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+/// Position 3 of the [perft results] on the chess programming wiki.
+///
+/// [perft results]: https://www.chessprogramming.org/Perft_Results
+pub const POSITION_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+
+/// Position 4 of the [perft results] on the chess programming wiki.
+///
+/// [perft results]: https://www.chessprogramming.org/Perft_Results
+pub const POSITION_4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+
+/// Position 5 of the [perft results] on the chess programming wiki.
+///
+/// [perft results]: https://www.chessprogramming.org/Perft_Results
+pub const POSITION_5: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+
+/// Position 6 of the [perft results] on the chess programming wiki.
+///
+/// [perft results]: https://www.chessprogramming.org/Perft_Results
+pub const POSITION_6: &str =
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+
 impl Position {
     /// Creates a Position from a [FEN] string or returns an error if the fen is invalid.
     ///
-    /// At the moment it ignores halfmove clock and fullmove number.
+    /// Leading and trailing whitespace and control characters (e.g. a `\r\n` line ending) are
+    /// ignored. Besides `-`, `0` and `–` (en dash) are accepted for no en passant square.
     ///
     /// # Examples
     ///
@@ -26,44 +69,27 @@ impl Position {
     ///
     /// [FEN]: https://en.wikipedia.org/wiki/Forsyth%E2%80%93Edwards_Notation
     pub fn from_fen(fen: &str) -> Result<Self, ParseFenError<'_>> {
-        let mut fields = fen.split_whitespace();
+        let mut fields = fen
+            .trim_matches(|c: char| c.is_whitespace() || c.is_control())
+            .split_whitespace();
 
         let mut next_field = || fields.next().ok_or(ParseFenError::TooShort);
 
         let pieces = parse_pieces(next_field()?)?;
         let active_color = parse_color(next_field()?)?;
-        let castling_rights = parse_castling_rights(next_field()?)?;
+        let castling_rights = parse_castling_rights(next_field()?, &pieces)?;
         let en_passant_square = parse_en_passant_square(next_field()?)?;
         let halfmove_clock = parse_halfmove_clock(next_field()?)?;
         let fullmove_number = parse_fullmove_number(next_field()?)?;
 
-        let ply = fullmove_number * 2 - active_color.map(1, 0);
-
-        let mut state = ArrayVec::new();
-        state.push(PositionState::new(
+        Ok(Self::from_parts(
+            pieces,
+            active_color,
             castling_rights,
             en_passant_square,
             halfmove_clock,
-        ));
-
-        let mut king_square = [Square::A1; 2];
-        let mut sq;
-        for i in 0..8 {
-            for j in 0..8 {
-                sq = Square::new(File::new(i), Rank::new(j));
-                if pieces[sq].is_type(PieceType::KING) {
-                    king_square[pieces[sq].color()] = sq;
-                }
-            }
-        }
-
-        Ok(Self {
-            pieces,
-            king_square,
-            side_to_move: active_color,
-            ply,
-            state,
-        })
+            fullmove_number,
+        ))
     }
 
     /// Returns the fen representation of the current position.
@@ -96,7 +122,7 @@ impl Position {
             }
         }
 
-        let fullmove_number = (self.ply + 1) / 2;
+        let fullmove_number = self.ply.div_ceil(2);
         format!(
             "{} {} {} {} {} {}",
             res,
@@ -109,7 +135,126 @@ impl Position {
     }
 }
 
+impl FromStr for Position {
+    type Err = ParsePositionError;
+
+    /// Parses a position from a FEN, see [`Position::from_fen`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::error::ParsePositionError;
+    /// use chers::{fen, Position};
+    ///
+    /// let pos: Position = fen::STARTING_FEN.parse().unwrap();
+    /// assert_eq!(pos, Position::new());
+    ///
+    /// let error = "8/8/8 w - - 0 1".parse::<Position>().unwrap_err();
+    /// assert_eq!(error, ParsePositionError::TooShort);
+    /// assert_eq!(error.to_string(), "too short");
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from_fen(s)?)
+    }
+}
+
+/// A board with all 64 squares empty, surrounded by the off board border.
+pub(crate) const EMPTY_BOARD: [Piece; 120] = {
+    let mut pieces = [Piece::OFF_BOARD; 120];
+    let mut i = Square::A1.to_usize();
+    while i <= Square::H8.to_usize() {
+        if !matches!(i % 10, 0 | 9) {
+            pieces[i] = Piece::EMPTY;
+        }
+        i += 1;
+    }
+    pieces
+};
+
+/// The number of files a character in the first field of a FEN advances, 0 for invalid
+/// characters.
+const FEN_ADVANCE: [u8; 256] = {
+    let mut table = [0; 256];
+    let mut c = b'1';
+    while c <= b'8' {
+        table[c as usize] = c - b'0';
+        c += 1;
+    }
+    let pieces = b"PNBRQKpnbrqk";
+    let mut i = 0;
+    while i < pieces.len() {
+        table[pieces[i] as usize] = 1;
+        i += 1;
+    }
+    table
+};
+
+/// The piece a character in the first field of a FEN puts on the board. A digit only skips
+/// empty squares and a `/` ends the rank on the border.
+const FEN_PIECES: [Piece; 256] = {
+    let mut table = [Piece::EMPTY; 256];
+    table[b'/' as usize] = Piece::OFF_BOARD;
+    table[b'P' as usize] = Piece::W_PAWN;
+    table[b'N' as usize] = Piece::W_KNIGHT;
+    table[b'B' as usize] = Piece::W_BISHOP;
+    table[b'R' as usize] = Piece::W_ROOK;
+    table[b'Q' as usize] = Piece::W_QUEEN;
+    table[b'K' as usize] = Piece::W_KING;
+    table[b'p' as usize] = Piece::B_PAWN;
+    table[b'n' as usize] = Piece::B_KNIGHT;
+    table[b'b' as usize] = Piece::B_BISHOP;
+    table[b'r' as usize] = Piece::B_ROOK;
+    table[b'q' as usize] = Piece::B_QUEEN;
+    table[b'k' as usize] = Piece::B_KING;
+    table
+};
+
 fn parse_pieces(s: &str) -> Result<[Piece; 120], ParseFenError<'_>> {
+    match parse_pieces_fast(s.as_bytes()) {
+        Some(pieces) => Ok(pieces),
+        None => parse_pieces_checked(s),
+    }
+}
+
+/// Parses the first field of a FEN by walking the mailbox directly, starting at a8 and skipping
+/// the border between the ranks. The characters are looked up in tables to avoid hard to predict
+/// branches.
+///
+/// Returns `None` if anything is unusual, [`parse_pieces_checked`] is then used to find out what
+/// exactly is wrong.
+fn parse_pieces_fast(bytes: &[u8]) -> Option<[Piece; 120]> {
+    let mut pieces = EMPTY_BOARD;
+    let mut index = Square::A8.to_usize();
+    let mut rank = 7;
+    let mut file = 0;
+
+    for &b in bytes {
+        let slash = b == b'/';
+        let advance = FEN_ADVANCE[b as usize] as usize;
+        let invalid = if slash {
+            file != 8
+        } else {
+            advance == 0 || file > 7
+        };
+        if invalid || rank == 0 && file == 8 {
+            return None;
+        }
+        pieces[index] = FEN_PIECES[b as usize];
+        if slash {
+            index -= 18;
+            file = 0;
+            rank -= 1;
+        } else {
+            index += advance;
+            file += advance;
+        }
+    }
+
+    (rank == 0 && file == 8).then_some(pieces)
+}
+
+/// Parses the first field of a FEN character by character, reporting the exact error.
+fn parse_pieces_checked(s: &str) -> Result<[Piece; 120], ParseFenError<'_>> {
     let mut chars = s.chars();
     let mut pieces = [Piece::OFF_BOARD; 120];
 
@@ -147,7 +292,11 @@ fn parse_pieces(s: &str) -> Result<[Piece; 120], ParseFenError<'_>> {
         }
     }
 
-    Ok(pieces)
+    match chars.next() {
+        None => Ok(pieces),
+        Some('/') => Err(ParseFenError::WrongNumberOfRanks),
+        Some(_) => Err(ParseFenError::WrongNumberOfFiles),
+    }
 }
 
 fn parse_color(s: &str) -> Result<Color, ParseFenError<'_>> {
@@ -155,35 +304,59 @@ fn parse_color(s: &str) -> Result<Color, ParseFenError<'_>> {
     Color::from_char(c).ok_or(ParseFenError::InvalidColor(c))
 }
 
-fn parse_castling_rights(s: &str) -> Result<CastlingRights, ParseFenError<'_>> {
-    let mut white_king_side = false;
-    let mut white_queen_side = false;
-    let mut black_king_side = false;
-    let mut black_queen_side = false;
-
-    if s != "-" {
-        for c in s.chars() {
-            match c {
-                'K' => white_king_side = true,
-                'Q' => white_queen_side = true,
-                'k' => black_king_side = true,
-                'q' => black_queen_side = true,
-
-                _ => return Err(ParseFenError::InvalidCastlingRights(s)),
-            }
+/// Parses the castling rights in standard FEN (`KQkq`), [Shredder-FEN] (`HAha`) or [X-FEN], where
+/// `K` and `Q` refer to the outermost rook on that side of the king.
+///
+/// [Shredder-FEN]: https://www.chessprogramming.org/Forsyth-Edwards_Notation#Shredder-FEN
+/// [X-FEN]: https://en.wikipedia.org/wiki/X-FEN
+fn parse_castling_rights<'a>(
+    s: &'a str,
+    pieces: &[Piece; 120],
+) -> Result<CastlingRights, ParseFenError<'a>> {
+    let mut castling_rights = CastlingRights::new(false, false, false, false);
+    if s == "-" {
+        return Ok(castling_rights);
+    }
+
+    for c in s.chars() {
+        let color = if c.is_ascii_uppercase() {
+            Color::WHITE
+        } else {
+            Color::BLACK
+        };
+        let rank = color.map(Rank::FIRST, Rank::EIGHTH);
+        let back_rank = |file: u8| pieces[Square::new(File::new(file), rank)];
+        let rook = Piece::new(PieceType::ROOK, color);
+
+        let king_file = (0..8).find(|&f| back_rank(f) == Piece::new(PieceType::KING, color));
+        let rook_file = match (c.to_ascii_lowercase(), king_file) {
+            ('k', Some(king_file)) => (king_file + 1..8).rev().find(|&f| back_rank(f) == rook),
+            ('q', Some(king_file)) => (0..king_file).find(|&f| back_rank(f) == rook),
+            ('k' | 'q', None) => None,
+            (file, _) => Some(
+                File::from_char(file)
+                    .ok_or(ParseFenError::InvalidCastlingRights(s))?
+                    .to_u8(),
+            ),
+        };
+        // without a king or rook the rights are invalid, which is detected when validating
+        let king_file = king_file.map_or(File::E, File::new);
+        let rook_file = match (c.to_ascii_lowercase(), rook_file) {
+            (_, Some(rook_file)) => File::new(rook_file),
+            ('k', None) => File::H,
+            _ => File::A,
+        };
+        if king_file == rook_file {
+            return Err(ParseFenError::InvalidCastlingRights(s));
         }
+        castling_rights = castling_rights.with_rook(color, king_file, rook_file);
     }
 
-    Ok(CastlingRights::new(
-        white_king_side,
-        white_queen_side,
-        black_king_side,
-        black_queen_side,
-    ))
+    Ok(castling_rights)
 }
 
 fn parse_en_passant_square(s: &str) -> Result<Square, ParseFenError<'_>> {
-    if s == "-" {
+    if matches!(s, "-" | "0" | "–") {
         return Ok(Square::NO_SQ);
     }
     Ok(Square::from_algebraic_notation(s)?)
@@ -195,8 +368,11 @@ fn parse_halfmove_clock(s: &str) -> Result<u16, ParseFenError<'_>> {
 }
 
 fn parse_fullmove_number(s: &str) -> Result<u16, ParseFenError<'_>> {
-    s.parse()
-        .map_err(|_| ParseFenError::InvalidFullmoveNumber(s))
+    match s.parse() {
+        // the ply is twice the fullmove number and has to fit into an u16
+        Ok(n) if (1..=u16::MAX / 2).contains(&n) => Ok(n),
+        _ => Err(ParseFenError::InvalidFullmoveNumber(s)),
+    }
 }
 
 #[cfg(test)]
@@ -204,19 +380,33 @@ mod tests {
     use test_case::test_case;
 
     use super::*;
+    use crate::error::ParseSquareError;
+    use crate::position_state::PositionState;
     use crate::utils;
+    use crate::PieceType;
     use ParseFenError::*;
 
     #[test_case("", TooShort; "too short")]
     #[test_case("rnbqkbnr/pppppppp/7/7/7/7/PPPPPPPP/RNBQKBNR w KQkq - 0 1", WrongNumberOfFiles; "not enough files")]
     #[test_case("rnbqkbnr/p7p/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", WrongNumberOfFiles; "too many files")]
     #[test_case("rnbqkbnr/pppppppp/9/9/9/9/PPPPPPPP/RNBQKBNR w KQkq - 0 1", WrongNumberOfFiles; "digit 9 in first sector")]
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", WrongNumberOfRanks; "too many ranks")]
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR/ w KQkq - 0 1", WrongNumberOfRanks; "trailing slash")]
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNRR w KQkq - 0 1", WrongNumberOfFiles; "too many files on the last rank")]
     #[test_case("rnbqk?nr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", InvalidPiece('?'); "invalid piece")]
     #[test_case("k7/8/8/8/8/8/8/k7 x KQkq - 0 1", InvalidColor('x'); "invalid color")]
     #[test_case("k7/8/8/8/8/8/8/k7 w Kx - 0 1", InvalidCastlingRights("Kx"); "invalid castling rights x")]
     #[test_case("k7/8/8/8/8/8/8/k7 w KQkqx - 0 1", InvalidCastlingRights("KQkqx"); "invalid castling rights trailing character")]
+    #[test_case("4k3/8/8/8/8/8/8/4K3 w E - 0 1", InvalidCastlingRights("E"); "castling rook on king file")]
+    #[test_case("k7/8/8/8/8/8/8/K7 w - e9 0 1", InvalidEnPassantSquare(ParseSquareError::InvalidRank('9')); "invalid en passant square")]
+    #[test_case("4k3/8/8/8/8/8/8/4K3 w - - 0 0", InvalidFullmoveNumber("0"); "fullmove number zero")]
+    #[test_case("4k3/8/8/8/8/8/8/4K3 w - - 0 32768", InvalidFullmoveNumber("32768"); "fullmove number too large")]
     fn test_from_fen_invalid(fen: &str, err: ParseFenError<'_>) {
         pretty_assertions::assert_eq!(Position::from_fen(fen), Err(err));
+
+        let owned = fen.parse::<Position>().unwrap_err();
+        pretty_assertions::assert_eq!(owned, ParsePositionError::from(err));
+        pretty_assertions::assert_eq!(owned.to_string(), err.to_string());
     }
 
     #[test_case(
@@ -388,27 +578,81 @@ mod tests {
             }
         }
 
-        let mut state = ArrayVec::new();
-        state.push(PositionState::new(
+        let state = vec![PositionState::new(
             castling_rights,
             en_passant_square,
             halfmove_clock,
-        ));
-        let expected = Position {
+        )];
+        let mut expected = Position {
             pieces: piece_array,
             king_square,
             side_to_move,
             ply,
             state,
+            castle_mask: castling_rights.castle_mask(),
         };
+        expected.state[0].key = expected.zobrist_hash();
 
         pretty_assertions::assert_eq!(Position::from_fen(fen).expect("valid position"), expected);
     }
 
-    #[test_case(utils::fen::STARTING_POSITION; "starting position")]
-    #[test_case(utils::fen::KIWIPETE; "kiwipete")]
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1\r\n"; "windows line ending")]
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1\n"; "unix line ending")]
+    #[test_case("  rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1  "; "surrounding spaces")]
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq 0 0 1"; "zero en passant")]
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq – 0 1"; "en dash en passant")]
+    fn test_from_fen_tolerant(fen: &str) {
+        pretty_assertions::assert_eq!(
+            Position::from_fen(fen).expect("valid position"),
+            Position::new()
+        );
+    }
+
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w HAha - 0 1", STARTING_FEN; "shredder fen")]
+    #[test_case("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9", "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9"; "x-fen")]
+    #[test_case("4k3/8/8/8/8/8/8/RR2K1RR w KQ - 0 1", "4k3/8/8/8/8/8/8/RR2K1RR w KQ - 0 1"; "x-fen outermost rooks")]
+    #[test_case("4k3/8/8/8/8/8/8/RR2K1RR w GB - 0 1", "4k3/8/8/8/8/8/8/RR2K1RR w GB - 0 1"; "shredder fen inner rooks")]
+    fn test_from_fen_castling_rights(fen: &str, expected: &str) {
+        let pos = Position::from_fen(fen).expect("valid position");
+        assert!(pos.is_legal_position());
+        pretty_assertions::assert_eq!(pos.to_fen(), expected);
+    }
+
+    #[test_case(STARTING_FEN; "starting position")]
+    #[test_case(KIWIPETE; "kiwipete")]
+    #[test_case("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9"; "chess960")]
     fn test_to_fen(fen: &str) {
         let pos = Position::from_fen(fen).unwrap();
         pretty_assertions::assert_eq!(pos.to_fen(), fen);
     }
+
+    #[test]
+    fn test_parse_pieces_fast_matches_checked() {
+        for fen in utils::fen::RANDOM_FENS {
+            let field = fen.split_whitespace().next().unwrap();
+            pretty_assertions::assert_eq!(
+                parse_pieces_fast(field.as_bytes()),
+                Some(parse_pieces_checked(field).unwrap())
+            );
+        }
+    }
+
+    #[test_case("rnbqkbnr/pppppppp/7/7/7/7/PPPPPPPP/RNBQKBNR"; "not enough files")]
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/8/PPPPPPPP/RNBQKBNR"; "too many ranks")]
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNRR"; "too many files on the last rank")]
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP"; "too short")]
+    #[test_case("rnbqk?nr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR"; "invalid piece")]
+    #[test_case("rnbqkänr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR"; "non ascii piece")]
+    #[test_case("rnbqkbnr/pppppppp/08/8/8/8/PPPPPPPP/RNBQKBNR"; "zero")]
+    fn test_parse_pieces_fast_falls_back(field: &str) {
+        pretty_assertions::assert_eq!(parse_pieces_fast(field.as_bytes()), None);
+    }
+
+    #[test]
+    fn test_from_fen_invalid_non_ascii_piece() {
+        pretty_assertions::assert_eq!(
+            Position::from_fen("rnbqkänr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"),
+            Err(InvalidPiece('ä'))
+        );
+    }
 }
@@ -1,7 +1,10 @@
+use std::convert::TryFrom;
 use std::fmt;
 use std::ops::Add;
 use std::ops::Sub;
 
+use crate::error::ParseSquareError;
+
 /// A file (otherwise known as column) on the board.
 #[derive(Clone, Copy, PartialEq)]
 pub struct File(u8);
@@ -64,6 +67,58 @@ impl File {
     pub fn to_i32(self) -> i32 {
         self.0 as i32
     }
+
+    /// Returns an iterator over all files in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::File;
+    ///
+    /// assert_eq!(File::iter().count(), 8);
+    /// assert_eq!(File::iter().next(), Some(File::A));
+    /// assert_eq!(File::iter().last(), Some(File::H));
+    /// ```
+    pub fn iter() -> impl Iterator<Item = Self> {
+        (0..8).map(Self)
+    }
+
+    /// Returns the absolute distance between two files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::File;
+    ///
+    /// assert_eq!(File::A.distance(File::H), 7);
+    /// assert_eq!(File::H.distance(File::A), 7);
+    /// assert_eq!(File::E.distance(File::E), 0);
+    /// ```
+    #[inline]
+    pub fn distance(self, other: Self) -> u8 {
+        self.0.abs_diff(other.0)
+    }
+}
+
+impl TryFrom<char> for File {
+    type Error = ParseSquareError;
+
+    /// Converts a character to a `File`, see [`File::from_char`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    ///
+    /// use chers::error::ParseSquareError;
+    /// use chers::File;
+    ///
+    /// assert_eq!(File::try_from('e'), Ok(File::E));
+    /// assert_eq!(File::try_from('i'), Err(ParseSquareError::InvalidFile('i')));
+    /// ```
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        Self::from_char(c).ok_or(ParseSquareError::InvalidFile(c))
+    }
 }
 
 impl fmt::Display for File {
@@ -126,6 +181,14 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_file_iter() {
+        let files: Vec<_> = File::iter().collect();
+        assert_eq!(files.len(), 8);
+        assert_eq!(files[0], File::A);
+        assert_eq!(files[7], File::H);
+    }
+
     #[test]
     fn test_file_new() {
         for i in 0..8 {
@@ -1,3 +1,4 @@
+use crate::attack::is_attacked_on;
 use crate::position::{
     BISHOP_OFFSETS, BLACK_PAWN_CAPTURE_OFFSETS, BLACK_PAWN_OFFSET, KING_OFFSETS, KNIGHT_OFFSETS,
     ROOK_OFFSETS, WHITE_PAWN_CAPTURE_OFFSETS, WHITE_PAWN_OFFSET,
@@ -12,69 +13,89 @@ use crate::Position;
 use crate::Rank;
 use crate::Square;
 
+/// Adds a move to the list. Only positions that can't occur in a game have more pseudo-legal moves
+/// than fit into a [`MoveList`].
+#[inline]
+fn push_move(moves: &mut MoveList, m: BitMove) {
+    debug_assert!(
+        !moves.is_full(),
+        "more than {} pseudo-legal moves, the position can't occur in a game",
+        moves.capacity()
+    );
+    moves.push(m);
+}
+
 impl Position {
     // Functions target add moves target the MoveList. They can later be used target assign diffrent scores target
     // the moves for move ordering.
     #[inline]
     fn add_quiet(&self, moves: &mut MoveList, origin: Square, target: Square) {
-        moves.push(BitMove::new_quiet(origin, target));
+        push_move(moves, BitMove::new_quiet(origin, target));
     }
 
     #[inline]
     fn add_double_pawn_push(&self, moves: &mut MoveList, origin: Square, target: Square) {
-        moves.push(BitMove::new_pawn_push(origin, target));
+        push_move(moves, BitMove::new_pawn_push(origin, target));
     }
 
     #[inline]
     fn add_capture(&self, moves: &mut MoveList, origin: Square, target: Square) {
-        moves.push(BitMove::new_capture(origin, target));
+        push_move(moves, BitMove::new_capture(origin, target));
     }
 
     #[inline]
     fn add_en_passant(&self, moves: &mut MoveList, origin: Square, target: Square) {
-        moves.push(BitMove::new_en_passant(origin, target));
+        push_move(moves, BitMove::new_en_passant(origin, target));
     }
 
     #[inline]
     fn add_promotion_capture(&self, moves: &mut MoveList, origin: Square, target: Square) {
-        moves.push(BitMove::new_promotion_capture(
-            origin,
-            target,
-            PieceType::QUEEN,
-        ));
-        moves.push(BitMove::new_promotion_capture(
-            origin,
-            target,
-            PieceType::ROOK,
-        ));
-        moves.push(BitMove::new_promotion_capture(
-            origin,
-            target,
-            PieceType::BISHOP,
-        ));
-        moves.push(BitMove::new_promotion_capture(
-            origin,
-            target,
-            PieceType::KNIGHT,
-        ));
+        push_move(
+            moves,
+            BitMove::new_promotion_capture(origin, target, PieceType::QUEEN),
+        );
+        push_move(
+            moves,
+            BitMove::new_promotion_capture(origin, target, PieceType::ROOK),
+        );
+        push_move(
+            moves,
+            BitMove::new_promotion_capture(origin, target, PieceType::BISHOP),
+        );
+        push_move(
+            moves,
+            BitMove::new_promotion_capture(origin, target, PieceType::KNIGHT),
+        );
     }
 
     #[inline]
     fn add_promotion(&self, moves: &mut MoveList, origin: Square, target: Square) {
-        moves.push(BitMove::new_promotion(origin, target, PieceType::QUEEN));
-        moves.push(BitMove::new_promotion(origin, target, PieceType::ROOK));
-        moves.push(BitMove::new_promotion(origin, target, PieceType::BISHOP));
-        moves.push(BitMove::new_promotion(origin, target, PieceType::KNIGHT));
+        push_move(
+            moves,
+            BitMove::new_promotion(origin, target, PieceType::QUEEN),
+        );
+        push_move(
+            moves,
+            BitMove::new_promotion(origin, target, PieceType::ROOK),
+        );
+        push_move(
+            moves,
+            BitMove::new_promotion(origin, target, PieceType::BISHOP),
+        );
+        push_move(
+            moves,
+            BitMove::new_promotion(origin, target, PieceType::KNIGHT),
+        );
     }
 
     #[inline]
     fn add_castle_kingside(&self, moves: &mut MoveList, origin: Square, target: Square) {
-        moves.push(BitMove::new_castle_kingside(origin, target));
+        push_move(moves, BitMove::new_castle_kingside(origin, target));
     }
 
     #[inline]
     fn add_castle_queenside(&self, moves: &mut MoveList, origin: Square, target: Square) {
-        moves.push(BitMove::new_castle_queenside(origin, target));
+        push_move(moves, BitMove::new_castle_queenside(origin, target));
     }
 
     /// Returns a [`MoveList`](crate::MoveList) of all legal moves.
@@ -94,18 +115,291 @@ impl Position {
     /// assert!(moves.iter().all(|m| *m != m2));
     /// ```
     pub fn generate_legal_moves(&mut self) -> MoveList {
+        let is_legal = self.legality_filter();
         self.generate_pseudo_legal_moves(false)
             .into_iter()
-            .filter(|candidate| {
-                self.make_bit_move(*candidate);
-                let result = !self.in_check(!self.side_to_move);
-                self.undo_move();
-                result
-            })
+            .filter(|candidate| is_legal(*candidate))
+            .collect()
+    }
+
+    /// Returns a [`MoveList`](crate::MoveList) of all legal captures, including en passant and
+    /// promotions that capture. This is the move set a quiescence search looks at.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{fen, Position};
+    ///
+    /// assert!(Position::new().generate_captures().is_empty());
+    ///
+    /// let pos = Position::from_fen(fen::KIWIPETE).unwrap();
+    /// assert_eq!(pos.generate_captures().len(), 8);
+    /// ```
+    pub fn generate_captures(&self) -> MoveList {
+        let is_legal = self.legality_filter();
+        self.generate_pseudo_legal_moves(true)
+            .into_iter()
+            .filter(|candidate| is_legal(*candidate))
             .collect()
     }
 
-    pub(crate) fn generate_pseudo_legal_moves(&self, only_captures: bool) -> MoveList {
+    /// Returns whether the side to move has at least one legal move.
+    ///
+    /// This stops as soon as a legal move is found and doesn't modify the position, so it is
+    /// cheaper than checking whether [`Position::generate_legal_moves`] is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// let mut pos = Position::new();
+    /// assert!(pos.has_legal_move());
+    ///
+    /// let mut stalemate = Position::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    /// assert!(!stalemate.has_legal_move());
+    /// ```
+    pub fn has_legal_move(&self) -> bool {
+        self.legal_moves_iter().next().is_some()
+    }
+
+    /// Returns an iterator over all legal moves, in the same order as
+    /// [`Position::generate_legal_moves`].
+    ///
+    /// The legality of each move is only checked when the iterator gets to it, without making
+    /// the move on the position. This makes it cheaper if only some of the moves are needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// let pos = Position::new();
+    /// let first = pos.legal_moves_iter().next();
+    ///
+    /// assert!(first.is_some());
+    /// assert_eq!(pos.legal_moves_iter().count(), 20);
+    /// ```
+    pub fn legal_moves_iter(&self) -> impl Iterator<Item = BitMove> + '_ {
+        let is_legal = self.legality_filter();
+        self.generate_pseudo_legal_moves(false)
+            .into_iter()
+            .filter(move |candidate| is_legal(*candidate))
+    }
+
+    /// Returns a function that decides whether a pseudo-legal move is legal.
+    ///
+    /// The checkers and the pinned pieces are computed once up front, so most moves can be
+    /// decided without looking at the board again: in double check only the king may move,
+    /// otherwise a move has to capture the checker or block the line of the check, and a pinned
+    /// piece has to stay on the line through its king. King moves and en passant captures, which
+    /// remove two pieces from the same rank, are still tested on a copy of the board.
+    fn legality_filter(&self) -> impl Fn(BitMove) -> bool + '_ {
+        let us = self.side_to_move;
+        let king_square = self.king_square[us];
+        let pinned = self.pinned_pieces(us);
+        // the target squares that get the king out of check
+        let evasions = match self.checkers().as_slice() {
+            [] => u64::MAX,
+            &[checker] => {
+                let mut evasions = 1 << checker.to_u8_0_63();
+                if let Some(offset) = direction(king_square, checker) {
+                    let mut square = (king_square.to_i8() + offset) as usize;
+                    while square != checker.to_usize() {
+                        evasions |= 1 << Square::from_index(square).to_u8_0_63();
+                        square = (square as i8 + offset) as usize;
+                    }
+                }
+                evasions
+            }
+            _ => 0,
+        };
+
+        move |m| {
+            if self.pieces[m.origin()].is_type(PieceType::KING) {
+                // castling moves are only generated if they are legal
+                m.is_castle() || self.square_safe_for_king(m.target(), us)
+            } else if m.is_en_passant() {
+                self.is_legal(m)
+            } else {
+                evasions >> m.target().to_u8_0_63() & 1 == 1
+                    && (!pinned.contains(&m.origin())
+                        || direction(king_square, m.target()) == direction(king_square, m.origin()))
+            }
+        }
+    }
+
+    /// Returns whether a pseudo-legal move doesn't leave the own king in check, without making
+    /// the move on the position.
+    pub(crate) fn is_legal(&self, m: BitMove) -> bool {
+        let pieces = self.pieces_after(m);
+        let king_square = if self.pieces[m.origin()].is_type(PieceType::KING) {
+            m.target()
+        } else {
+            self.king_square[self.side_to_move]
+        };
+        !is_attacked_on(&pieces, king_square, !self.side_to_move)
+    }
+
+    /// Returns the board after a pseudo-legal move, without touching the rest of the position.
+    pub(crate) fn pieces_after(&self, m: BitMove) -> [Piece; 120] {
+        let mut pieces = self.pieces;
+        let piece = pieces[m.origin()];
+        let target = m.target();
+
+        if m.is_en_passant() {
+            pieces[Square::new(target.file(), m.origin().rank())] = Piece::EMPTY;
+        } else if m.is_castle() {
+            let (rook_origin, rook_target) = self.state[self.state.len() - 1]
+                .castling_rights
+                .rook_squares(piece.color(), m.is_king_side_castle());
+            pieces[m.origin()] = Piece::EMPTY;
+            pieces[rook_origin] = Piece::EMPTY;
+            pieces[rook_target] = Piece::new(PieceType::ROOK, piece.color());
+            pieces[target] = piece;
+            return pieces;
+        }
+        pieces[target] = if m.is_promotion() {
+            Piece::new(m.promotion_piece(), piece.color())
+        } else {
+            piece
+        };
+        pieces[m.origin()] = Piece::EMPTY;
+        pieces
+    }
+
+    /// Returns whether a move could be generated by [`Position::generate_pseudo_legal_moves`],
+    /// i.e. whether the piece on the origin square can make the move with these flags on the
+    /// current board, ignoring the safety of the own king.
+    ///
+    /// This is useful to validate moves from an external source before making them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{BitMove, Position, Square};
+    ///
+    /// let pos = Position::from_fen("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1").unwrap();
+    ///
+    /// assert!(pos.is_pseudo_legal(BitMove::new_quiet(Square::C1, Square::F4)));
+    /// assert!(!pos.is_pseudo_legal(BitMove::new_quiet(Square::C1, Square::D3)));
+    /// ```
+    pub fn is_pseudo_legal(&self, m: BitMove) -> bool {
+        let piece = self.pieces[m.origin()];
+        if m.is_null() || !piece.is_piece() || !piece.is_color(self.side_to_move) {
+            return false;
+        }
+        // pawn moves and castling have too many special cases, so we just compare them with the
+        // generated moves
+        if piece.is_type(PieceType::PAWN) || m.is_castle() {
+            return self.generate_pseudo_legal_moves(false).contains(&m);
+        }
+
+        let captured = self.pieces[m.target()];
+        let flags_match = if captured.is_piece() {
+            captured.is_color(!self.side_to_move)
+                && m == BitMove::new_capture(m.origin(), m.target())
+        } else {
+            m.is_quiet()
+        };
+        flags_match && self.attacks_from(m.origin()) & 1 << m.target().to_u8_0_63() != 0
+    }
+
+    /// Generates all legal moves of the piece on a given `Square`. The result is empty if the
+    /// square is empty or occupied by a piece of the side not to move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Position, Square};
+    ///
+    /// let mut pos = Position::new();
+    ///
+    /// assert_eq!(pos.moves_from(Square::E2).len(), 2);
+    /// assert_eq!(pos.moves_from(Square::E7).len(), 0);
+    /// ```
+    pub fn moves_from(&mut self, origin: Square) -> MoveList {
+        self.generate_legal_moves()
+            .into_iter()
+            .filter(|m| m.origin() == origin)
+            .collect()
+    }
+
+    /// Generates all legal moves grouped by the type of the moving piece. The groups are sorted
+    /// from pawn to king and piece types without legal moves are left out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{PieceType, Position};
+    ///
+    /// let mut pos = Position::new();
+    /// let groups = pos.legal_moves_by_piece();
+    ///
+    /// assert_eq!(groups.len(), 2);
+    /// assert_eq!(groups[0].0, PieceType::PAWN);
+    /// assert_eq!(groups[0].1.len(), 16);
+    /// assert_eq!(groups[1].0, PieceType::KNIGHT);
+    /// assert_eq!(groups[1].1.len(), 4);
+    /// ```
+    pub fn legal_moves_by_piece(&mut self) -> Vec<(PieceType, MoveList)> {
+        let mut groups: Vec<(PieceType, MoveList)> = Vec::new();
+        for m in self.generate_legal_moves() {
+            let piece_type = self.pieces[m.origin()].piece_type();
+            match groups.iter_mut().find(|(t, _)| *t == piece_type) {
+                Some((_, moves)) => moves.push(m),
+                None => {
+                    let mut moves = MoveList::new();
+                    moves.push(m);
+                    groups.push((piece_type, moves));
+                }
+            }
+        }
+        groups.sort_unstable_by_key(|(piece_type, _)| *piece_type);
+        groups
+    }
+
+    /// Returns a bitboard of all squares the piece on a given `Square` can legally move to. For
+    /// castling the destination square of the king is included.
+    ///
+    /// Bit `n` corresponds to the square with index `n` where a1 = 0, b1 = 1, ..., h8 = 63.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Position, Square};
+    ///
+    /// let mut pos = Position::new();
+    ///
+    /// assert_eq!(pos.legal_destinations(Square::B1), 1 << 16 | 1 << 18); // a3 and c3
+    /// ```
+    pub fn legal_destinations(&mut self, origin: Square) -> u64 {
+        self.moves_from(origin)
+            .iter()
+            .fold(0, |acc, m| acc | 1 << m.target().to_u8_0_63())
+    }
+
+    /// Generates all pseudo-legal moves for the side to move. If `only_captures` is true, only
+    /// captures (including en passant) are generated.
+    ///
+    /// Pseudo-legal moves follow the movement rules of the pieces but may leave the own king in
+    /// check. It is up to the caller to filter those out, e.g. by making the move and checking
+    /// [`Position::in_check`]. Castling moves are only generated if the king doesn't castle out
+    /// of, through or into check. Use [`Position::generate_legal_moves`] if you only want legal
+    /// moves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// let pos = Position::new();
+    ///
+    /// // none of the moves in the starting position are illegal
+    /// assert_eq!(pos.generate_pseudo_legal_moves(false).len(), 20);
+    /// assert_eq!(pos.generate_pseudo_legal_moves(true).len(), 0);
+    /// ```
+    pub fn generate_pseudo_legal_moves(&self, only_captures: bool) -> MoveList {
         let mut moves = MoveList::new();
 
         for i in 0..8 {
@@ -307,68 +601,45 @@ impl Position {
     }
 
     fn generate_castling_moves(&self, moves: &mut MoveList) {
-        let state = &self.state[self.state.len() - 1];
+        let castling_rights = self.state[self.state.len() - 1].castling_rights;
+        let us = self.side_to_move;
+        let king_origin = self.king_square[us];
+        let rank = king_origin.rank();
         // the order of the following operations is important for the best performance
         // 1. we check if the squares are empty since that is just a lookup and really fast.
         // 2. we check if we are in check and return early.
         // 3. we check if the squares are attacked
-        match self.side_to_move {
-            Color::WHITE => {
-                if state.castling_rights.white_king_side()
-                    && self.pieces[Square::F1] == Piece::EMPTY
-                    && self.pieces[Square::G1] == Piece::EMPTY
-                {
-                    if self.is_check() {
-                        return;
-                    }
-                    if !self.is_attacked(Square::F1, Color::BLACK)
-                        && !self.is_attacked(Square::G1, Color::BLACK)
-                    {
-                        self.add_castle_kingside(moves, Square::E1, Square::G1);
-                    }
-                }
-                if state.castling_rights.white_queen_side()
-                    && self.pieces[Square::B1] == Piece::EMPTY
-                    && self.pieces[Square::C1] == Piece::EMPTY
-                    && self.pieces[Square::D1] == Piece::EMPTY
-                {
-                    if self.is_check() {
-                        return;
-                    }
-                    if !self.is_attacked(Square::C1, Color::BLACK)
-                        && !self.is_attacked(Square::D1, Color::BLACK)
-                    {
-                        self.add_castle_queenside(moves, Square::E1, Square::C1);
-                    }
-                }
+        for kingside in [true, false] {
+            if !castling_rights.has_right(us, kingside) {
+                continue;
             }
-            Color::BLACK => {
-                if state.castling_rights.black_king_side()
-                    && self.pieces[Square::F8] == Piece::EMPTY
-                    && self.pieces[Square::G8] == Piece::EMPTY
-                {
-                    if self.is_check() {
-                        return;
-                    }
-                    if !self.is_attacked(Square::F8, Color::WHITE)
-                        && !self.is_attacked(Square::G8, Color::WHITE)
-                    {
-                        self.add_castle_kingside(moves, Square::E8, Square::G8);
-                    }
-                }
-                if state.castling_rights.black_queen_side()
-                    && self.pieces[Square::B8] == Piece::EMPTY
-                    && self.pieces[Square::C8] == Piece::EMPTY
-                    && self.pieces[Square::D8] == Piece::EMPTY
-                {
-                    if self.is_check() {
-                        return;
-                    }
-                    if !self.is_attacked(Square::C8, Color::WHITE)
-                        && !self.is_attacked(Square::D8, Color::WHITE)
-                    {
-                        self.add_castle_queenside(moves, Square::E8, Square::C8);
-                    }
+            let king_target = Square::new(if kingside { File::G } else { File::C }, rank);
+            let (rook_origin, rook_target) = castling_rights.rook_squares(us, kingside);
+
+            // in Chess960 the king and rook may already stand on or next to their targets, so
+            // all squares they pass except their own have to be empty
+            let path_clear = squares_between(king_origin, king_target)
+                .chain(squares_between(rook_origin, rook_target))
+                .all(|sq| {
+                    sq == king_origin || sq == rook_origin || self.pieces[sq] == Piece::EMPTY
+                });
+            if !path_clear {
+                continue;
+            }
+            if self.is_check() {
+                return;
+            }
+
+            // the castling rook might shield the king from an attack along the back rank
+            let mut pieces = self.pieces;
+            pieces[king_origin] = Piece::EMPTY;
+            pieces[rook_origin] = Piece::EMPTY;
+            if squares_between(king_origin, king_target).all(|sq| !is_attacked_on(&pieces, sq, !us))
+            {
+                if kingside {
+                    self.add_castle_kingside(moves, king_origin, king_target);
+                } else {
+                    self.add_castle_queenside(moves, king_origin, king_target);
                 }
             }
         }
@@ -401,16 +672,38 @@ impl Position {
     }
 }
 
+/// Returns the offset leading from `from` towards `to` if both squares are on the same rank, file
+/// or diagonal.
+fn direction(from: Square, to: Square) -> Option<i8> {
+    let files = to.file().to_u8() as i8 - from.file().to_u8() as i8;
+    let ranks = to.rank().to_u8() as i8 - from.rank().to_u8() as i8;
+    match (files, ranks) {
+        (0, 0) => None,
+        (0, _) => Some(10 * ranks.signum()),
+        (_, 0) => Some(files.signum()),
+        _ if files == ranks => Some(11 * ranks.signum()),
+        _ if files == -ranks => Some(9 * ranks.signum()),
+        _ => None,
+    }
+}
+
+/// Returns all squares of a rank from `from` to `to`, both included.
+fn squares_between(from: Square, to: Square) -> impl Iterator<Item = Square> {
+    let (low, high) = if from <= to { (from, to) } else { (to, from) };
+    (low.to_usize()..=high.to_usize()).map(Square::from_index)
+}
+
 #[cfg(test)]
 mod tests {
     use test_case::test_case;
 
+    use crate::fen;
     use crate::utils;
 
     use super::*;
 
     #[test_case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &mut ["a2a3", "a2a4", "b2b3", "b2b4", "c2c3", "c2c4", "d2d3", "d2d4", "e2e3", "e2e4", "f2f3", "f2f4", "g2g3", "g2g4", "h2h3", "h2h4", "b1a3", "b1c3", "g1f3", "g1h3"]; "starting position")]
-    #[test_case(utils::fen::KIWIPETE, &mut ["a2a3", "b2b3", "g2g3", "d5d6", "a2a4", "g2g4", "g2h3", "d5e6", "c3b1", "c3d1", "c3a4", "c3b5", "e5d3", "e5c4", "e5g4", "e5c6", "e5g6", "e5d7", "e5f7", "d2c1", "d2e3", "d2f4", "d2g5", "d2h6", "e2d1", "e2f1", "e2d3", "e2c4", "e2b5", "e2a6", "a1b1", "a1c1", "a1d1", "h1f1", "h1g1", "f3d3", "f3e3", "f3g3", "f3h3", "f3f4", "f3g4", "f3f5", "f3h5", "f3f6", "e1d1", "e1f1", "e1g1", "e1c1"]; "kiwipete")]
+    #[test_case(fen::KIWIPETE, &mut ["a2a3", "b2b3", "g2g3", "d5d6", "a2a4", "g2g4", "g2h3", "d5e6", "c3b1", "c3d1", "c3a4", "c3b5", "e5d3", "e5c4", "e5g4", "e5c6", "e5g6", "e5d7", "e5f7", "d2c1", "d2e3", "d2f4", "d2g5", "d2h6", "e2d1", "e2f1", "e2d3", "e2c4", "e2b5", "e2a6", "a1b1", "a1c1", "a1d1", "h1f1", "h1g1", "f3d3", "f3e3", "f3g3", "f3h3", "f3f4", "f3g4", "f3f5", "f3h5", "f3f6", "e1d1", "e1f1", "e1g1", "e1c1"]; "kiwipete")]
     // En passant move is not covered in kiwipete.
     #[test_case("rnbqkbnr/pppp2pp/8/3Ppp2/8/8/PPP1PPPP/RNBQKBNR w KQkq e6 0 3", &mut ["a2a3", "b2b3", "c2c3", "e2e3", "f2f3", "g2g3", "h2h3", "d5d6", "a2a4", "b2b4", "c2c4", "e2e4", "f2f4", "g2g4", "h2h4", "d5e6", "b1d2", "b1a3", "b1c3", "g1f3", "g1h3", "c1d2", "c1e3", "c1f4", "c1g5", "c1h6", "d1d2", "d1d3", "d1d4", "e1d2", ]; "en passant")]
     // There was a bug in this position on commit 31459f2b8cee5d4ab8fd1d3152d1ca432b7df125.
@@ -434,8 +727,131 @@ mod tests {
         pretty_assertions::assert_eq!(moves, expected_moves);
     }
 
-    #[test_case(utils::fen::STARTING_POSITION, &mut []; "starting position")]
-    #[test_case(utils::fen::KIWIPETE, &mut ["d5e6", "e2a6", "e5d7", "e5f7", "e5g6", "f3f6", "f3h3", "g2h3"]; "kiwipete")]
+    #[test_case(fen::STARTING_FEN, true; "starting position")]
+    #[test_case(fen::KIWIPETE, true; "kiwipete")]
+    #[test_case("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", true; "position 3")]
+    #[test_case("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", true; "position 4")]
+    #[test_case("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", true; "position 5")]
+    #[test_case("r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10", true; "position 6")]
+    #[test_case("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3", false; "checkmate")]
+    #[test_case("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", false; "stalemate")]
+    #[test_case("k7/8/1K6/8/8/8/8/7Q b - - 0 1", true; "only king moves")]
+    fn test_position_has_legal_move(fen: &str, expected: bool) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+
+        pretty_assertions::assert_eq!(pos.has_legal_move(), expected);
+        pretty_assertions::assert_eq!(pos.has_legal_move(), !pos.generate_legal_moves().is_empty());
+    }
+
+    #[test_case(fen::STARTING_FEN; "starting position")]
+    #[test_case(fen::KIWIPETE; "kiwipete")]
+    #[test_case("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"; "position 3")]
+    #[test_case("8/8/3p4/KPp4r/1R3p1k/8/4P1P1/8 w - c6 0 2"; "en passant pin")]
+    #[test_case("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1"; "position 4")]
+    #[test_case("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1"; "position 4 black")]
+    #[test_case("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8"; "position 5")]
+    #[test_case("8/8/8/4k3/8/8/8/4R2K b - - 0 1"; "rook check ray")]
+    #[test_case("8/8/8/4k3/8/8/1B6/7K b - - 0 1"; "bishop check ray")]
+    fn test_position_is_legal(fen: &str) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        for m in pos.generate_pseudo_legal_moves(false) {
+            pos.make_bit_move(m);
+            let expected = !pos.in_check(!pos.side_to_move);
+            pos.undo_move();
+
+            pretty_assertions::assert_eq!(pos.is_legal(m), expected, "{}", m);
+        }
+    }
+
+    // The king must not step away from a checking slider along the line of the check, even
+    // though it blocks the slider on its current square.
+    #[test_case("8/8/8/4k3/8/8/8/4R2K b - - 0 1", &["e5d4", "e5d5", "e5d6", "e5f4", "e5f5", "e5f6"]; "rook on the file")]
+    #[test_case("8/8/8/R3k3/8/8/8/7K b - - 0 1", &["e5d4", "e5d6", "e5e4", "e5e6", "e5f4", "e5f6"]; "rook on the rank")]
+    #[test_case("8/8/8/4k3/8/8/1B6/7K b - - 0 1", &["e5d5", "e5d6", "e5e4", "e5e6", "e5f4", "e5f5"]; "bishop on the diagonal")]
+    #[test_case("7k/8/8/8/8/8/8/R6K w - - 0 1", &["a1a2", "a1a3", "a1a4", "a1a5", "a1a6", "a1a7", "a1a8", "a1b1", "a1c1", "a1d1", "a1e1", "a1f1", "a1g1", "h1g1", "h1g2", "h1h2"]; "no check")]
+    fn test_position_king_retreat_along_check_ray(fen: &str, expected: &[&str]) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let mut moves: Vec<String> = pos
+            .generate_legal_moves()
+            .iter()
+            .map(|m| m.to_string())
+            .collect();
+        moves.sort();
+
+        pretty_assertions::assert_eq!(moves, expected);
+    }
+
+    /// Filters the pseudo-legal moves by making them, which is slow but obviously correct.
+    fn legal_moves_by_make_move(pos: &mut Position) -> MoveList {
+        pos.generate_pseudo_legal_moves(false)
+            .into_iter()
+            .filter(|m| {
+                pos.make_bit_move(*m);
+                let legal = !pos.in_check(!pos.side_to_move);
+                pos.undo_move();
+                legal
+            })
+            .collect()
+    }
+
+    #[test_case("4k3/4r3/8/8/4N3/8/8/4K3 w - - 0 1"; "pinned knight")]
+    #[test_case("4k3/4r3/8/8/4R3/8/8/4K3 w - - 0 1"; "rook pinned along its line")]
+    #[test_case("4k3/8/b7/8/8/3Q4/8/5K2 w - - 0 1"; "queen pinned on the diagonal")]
+    #[test_case("4k3/8/8/8/1b6/8/3P4/4K3 w - - 0 1"; "pinned pawn")]
+    #[test_case("4k3/8/8/8/4r3/8/3B4/4K1N1 w - - 0 1"; "block or capture")]
+    #[test_case("4k3/8/8/8/7b/8/6P1/4K3 w - - 0 1"; "pawn blocks")]
+    #[test_case("4k3/8/8/8/8/3n4/8/R3K1NR w KQ - 0 1"; "knight check")]
+    #[test_case("4k3/8/8/8/8/8/3pP3/2B1K3 w - - 0 1"; "capture the checking pawn")]
+    #[test_case("4k3/8/8/8/8/3n4/8/Q3R1K1 w - - 0 1"; "no check")]
+    #[test_case("4k3/8/8/8/8/3n4/8/1B2R1K1 b - - 0 1"; "black knight")]
+    #[test_case("4k3/4r3/8/b7/8/8/8/R3K2R w KQ - 0 1"; "double check")]
+    #[test_case("8/8/3p4/KPp4r/1R3p1k/8/4P1P1/8 w - c6 0 2"; "en passant pin")]
+    #[test_case("8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1"; "en passant captures the checker")]
+    #[test_case("8/8/8/8/k2Pp2Q/8/8/3K4 b - d3 0 1"; "en passant exposes the king")]
+    fn test_position_generate_legal_moves_pins_and_checks(fen: &str) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        pretty_assertions::assert_eq!(
+            pos.generate_legal_moves(),
+            legal_moves_by_make_move(&mut pos)
+        );
+    }
+
+    #[test]
+    fn test_position_generate_legal_moves_random_positions() {
+        for fen in utils::fen::RANDOM_FENS {
+            let mut pos = Position::from_fen(fen).expect("valid position");
+            pretty_assertions::assert_eq!(
+                pos.generate_legal_moves(),
+                legal_moves_by_make_move(&mut pos),
+                "{}",
+                fen
+            );
+        }
+    }
+
+    #[test]
+    fn test_position_generate_moves_max_mobility() {
+        // the position with the most legal moves known
+        let mut pos = Position::from_fen("R6R/3Q4/1Q4Q1/4Q3/2Q4Q/Q4Q2/pp1Q4/kBNN1KB1 w - - 0 1")
+            .expect("valid position");
+        let pseudo_legal_moves = pos.generate_pseudo_legal_moves(false);
+
+        assert!(pseudo_legal_moves.len() < pseudo_legal_moves.capacity());
+        pretty_assertions::assert_eq!(pos.generate_legal_moves().len(), 218);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "pseudo-legal moves, the position can't occur in a game")]
+    fn test_position_generate_moves_too_many() {
+        let pos =
+            Position::from_fen("n1n1n1n1/QPQPQPQP/Q6Q/Q2Q1Q1Q/Q6Q/Q6Q/1Q5Q/KQQQQQQk w - - 0 1")
+                .expect("valid FEN");
+        pos.generate_pseudo_legal_moves(false);
+    }
+
+    #[test_case(fen::STARTING_FEN, &mut []; "starting position")]
+    #[test_case(fen::KIWIPETE, &mut ["d5e6", "e2a6", "e5d7", "e5f7", "e5g6", "f3f6", "f3h3", "g2h3"]; "kiwipete")]
     fn test_position_generate_captures(fen: &str, expected_moves: &mut [&str]) {
         let pos = Position::from_fen(fen).expect("valid position");
         let mut moves: Vec<_> = pos
@@ -448,4 +864,165 @@ mod tests {
 
         pretty_assertions::assert_eq!(moves, expected_moves);
     }
+
+    #[test_case(fen::KIWIPETE; "kiwipete")]
+    #[test_case(fen::POSITION_4; "position 4")]
+    #[test_case("rnbqkbnr/pppp2pp/8/3Ppp2/8/8/PPP1PPPP/RNBQKBNR w KQkq e6 0 3"; "en passant")]
+    #[test_case("4k3/8/8/8/8/8/4r3/R3K3 w - - 0 1"; "in check")]
+    #[test_case("4k3/8/8/8/1b6/8/3N4/4K3 w - - 0 1"; "pinned piece")]
+    fn test_position_generate_captures_legal(fen: &str) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let mut captures = pos.generate_captures();
+        let mut expected: MoveList = pos
+            .generate_legal_moves()
+            .into_iter()
+            .filter(|m| m.is_capture())
+            .collect();
+        captures.sort_unstable();
+        expected.sort_unstable();
+
+        pretty_assertions::assert_eq!(captures, expected);
+    }
+
+    #[test]
+    fn test_position_legal_moves_iter_random_positions() {
+        for fen in utils::fen::RANDOM_FENS {
+            let mut pos = Position::from_fen(fen).expect("valid position");
+            let moves: MoveList = pos.legal_moves_iter().collect();
+
+            pretty_assertions::assert_eq!(moves, pos.generate_legal_moves(), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_position_generate_captures_random_positions() {
+        for fen in utils::fen::RANDOM_FENS {
+            let mut pos = Position::from_fen(fen).expect("valid position");
+            let mut captures = pos.generate_captures();
+            let mut expected: MoveList = pos
+                .generate_legal_moves()
+                .into_iter()
+                .filter(|m| m.is_capture())
+                .collect();
+            captures.sort_unstable();
+            expected.sort_unstable();
+
+            pretty_assertions::assert_eq!(captures, expected, "{}", fen);
+        }
+    }
+
+    #[test_case("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", &mut ["e1g1", "e1c1"]; "white both sides")]
+    #[test_case("r3k2r/8/8/4q3/8/8/8/R3K2R w KQkq - 0 1", &mut []; "white out of check")]
+    #[test_case("r3k2r/8/8/8/8/8/2n5/R3K2R w KQkq - 0 1", &mut []; "white out of knight check")]
+    #[test_case("r3kr2/8/8/8/8/8/8/R3K2R w KQq - 0 1", &mut ["e1c1"]; "white kingside through check")]
+    #[test_case("r3k1r1/8/8/8/8/8/8/R3K2R w KQq - 0 1", &mut ["e1c1"]; "white kingside into check")]
+    #[test_case("r2rk3/8/8/8/8/8/8/R3K2R w KQq - 0 1", &mut ["e1g1"]; "white queenside through check")]
+    #[test_case("r1r1k3/8/8/8/8/8/8/R3K2R w KQq - 0 1", &mut ["e1g1"]; "white queenside into check")]
+    #[test_case("rr2k3/8/8/8/8/8/8/R3K2R w KQq - 0 1", &mut ["e1g1", "e1c1"]; "white queenside b1 attacked")]
+    #[test_case("r3k2r/8/8/8/8/8/8/RN2K1NR w KQkq - 0 1", &mut []; "white blocked")]
+    #[test_case("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", &mut ["e8g8", "e8c8"]; "black both sides")]
+    #[test_case("r3k2r/8/8/8/4Q3/8/8/R3K2R b KQkq - 0 1", &mut []; "black out of check")]
+    #[test_case("r3k2r/3P4/8/8/8/8/8/R3K2R b KQkq - 0 1", &mut []; "black out of pawn check")]
+    #[test_case("r3k2r/8/8/8/8/8/8/R3KR2 b Qkq - 0 1", &mut ["e8c8"]; "black kingside through check")]
+    #[test_case("r3k2r/8/8/8/8/8/8/R3K1R1 b Qkq - 0 1", &mut ["e8c8"]; "black kingside into check")]
+    #[test_case("r3k2r/8/8/8/8/8/8/R2RK3 b Qkq - 0 1", &mut ["e8g8"]; "black queenside through check")]
+    #[test_case("r3k2r/8/8/8/8/8/8/R1R1K3 b Qkq - 0 1", &mut ["e8g8"]; "black queenside into check")]
+    #[test_case("r3k2r/8/8/8/8/8/8/RR2K3 b Qkq - 0 1", &mut ["e8g8", "e8c8"]; "black queenside b8 attacked")]
+    #[test_case("rn2k1nr/8/8/8/8/8/8/R3K2R b KQkq - 0 1", &mut []; "black blocked")]
+    #[test_case("4k3/8/8/8/8/8/8/5KR1 w G - 0 1", &mut ["f1g1"]; "chess960 king onto rook square")]
+    #[test_case("4k3/8/8/8/8/8/8/1RK5 w B - 0 1", &mut ["c1c1"]; "chess960 king stays")]
+    #[test_case("4k3/8/8/8/8/8/8/rRK5 w B - 0 1", &mut []; "chess960 castling rook shields king")]
+    #[test_case("4k3/8/8/8/8/8/8/RK3BR1 w GA - 0 1", &mut ["b1c1"]; "chess960 rook blocked")]
+    #[test_case("1rk2r2/8/8/8/8/8/8/4K3 b fb - 0 1", &mut ["c8g8", "c8c8"]; "chess960 black")]
+    fn test_position_generate_castling_moves(fen: &str, expected_moves: &mut [&str]) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let mut moves: Vec<_> = pos
+            .generate_legal_moves()
+            .into_iter()
+            .filter(|m| m.is_castle())
+            .map(|m| m.to_string())
+            .collect();
+        expected_moves.sort_unstable();
+        moves.sort_unstable();
+
+        pretty_assertions::assert_eq!(moves, expected_moves);
+    }
+
+    #[test_case(fen::STARTING_FEN, Square::B1, &[Square::A3, Square::C3]; "starting position knight")]
+    #[test_case(fen::STARTING_FEN, Square::E7, &[]; "opponent piece")]
+    #[test_case(fen::STARTING_FEN, Square::E4, &[]; "empty square")]
+    #[test_case("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", Square::E1, &[Square::C1, Square::D1, Square::D2, Square::E2, Square::F2, Square::F1, Square::G1]; "castling")]
+    fn test_position_legal_destinations(fen: &str, origin: Square, expected: &[Square]) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let expected = expected
+            .iter()
+            .fold(0, |acc, square| acc | 1 << square.to_u8_0_63());
+
+        pretty_assertions::assert_eq!(pos.legal_destinations(origin), expected);
+    }
+
+    #[test_case(fen::STARTING_FEN, &[(PieceType::PAWN, 16), (PieceType::KNIGHT, 4)]; "starting position")]
+    #[test_case(fen::KIWIPETE, &[
+        (PieceType::PAWN, 8),
+        (PieceType::KNIGHT, 11),
+        (PieceType::BISHOP, 11),
+        (PieceType::ROOK, 5),
+        (PieceType::QUEEN, 9),
+        (PieceType::KING, 4),
+    ]; "kiwipete")]
+    #[test_case("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", &[]; "stalemate")]
+    fn test_position_legal_moves_by_piece(fen: &str, expected: &[(PieceType, usize)]) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let groups: Vec<(PieceType, usize)> = pos
+            .legal_moves_by_piece()
+            .iter()
+            .map(|(piece_type, moves)| (*piece_type, moves.len()))
+            .collect();
+
+        pretty_assertions::assert_eq!(groups, expected);
+    }
+
+    #[test_case("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1", BitMove::new_quiet(Square::C1, Square::F4), true; "bishop move")]
+    #[test_case("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1", BitMove::new_quiet(Square::C1, Square::D3), false; "bishop moving like a knight")]
+    #[test_case("4k3/8/8/8/8/8/3P4/2B1K3 w - - 0 1", BitMove::new_quiet(Square::C1, Square::E3), false; "blocked bishop")]
+    #[test_case("4k3/8/8/8/8/4p3/8/2B1K3 w - - 0 1", BitMove::new_quiet(Square::C1, Square::E3), false; "capture without flag")]
+    #[test_case("4k3/8/8/8/8/4p3/8/2B1K3 w - - 0 1", BitMove::new_capture(Square::C1, Square::E3), true; "capture")]
+    #[test_case("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1", BitMove::new_capture(Square::C1, Square::E3), false; "capture of empty square")]
+    #[test_case("4k3/8/8/8/8/8/8/2B1K3 b - - 0 1", BitMove::new_quiet(Square::C1, Square::F4), false; "wrong side")]
+    #[test_case("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1", BitMove::NULL, false; "null move")]
+    fn test_position_is_pseudo_legal(fen: &str, m: BitMove, expected: bool) {
+        let pos = Position::from_fen(fen).expect("valid position");
+
+        pretty_assertions::assert_eq!(pos.is_pseudo_legal(m), expected);
+    }
+
+    #[test]
+    fn test_position_is_pseudo_legal_matches_generated_moves() {
+        let squares: Vec<Square> = (0..8)
+            .flat_map(|f| (0..8).map(move |r| Square::new(File::new(f), Rank::new(r))))
+            .collect();
+        for fen in utils::fen::RANDOM_FENS {
+            let pos = Position::from_fen(fen).expect("valid position");
+            let moves = pos.generate_pseudo_legal_moves(false);
+            for m in &moves {
+                assert!(pos.is_pseudo_legal(*m), "{} in {}", m, fen);
+            }
+            for &origin in &squares {
+                for &target in &squares {
+                    for m in [
+                        BitMove::new_quiet(origin, target),
+                        BitMove::new_capture(origin, target),
+                    ] {
+                        pretty_assertions::assert_eq!(
+                            pos.is_pseudo_legal(m),
+                            moves.contains(&m),
+                            "{} in {}",
+                            m,
+                            fen
+                        );
+                    }
+                }
+            }
+        }
+    }
 }
@@ -16,20 +16,29 @@ mod bit_move;
 mod castling_rights;
 mod color;
 mod evaluate;
-mod fen;
 mod file;
 mod generate_moves;
+mod material;
 mod move_list;
+mod outcome;
 mod parsed_move;
+mod pawn_structure;
 mod perft;
 mod piece;
 mod position;
+mod position_builder;
 mod position_state;
 mod rank;
+mod san;
 mod search;
+mod see;
 mod square;
+mod zobrist;
 
 pub mod error;
+pub mod fen;
+pub mod pgn;
+pub mod uci;
 
 // This module needs to be public so that it can be used in benchmarkes and integration tests.
 #[doc(hidden)]
@@ -44,11 +53,27 @@ pub use piece::Piece;
 pub use piece::PieceType;
 
 pub use bit_move::BitMove;
+pub use bit_move::MoveFlags;
 pub use move_list::MoveList;
 pub use parsed_move::ParsedMove;
 
 pub use castling_rights::CastlingRights;
+pub use evaluate::EvalTerm;
+pub use evaluate::EvalTrace;
+pub use material::MaterialCount;
+pub use outcome::Outcome;
 pub use position::Position;
+pub use position::Snapshot;
+pub use position_builder::PositionBuilder;
 pub(crate) use position_state::PositionState;
+pub use search::SearchInfo;
+pub use search::SearchParams;
+pub use search::SearchResult;
 
 pub use perft::perft;
+pub use perft::perft_detailed;
+pub use perft::perft_divide;
+pub use perft::perft_divide_san;
+pub use perft::perft_fen;
+pub use perft::perft_with_progress;
+pub use perft::PerftStats;
@@ -1,12 +1,17 @@
 use chers::Color;
+use chers::Outcome;
 use chers::ParsedMove;
 use chers::Position;
 use std::io;
 use std::io::Write;
 
 fn main() -> io::Result<()> {
+    if std::env::args().any(|arg| arg == "--uci") {
+        return chers::uci::run_uci(io::stdin().lock(), io::stdout());
+    }
+
     let mut pos = Position::new();
-    while !(pos.is_draw() || pos.is_checkmate()) {
+    while !pos.game_over() {
         println!("{}", pos);
         if pos.side_to_move() == Color::WHITE {
             loop {
@@ -26,15 +31,18 @@ fn main() -> io::Result<()> {
             }
         } else {
             println!("Thinking...");
-            let m = pos.search(4);
-            dbg!(m);
-            pos.make_bit_move(m);
+            match pos.search(4) {
+                Some(m) => {
+                    println!("{}", m);
+                    pos.make_bit_move(m);
+                }
+                None => break,
+            }
         }
     }
-    if pos.is_draw() {
-        println!("Draw!");
-    } else {
-        println!("{} won!", pos.side_to_move());
+    match pos.outcome() {
+        Outcome::Checkmate { winner } => println!("{} won!", winner),
+        _ => println!("Draw!"),
     }
 
     Ok(())
@@ -0,0 +1,170 @@
+use crate::Color;
+use crate::File;
+use crate::PieceType;
+use crate::Position;
+use crate::Rank;
+use crate::Square;
+
+/// The number of pieces of each type on the board. Every field can be indexed by [`Color`].
+///
+/// # Examples
+///
+/// ```
+/// use chers::{Color, Position};
+///
+/// let count = Position::new().material_count();
+///
+/// assert_eq!(count.pawns[Color::WHITE], 8);
+/// assert_eq!(count.queens[Color::BLACK], 1);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MaterialCount {
+    /// Number of pawns
+    pub pawns: [u8; 2],
+    /// Number of knights
+    pub knights: [u8; 2],
+    /// Number of bishops
+    pub bishops: [u8; 2],
+    /// Number of rooks
+    pub rooks: [u8; 2],
+    /// Number of queens
+    pub queens: [u8; 2],
+    /// Number of kings
+    pub kings: [u8; 2],
+}
+
+impl MaterialCount {
+    /// Returns the number of pieces of a given type and color.
+    pub fn get(&self, piece_type: PieceType, color: Color) -> u8 {
+        match piece_type {
+            PieceType::PAWN => self.pawns[color],
+            PieceType::KNIGHT => self.knights[color],
+            PieceType::BISHOP => self.bishops[color],
+            PieceType::ROOK => self.rooks[color],
+            PieceType::QUEEN => self.queens[color],
+            _ => self.kings[color],
+        }
+    }
+}
+
+impl Position {
+    /// Counts the pieces on the board, see [`MaterialCount`].
+    pub fn material_count(&self) -> MaterialCount {
+        let mut count = MaterialCount::default();
+        for i in 0..8 {
+            for j in 0..8 {
+                let piece = self.pieces[Square::new(File::new(i), Rank::new(j))];
+                if !piece.is_piece() {
+                    continue;
+                }
+                let field = match piece.piece_type() {
+                    PieceType::PAWN => &mut count.pawns,
+                    PieceType::KNIGHT => &mut count.knights,
+                    PieceType::BISHOP => &mut count.bishops,
+                    PieceType::ROOK => &mut count.rooks,
+                    PieceType::QUEEN => &mut count.queens,
+                    _ => &mut count.kings,
+                };
+                field[piece.color()] += 1;
+            }
+        }
+        count
+    }
+
+    /// Returns whether neither side has enough material left to checkmate, i.e. only kings and
+    /// at most one knight or any number of bishops on squares of the same color are left.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// assert!(!Position::new().is_insufficient_material());
+    ///
+    /// let pos = Position::from_fen("8/8/4k3/8/8/4K3/4N3/8 w - - 0 1").unwrap();
+    /// assert!(pos.is_insufficient_material());
+    /// ```
+    pub fn is_insufficient_material(&self) -> bool {
+        let mut knights = 0;
+        let mut light_bishops = 0;
+        let mut dark_bishops = 0;
+        for i in 0..8 {
+            for j in 0..8 {
+                let square = Square::new(File::new(i), Rank::new(j));
+                let piece = self.pieces[square];
+                if !piece.is_piece() {
+                    continue;
+                }
+                match piece.piece_type() {
+                    PieceType::KING => {}
+                    PieceType::KNIGHT => knights += 1,
+                    PieceType::BISHOP if square.is_light() => light_bishops += 1,
+                    PieceType::BISHOP => dark_bishops += 1,
+                    _ => return false,
+                }
+            }
+        }
+        matches!(
+            (knights, light_bishops, dark_bishops),
+            (0, _, 0) | (0, 0, _) | (1, 0, 0)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use test_case::test_case;
+
+    use super::*;
+    use crate::fen;
+
+    #[test]
+    fn test_position_material_count_starting_position() {
+        let expected = MaterialCount {
+            pawns: [8, 8],
+            knights: [2, 2],
+            bishops: [2, 2],
+            rooks: [2, 2],
+            queens: [1, 1],
+            kings: [1, 1],
+        };
+
+        assert_eq!(Position::new().material_count(), expected);
+    }
+
+    #[test]
+    fn test_position_material_count() {
+        let position = Position::from_fen(fen::KIWIPETE).expect("valid position");
+        let count = position.material_count();
+
+        assert_eq!(count.pawns, [8, 8]);
+        assert_eq!(count.get(PieceType::KNIGHT, Color::WHITE), 2);
+        assert_eq!(count.get(PieceType::BISHOP, Color::BLACK), 2);
+
+        let position =
+            Position::from_fen("8/8/4k3/8/8/4K3/4R3/8 w - - 0 1").expect("valid position");
+        let count = position.material_count();
+
+        assert_eq!(count.rooks, [1, 0]);
+        assert_eq!(count.pawns, [0, 0]);
+        assert_eq!(count.kings, [1, 1]);
+    }
+
+    #[test_case(fen::STARTING_FEN, false; "starting position")]
+    #[test_case("8/8/4k3/8/8/4K3/8/8 w - - 0 1", true; "kings only")]
+    #[test_case("8/8/4k3/8/8/4K3/4N3/8 w - - 0 1", true; "knight")]
+    #[test_case("8/8/4k3/8/8/4K3/4B3/8 w - - 0 1", true; "bishop")]
+    #[test_case("8/8/4k3/3b4/8/4K3/4B3/8 w - - 0 1", true; "bishops on the same color")]
+    #[test_case("8/8/4k3/4b3/8/4K3/4B3/8 w - - 0 1", false; "bishops on opposite colors")]
+    #[test_case("8/8/4k3/8/8/4K3/3NN3/8 w - - 0 1", false; "two knights")]
+    #[test_case("8/8/4k3/4n3/8/4K3/4N3/8 w - - 0 1", false; "knight each")]
+    #[test_case("8/8/4k3/8/8/4K3/3BN3/8 w - - 0 1", false; "bishop and knight")]
+    #[test_case("8/8/4k3/8/8/4K3/4P3/8 w - - 0 1", false; "pawn")]
+    #[test_case("8/8/4k3/8/8/4K3/4R3/8 w - - 0 1", false; "rook")]
+    fn test_position_is_insufficient_material(fen: &str, expected: bool) {
+        let position = Position::from_fen(fen).expect("valid position");
+
+        assert_eq!(position.is_insufficient_material(), expected);
+    }
+}
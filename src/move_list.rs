@@ -4,6 +4,12 @@ use crate::BitMove;
 
 /// A container for moves.
 ///
+/// It can hold up to 256 moves without allocating. The most legal moves known in a reachable
+/// chess position are 218 (`R6R/3Q4/1Q4Q1/4Q3/2Q4Q/Q4Q2/pp1Q4/kBNN1KB1 w - - 0 1`), the rest of
+/// the capacity leaves room for the pseudo-legal moves the move generator adds on top of the legal
+/// ones. Positions that can't occur in a game, e.g. with two dozen queens, can have more moves and
+/// generating them panics. Use [`ArrayVec::try_push`] when pushing moves that might not fit.
+///
 /// # Examples
 ///
 /// ```
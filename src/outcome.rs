@@ -0,0 +1,220 @@
+use crate::Color;
+use crate::Position;
+
+/// The state of the game in a position.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Outcome {
+    /// The game is not over yet.
+    Ongoing,
+    /// The side to move is checkmated.
+    Checkmate {
+        /// The side that delivered the mate.
+        winner: Color,
+    },
+    /// The side to move has no legal moves but is not in check.
+    Stalemate,
+    /// Neither side has enough material left to checkmate.
+    InsufficientMaterial,
+    /// No capture or pawn move happened in the last fifty moves.
+    FiftyMoveRule,
+    /// The position occurred for the third time.
+    ThreefoldRepetition,
+}
+
+impl Position {
+    /// Returns the [`Outcome`] of the game in the current position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Color, Outcome, Position};
+    ///
+    /// let pos = Position::new();
+    /// assert_eq!(pos.outcome(), Outcome::Ongoing);
+    ///
+    /// let mate = Position::from_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3").unwrap();
+    /// assert_eq!(mate.outcome(), Outcome::Checkmate { winner: Color::BLACK });
+    /// ```
+    pub fn outcome(&self) -> Outcome {
+        if !self.has_legal_move() {
+            if self.is_check() {
+                return Outcome::Checkmate {
+                    winner: !self.side_to_move,
+                };
+            }
+            return Outcome::Stalemate;
+        }
+        if self.is_insufficient_material() {
+            return Outcome::InsufficientMaterial;
+        }
+        if self.is_fifty_move_draw() {
+            return Outcome::FiftyMoveRule;
+        }
+        if self.is_repetition(3) {
+            return Outcome::ThreefoldRepetition;
+        }
+        Outcome::Ongoing
+    }
+
+    /// Returns whether the game is over, i.e. the [`Outcome`] is not [`Outcome::Ongoing`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// assert!(!Position::new().game_over());
+    ///
+    /// let stalemate = Position::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    /// assert!(stalemate.game_over());
+    /// ```
+    pub fn game_over(&self) -> bool {
+        self.outcome() != Outcome::Ongoing
+    }
+
+    /// Returns whether the current position occurred at least `count` times, including the current
+    /// occurrence. Positions are equal if the pieces, the side to move, the castling rights and
+    /// the en passant square are the same, the move counters are ignored. They are compared by
+    /// their [`Position::repetition_key`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{ParsedMove, Position};
+    ///
+    /// let mut pos = Position::new();
+    /// assert!(pos.is_repetition(1));
+    ///
+    /// for m in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+    ///     pos.make_move(ParsedMove::from_coordinate_notation(m).unwrap());
+    /// }
+    ///
+    /// assert!(pos.is_repetition(2));
+    /// assert!(!pos.is_repetition(3));
+    /// ```
+    pub fn is_repetition(&self, count: usize) -> bool {
+        self.repetitions() + 1 >= count
+    }
+
+    /// Returns how often the current position occurred before.
+    ///
+    /// Only positions since the last capture or pawn move are considered, since earlier ones can
+    /// never be repeated.
+    fn repetitions(&self) -> usize {
+        let last = self.state.len() - 1;
+        let current = &self.state[last];
+        let oldest = last - last.min(current.halfmove_clock as usize);
+        let mut count = 0;
+
+        // only every second position has the same side to move
+        let mut i = last;
+        while i >= oldest + 2 {
+            // positions before a null move made by the search aren't part of the game
+            if self.state[i].prev_move.is_null() || self.state[i - 1].prev_move.is_null() {
+                break;
+            }
+            i -= 2;
+            if self.state[i].key == current.key {
+                count += 1;
+            }
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+    use crate::fen;
+    use crate::ParsedMove;
+
+    #[test_case(fen::STARTING_FEN, &[], Outcome::Ongoing; "starting position")]
+    #[test_case("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3", &[], Outcome::Checkmate { winner: Color::BLACK }; "checkmate")]
+    #[test_case("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", &[], Outcome::Stalemate; "stalemate")]
+    #[test_case("8/8/4k3/8/8/4K3/4R3/8 w - - 100 80", &[], Outcome::FiftyMoveRule; "fifty move rule")]
+    #[test_case("8/8/4k3/8/8/4K3/4N3/8 w - - 0 1", &[], Outcome::InsufficientMaterial; "insufficient material")]
+    #[test_case("8/8/4k3/8/8/4K3/4N3/8 w - - 100 80", &[], Outcome::InsufficientMaterial; "insufficient material and fifty move rule")]
+    #[test_case(fen::STARTING_FEN, &["g1f3", "g8f6", "f3g1", "f6g8"], Outcome::Ongoing; "twofold repetition")]
+    #[test_case(fen::STARTING_FEN, &["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8"], Outcome::ThreefoldRepetition; "threefold repetition")]
+    fn test_position_outcome(fen: &str, moves: &[&str], expected: Outcome) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        for m in moves {
+            assert!(pos.make_move(ParsedMove::from_coordinate_notation(m).unwrap()));
+        }
+
+        pretty_assertions::assert_eq!(pos.outcome(), expected);
+    }
+
+    #[test_case(fen::STARTING_FEN, &[], false; "starting position")]
+    #[test_case(fen::KIWIPETE, &[], false; "kiwipete")]
+    #[test_case("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3", &[], true; "checkmate")]
+    #[test_case("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", &[], true; "stalemate")]
+    #[test_case("8/8/4k3/8/8/4K3/4R3/8 w - - 100 80", &[], true; "fifty move rule")]
+    #[test_case("8/8/4k3/8/8/4K3/4R3/8 w - - 99 80", &[], false; "one halfmove before the fifty move rule")]
+    #[test_case(fen::STARTING_FEN, &["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8"], true; "threefold repetition")]
+    #[test_case("8/8/4k3/8/8/4K3/4B3/8 w - - 0 1", &[], true; "insufficient material")]
+    fn test_position_game_over(fen: &str, moves: &[&str], expected: bool) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        for m in moves {
+            assert!(pos.make_move(ParsedMove::from_coordinate_notation(m).unwrap()));
+        }
+
+        pretty_assertions::assert_eq!(pos.game_over(), expected);
+    }
+
+    #[test_case(fen::STARTING_FEN, &[], 0; "no history")]
+    #[test_case(fen::STARTING_FEN, &["g1f3", "g8f6", "f3g1", "f6g8"], 1; "one repetition")]
+    #[test_case("n3k3/8/8/8/8/8/8/R3K3 w - - 0 1", &[
+        "a1a2", "a8b6", "a2a1", "b6a8",
+        "a1a8", "e8e7", "a8a1", "e7e8",
+        "a1a8", "e8e7", "a8a1", "e7e8",
+    ], 1; "capture resets repetitions")]
+    #[test_case("r3k3/8/8/8/8/8/8/R3K3 w Qq - 0 1", &["e1d1", "e8d8", "d1e1", "d8e8"], 0; "lost castling rights")]
+    #[test_case("r3k3/8/8/8/8/8/8/R3K3 w Qq - 0 1", &[
+        "e1d1", "e8d8", "d1e1", "d8e8",
+        "e1d1", "e8d8", "d1e1", "d8e8",
+    ], 1; "repetition after lost castling rights")]
+    fn test_position_repetitions(fen: &str, moves: &[&str], expected: usize) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        for m in moves {
+            assert!(pos.make_move(ParsedMove::from_coordinate_notation(m).unwrap()));
+        }
+
+        pretty_assertions::assert_eq!(pos.repetitions(), expected);
+        assert!(pos.is_repetition(expected + 1));
+        assert!(!pos.is_repetition(expected + 2));
+    }
+
+    #[test]
+    fn test_position_repetitions_null_move() {
+        let mut pos = Position::new();
+        pos.make_null_move();
+        assert!(pos.make_move(ParsedMove::from_coordinate_notation("g8f6").unwrap()));
+        pos.make_null_move();
+        assert!(pos.make_move(ParsedMove::from_coordinate_notation("f6g8").unwrap()));
+
+        // the starting position was only reached again by passing the turn
+        pretty_assertions::assert_eq!(pos.repetition_key(), Position::new().repetition_key());
+        pretty_assertions::assert_eq!(pos.repetitions(), 0);
+    }
+
+    const PERPETUAL_CHECK: &str = "6k1/6p1/8/8/8/r7/8/4Q2K w - - 0 1";
+
+    #[test_case(PERPETUAL_CHECK, &["e1e8"], 1, false; "first check")]
+    #[test_case(PERPETUAL_CHECK, &["e1e8", "g8h7", "e8h5", "h7g8", "h5e8"], 2, false; "second check")]
+    #[test_case(PERPETUAL_CHECK, &["e1e8", "g8h7", "e8h5", "h7g8", "h5e8", "g8h7", "e8h5", "h7g8"], 2, false; "before the third check")]
+    #[test_case(PERPETUAL_CHECK, &["e1e8", "g8h7", "e8h5", "h7g8", "h5e8", "g8h7", "e8h5", "h7g8", "h5e8"], 3, true; "perpetual check")]
+    #[test_case(PERPETUAL_CHECK, &["e1e8", "g8h7", "e8h5", "h7g8", "h5e8", "g8h7", "e8h5", "h7g8", "h5d1"], 1, false; "escape from the perpetual")]
+    fn test_position_is_repetition(fen: &str, moves: &[&str], count: usize, draw: bool) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        for m in moves {
+            assert!(pos.make_move(ParsedMove::from_coordinate_notation(m).unwrap()));
+        }
+
+        assert!(pos.is_repetition(count));
+        assert!(!pos.is_repetition(count + 1));
+        pretty_assertions::assert_eq!(pos.is_draw(), draw);
+    }
+}
@@ -1,7 +1,8 @@
-use crate::error::ParseMoveError;
-use crate::{PieceType, Square};
+use crate::error::{ParseMoveError, ParseSanError};
+use crate::{PieceType, Position, Square};
 
 use std::fmt;
+use std::str::FromStr;
 
 /// A chess move.
 ///
@@ -10,7 +11,7 @@ use std::fmt;
 /// [`BitMove`](crate::BitMove).
 ///
 /// The move can either be a normal move, a capture, castling, or a promotion.
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
 pub struct ParsedMove {
     pub(crate) origin: Square,
     pub(crate) target: Square,
@@ -93,6 +94,54 @@ impl ParsedMove {
             promotion_piece,
         })
     }
+
+    /// Creates a new `Move` from [standard algebraic notation][SAN] like `Nf3`, `exd5`, `O-O`,
+    /// `e8=Q` or `Rdf8`.
+    ///
+    /// SAN depends on the position, so the move is matched against the legal moves in `pos`. See
+    /// [`Position::parse_san`] for the accepted syntax.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{error::ParseSanError, ParsedMove, Position};
+    ///
+    /// let pos = Position::new();
+    ///
+    /// assert_eq!(ParsedMove::from_san("Nf3", &pos).unwrap().to_string(), "g1f3");
+    /// assert_eq!(ParsedMove::from_san("e5", &pos), Err(ParseSanError::IllegalMove));
+    /// ```
+    ///
+    /// [SAN]: https://en.wikipedia.org/wiki/Algebraic_notation_(chess)
+    pub fn from_san(s: &str, pos: &Position) -> Result<Self, ParseSanError> {
+        let m = pos.clone().parse_san(s)?;
+        Ok(Self::new(
+            m.origin(),
+            m.target(),
+            m.is_promotion().then(|| m.promotion_piece()),
+        ))
+    }
+}
+
+impl FromStr for ParsedMove {
+    type Err = ParseMoveError;
+
+    /// Parses a move in coordinate notation, see [`ParsedMove::from_coordinate_notation`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::error::ParseMoveError;
+    /// use chers::ParsedMove;
+    ///
+    /// let m: ParsedMove = "e7e8q".parse().unwrap();
+    /// assert_eq!(m.to_string(), "e7e8q");
+    ///
+    /// assert_eq!("e7".parse::<ParsedMove>(), Err(ParseMoveError::TooShort));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_coordinate_notation(s)
+    }
 }
 
 impl fmt::Display for ParsedMove {
@@ -111,6 +160,7 @@ mod tests {
 
     use super::*;
     use crate::error::ParseSquareError;
+    use crate::fen;
 
     #[test_case(""; "empty string")] // error without the explicit name
     #[test_case("e")]
@@ -172,4 +222,60 @@ mod tests {
         let expected = ParsedMove::new(from, to, promotion_piece);
         pretty_assertions::assert_eq!(ParsedMove::from_coordinate_notation(m), Ok(expected));
     }
+
+    #[test_case(fen::STARTING_FEN, "Nf3", "g1f3"; "knight move")]
+    #[test_case("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2", "exd5", "e4d5"; "pawn capture")]
+    #[test_case("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "O-O", "e1g1"; "king side castle")]
+    #[test_case("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "O-O-O", "e8c8"; "queen side castle")]
+    #[test_case("8/4P1k1/8/8/8/8/8/4K3 w - - 0 1", "e8=Q", "e7e8q"; "promotion")]
+    #[test_case("8/4P1k1/8/8/8/8/8/4K3 w - - 0 1", "e8=N", "e7e8n"; "under promotion")]
+    #[test_case("3r3r/6k1/8/8/8/8/8/6K1 b - - 0 1", "Rdf8", "d8f8"; "file disambiguation")]
+    #[test_case("6k1/8/8/8/8/8/8/R3K1R1 w - - 0 1", "Rg8+", "g1g8"; "check suffix")]
+    #[test_case("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "Ra8#", "a1a8"; "mate suffix")]
+    fn test_move_from_san(fen: &str, san: &str, expected: &str) {
+        let pos = Position::from_fen(fen).expect("valid position");
+        pretty_assertions::assert_eq!(
+            ParsedMove::from_san(san, &pos),
+            Ok(ParsedMove::from_coordinate_notation(expected).unwrap())
+        );
+    }
+
+    #[test_case(fen::STARTING_FEN, "Nf6", ParseSanError::IllegalMove; "illegal move")]
+    #[test_case(fen::STARTING_FEN, "O-O", ParseSanError::IllegalMove; "illegal castle")]
+    #[test_case("3r3r/6k1/8/8/8/8/8/6K1 b - - 0 1", "Rf8", ParseSanError::AmbiguousMove; "ambiguous move")]
+    #[test_case(fen::STARTING_FEN, "Zf3", ParseSanError::InvalidSyntax; "invalid piece")]
+    #[test_case(fen::STARTING_FEN, "e", ParseSanError::InvalidSyntax; "too short")]
+    fn test_move_from_san_invalid(fen: &str, san: &str, expected: ParseSanError) {
+        let pos = Position::from_fen(fen).expect("valid position");
+        pretty_assertions::assert_eq!(ParsedMove::from_san(san, &pos), Err(expected));
+    }
+
+    #[test]
+    fn test_move_hash() {
+        let mut moves = std::collections::HashSet::new();
+        moves.insert(ParsedMove::from_coordinate_notation("e2e4").unwrap());
+        moves.insert(ParsedMove::new(Square::E2, Square::E4, None));
+
+        pretty_assertions::assert_eq!(moves.len(), 1);
+    }
+
+    #[test]
+    fn test_move_ord() {
+        let mut moves = std::collections::BTreeSet::new();
+        moves.insert(ParsedMove::new(
+            Square::E7,
+            Square::E8,
+            Some(PieceType::QUEEN),
+        ));
+        moves.insert(ParsedMove::new(Square::E2, Square::E4, None));
+        moves.insert(ParsedMove::new(
+            Square::E7,
+            Square::E8,
+            Some(PieceType::KNIGHT),
+        ));
+        moves.insert(ParsedMove::new(Square::E2, Square::E4, None));
+
+        let moves: Vec<_> = moves.into_iter().map(|m| m.to_string()).collect();
+        pretty_assertions::assert_eq!(moves, ["e2e4", "e7e8n", "e7e8q"]);
+    }
 }
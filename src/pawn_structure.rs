@@ -0,0 +1,154 @@
+use crate::Color;
+use crate::File;
+use crate::Piece;
+use crate::PieceType;
+use crate::Position;
+use crate::Rank;
+use crate::Square;
+
+impl Position {
+    /// Returns whether there is a pawn on a given `Square` that can't be stopped by enemy pawns,
+    /// i.e. there are no enemy pawns in front of it on the same or an adjacent file.
+    ///
+    /// Returns `false` if there is no pawn on the square.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Position, Square};
+    ///
+    /// let pos = Position::from_fen("4k3/p7/8/3P4/8/8/6P1/4K3 w - - 0 1").unwrap();
+    ///
+    /// assert!(pos.is_passed_pawn(Square::D5));
+    /// assert!(pos.is_passed_pawn(Square::A7));
+    /// assert!(!pos.is_passed_pawn(Square::E4));
+    /// ```
+    pub fn is_passed_pawn(&self, square: Square) -> bool {
+        let Some(color) = self.pawn_color(square) else {
+            return false;
+        };
+        let enemy_pawn = Piece::new(PieceType::PAWN, !color);
+        let rank = square.rank().to_u8();
+        let in_front = |r: Rank| color.map(r.to_u8() > rank, r.to_u8() < rank);
+
+        !self.any_on_adjacent_files(square.file(), true, |s| {
+            in_front(s.rank()) && self.pieces[s] == enemy_pawn
+        })
+    }
+
+    /// Returns whether there is a pawn on a given `Square` without friendly pawns on the
+    /// adjacent files.
+    ///
+    /// Returns `false` if there is no pawn on the square.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Position, Square};
+    ///
+    /// let pos = Position::from_fen("4k3/8/8/8/8/8/P1PP4/4K3 w - - 0 1").unwrap();
+    ///
+    /// assert!(pos.is_isolated_pawn(Square::A2));
+    /// assert!(!pos.is_isolated_pawn(Square::C2));
+    /// ```
+    pub fn is_isolated_pawn(&self, square: Square) -> bool {
+        let Some(color) = self.pawn_color(square) else {
+            return false;
+        };
+        let own_pawn = Piece::new(PieceType::PAWN, color);
+
+        !self.any_on_adjacent_files(square.file(), false, |s| self.pieces[s] == own_pawn)
+    }
+
+    /// Returns whether there is a pawn on a given `Square` with another friendly pawn on the
+    /// same file.
+    ///
+    /// Returns `false` if there is no pawn on the square.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Position, Square};
+    ///
+    /// let pos = Position::from_fen("4k3/8/8/8/8/2P5/2P1P3/4K3 w - - 0 1").unwrap();
+    ///
+    /// assert!(pos.is_doubled_pawn(Square::C2));
+    /// assert!(pos.is_doubled_pawn(Square::C3));
+    /// assert!(!pos.is_doubled_pawn(Square::E2));
+    /// ```
+    pub fn is_doubled_pawn(&self, square: Square) -> bool {
+        let Some(color) = self.pawn_color(square) else {
+            return false;
+        };
+        let own_pawn = Piece::new(PieceType::PAWN, color);
+
+        (0..8)
+            .map(|rank| Square::new(square.file(), Rank::new(rank)))
+            .any(|s| s != square && self.pieces[s] == own_pawn)
+    }
+
+    /// Returns the color of the pawn on a given `Square` or `None` if there is no pawn.
+    fn pawn_color(&self, square: Square) -> Option<Color> {
+        let piece = self.pieces[square];
+        piece.is_type(PieceType::PAWN).then(|| piece.color())
+    }
+
+    /// Returns whether `f` is true for any square on the files next to `file`, or also on
+    /// `file` itself if `include_file` is set.
+    fn any_on_adjacent_files(
+        &self,
+        file: File,
+        include_file: bool,
+        f: impl Fn(Square) -> bool,
+    ) -> bool {
+        let file = file.to_u8() as i8;
+        (file - 1..=file + 1)
+            .filter(|&i| (0..8).contains(&i) && (include_file || i != file))
+            .flat_map(|i| (0..8).map(move |rank| Square::new(File::new(i as u8), Rank::new(rank))))
+            .any(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+    use crate::fen;
+
+    #[test_case(fen::STARTING_FEN, &[]; "starting position")]
+    #[test_case("4k3/p7/8/3P4/8/8/6P1/4K3 w - - 0 1", &[Square::A7, Square::D5, Square::G2]; "no opposing pawns")]
+    #[test_case("4k3/8/4p3/3P4/8/8/8/4K3 w - - 0 1", &[]; "adjacent file")]
+    #[test_case("4k3/8/8/3Pp3/8/8/8/4K3 w - - 0 1", &[Square::D5, Square::E5]; "pawns next to each other")]
+    #[test_case("4k3/8/3p4/3P4/8/8/8/4K3 w - - 0 1", &[]; "blocked")]
+    fn test_position_is_passed_pawn(fen: &str, expected: &[Square]) {
+        let pos = Position::from_fen(fen).expect("valid position");
+        let passed: Vec<Square> = squares().filter(|s| pos.is_passed_pawn(*s)).collect();
+
+        pretty_assertions::assert_eq!(passed, expected);
+    }
+
+    #[test_case(fen::STARTING_FEN, &[]; "starting position")]
+    #[test_case("4k3/8/8/8/8/8/P1PP4/4K3 w - - 0 1", &[Square::A2]; "a pawn")]
+    #[test_case("4k3/7p/8/8/8/8/P3P3/4K3 w - - 0 1", &[Square::A2, Square::E2, Square::H7]; "both colors")]
+    fn test_position_is_isolated_pawn(fen: &str, expected: &[Square]) {
+        let pos = Position::from_fen(fen).expect("valid position");
+        let isolated: Vec<Square> = squares().filter(|s| pos.is_isolated_pawn(*s)).collect();
+
+        pretty_assertions::assert_eq!(isolated, expected);
+    }
+
+    #[test_case(fen::STARTING_FEN, &[]; "starting position")]
+    #[test_case("4k3/2p5/8/8/8/2P5/2P1P3/4K3 w - - 0 1", &[Square::C2, Square::C3]; "enemy pawn on the same file")]
+    fn test_position_is_doubled_pawn(fen: &str, expected: &[Square]) {
+        let pos = Position::from_fen(fen).expect("valid position");
+        let doubled: Vec<Square> = squares().filter(|s| pos.is_doubled_pawn(*s)).collect();
+
+        pretty_assertions::assert_eq!(doubled, expected);
+    }
+
+    /// All squares in the order a1, a2, ..., h8.
+    fn squares() -> impl Iterator<Item = Square> {
+        (0..8).flat_map(|f| (0..8).map(move |r| Square::new(File::new(f), Rank::new(r))))
+    }
+}
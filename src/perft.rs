@@ -1,6 +1,28 @@
+use std::ops::AddAssign;
+
+use crate::error::ParseFenError;
+use crate::BitMove;
 use crate::Position;
 
 /// Counts the number of leaf nodes from generating moves to a certain depth.
+///
+/// With debug assertions enabled every move is checked to leave the position exactly as it was
+/// after undoing it. This takes a [`Snapshot`](crate::Snapshot) at every inner node and makes
+/// perft noticeably slower, release builds are not affected.
+///
+/// # Examples
+///
+/// ```
+/// use chers::{fen, perft, ParsedMove, Position};
+///
+/// let mut pos = Position::from_fen(fen::KIWIPETE).unwrap();
+/// assert_eq!(perft(&mut pos, 2), 2039);
+///
+/// // the position is left unchanged, so it can be used to continue the game
+/// assert_eq!(pos.to_fen(), fen::KIWIPETE);
+/// assert!(pos.make_move(ParsedMove::from_coordinate_notation("e1g1").unwrap()));
+/// assert_eq!(perft(&mut pos, 1), 43);
+/// ```
 pub fn perft(pos: &mut Position, depth: u16) -> u64 {
     match depth {
         0 => 1,
@@ -9,42 +31,244 @@ pub fn perft(pos: &mut Position, depth: u16) -> u64 {
             let mut count = 0;
 
             for m in pos.generate_legal_moves() {
+                #[cfg(debug_assertions)]
+                let before = pos.snapshot();
+
                 pos.make_bit_move(m);
                 count += perft(pos, depth - 1);
                 pos.undo_move();
+
+                #[cfg(debug_assertions)]
+                assert!(
+                    pos.matches_snapshot(&before),
+                    "undoing {} did not restore the position {}",
+                    m,
+                    pos.to_fen()
+                );
             }
             count
         }
     }
 }
 
+impl Position {
+    /// Counts the number of leaf nodes from generating moves to a certain depth. See [`perft`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is not legal (see [`Position::is_legal_position`]), since the counts
+    /// would be meaningless. Use [`perft_fen`] to get an error instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// assert_eq!(Position::new().perft(3), 8902);
+    /// ```
+    #[inline]
+    pub fn perft(&mut self, depth: u16) -> u64 {
+        assert!(
+            self.is_legal_position(),
+            "perft on an illegal position {}",
+            self.to_fen()
+        );
+        perft(self, depth)
+    }
+}
+
+/// Parses a FEN and counts the number of leaf nodes from generating moves to a certain depth. See
+/// [`perft`] and [`Position::from_fen`].
+///
+/// Returns [`ParseFenError::IllegalPosition`] if the FEN describes a position that cannot occur in a
+/// game, e.g. because the side not to move is in check.
+///
+/// # Examples
+///
+/// ```
+/// use chers::{fen, perft_fen};
+///
+/// assert_eq!(perft_fen(fen::STARTING_FEN, 3), Ok(8902));
+/// assert!(perft_fen("invalid", 3).is_err());
+/// assert!(perft_fen("4k3/8/8/8/8/8/8/4RK2 w - - 0 1", 3).is_err());
+/// ```
+pub fn perft_fen(fen: &str, depth: u16) -> Result<u64, ParseFenError<'_>> {
+    let mut pos = Position::from_fen(fen)?;
+    pos.validate()?;
+    Ok(perft(&mut pos, depth))
+}
+
+/// Same as [`perft`], but calls `on_root` with every root move and the number of leaf nodes below
+/// it as soon as its subtree has been counted.
+///
+/// # Examples
+///
+/// ```
+/// use chers::{perft_with_progress, Position};
+///
+/// let mut pos = Position::new();
+/// let total = perft_with_progress(&mut pos, 3, |m, count| println!("{}: {}", m, count));
+///
+/// assert_eq!(total, 8902);
+/// ```
+pub fn perft_with_progress(
+    pos: &mut Position,
+    depth: u16,
+    mut on_root: impl FnMut(BitMove, u64),
+) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let mut count = 0;
+
+    for m in pos.generate_legal_moves() {
+        pos.make_bit_move(m);
+        let subtree = perft(pos, depth - 1);
+        pos.undo_move();
+        on_root(m, subtree);
+        count += subtree;
+    }
+    count
+}
+
+/// The number of leaf nodes of a perft together with the kind of moves leading to them, as listed
+/// on the [Perft Results](https://www.chessprogramming.org/Perft_Results) page.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PerftStats {
+    /// The number of leaf nodes
+    pub nodes: u64,
+    /// The number of captures, including en passant captures
+    pub captures: u64,
+    /// The number of en passant captures
+    pub en_passant: u64,
+    /// The number of castling moves
+    pub castles: u64,
+    /// The number of promotions, including promotions that capture
+    pub promotions: u64,
+    /// The number of moves giving check
+    pub checks: u64,
+}
+
+impl AddAssign for PerftStats {
+    fn add_assign(&mut self, other: Self) {
+        self.nodes += other.nodes;
+        self.captures += other.captures;
+        self.en_passant += other.en_passant;
+        self.castles += other.castles;
+        self.promotions += other.promotions;
+        self.checks += other.checks;
+    }
+}
+
+/// Same as [`perft`], but also counts how many of the moves leading to the leaf nodes are
+/// captures, castling moves and so on. This helps to find out which kind of move is generated
+/// incorrectly if the node count is wrong.
+///
+/// # Examples
+///
+/// ```
+/// use chers::{fen, perft_detailed, PerftStats, Position};
+///
+/// let mut pos = Position::from_fen(fen::KIWIPETE).unwrap();
+///
+/// assert_eq!(
+///     perft_detailed(&mut pos, 1),
+///     PerftStats {
+///         nodes: 48,
+///         captures: 8,
+///         en_passant: 0,
+///         castles: 2,
+///         promotions: 0,
+///         checks: 0,
+///     }
+/// );
+/// ```
+pub fn perft_detailed(pos: &mut Position, depth: u16) -> PerftStats {
+    let mut stats = PerftStats::default();
+    match depth {
+        0 => stats.nodes = 1,
+        1 => {
+            for m in pos.generate_legal_moves() {
+                stats.nodes += 1;
+                stats.captures += m.is_capture() as u64;
+                stats.en_passant += m.is_en_passant() as u64;
+                stats.castles += m.is_castle() as u64;
+                stats.promotions += m.is_promotion() as u64;
+                stats.checks += pos.gives_check(m) as u64;
+            }
+        }
+        _ => {
+            for m in pos.generate_legal_moves() {
+                pos.make_bit_move(m);
+                stats += perft_detailed(pos, depth - 1);
+                pos.undo_move();
+            }
+        }
+    }
+    stats
+}
+
+/// Returns every legal root move together with the number of leaf nodes below it.
+///
+/// # Examples
+///
+/// ```
+/// use chers::{perft_divide, Position};
+///
+/// let mut pos = Position::new();
+/// let divide = perft_divide(&mut pos, 2);
+///
+/// assert_eq!(divide.len(), 20);
+/// assert!(divide.iter().all(|&(_, count)| count == 20));
+/// ```
+pub fn perft_divide(pos: &mut Position, depth: u16) -> Vec<(BitMove, u64)> {
+    let mut result = Vec::new();
+    perft_with_progress(pos, depth, |m, count| result.push((m, count)));
+    result
+}
+
+/// Same as [`perft_divide`], but with the root moves in standard algebraic notation. This makes it
+/// easier to compare the results with other engines.
+///
+/// # Examples
+///
+/// ```
+/// use chers::{perft_divide_san, Position};
+///
+/// let mut pos = Position::new();
+/// let divide = perft_divide_san(&mut pos, 3);
+///
+/// assert!(divide.contains(&("Nf3".to_string(), 440)));
+/// ```
+pub fn perft_divide_san(pos: &mut Position, depth: u16) -> Vec<(String, u64)> {
+    perft_divide(pos, depth)
+        .into_iter()
+        .map(|(m, count)| (pos.san(m), count))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use test_case::test_case;
 
     use super::*;
 
-    use crate::utils;
+    use crate::fen;
 
     fn print_perft_results(pos: &mut Position, depth: u16) -> String {
         let mut result = String::new();
-        if depth == 0 {
-            return result;
-        }
-        for m in pos.generate_legal_moves() {
-            pos.make_bit_move(m);
-            result.push_str(&format!("{}: {}\n", m, perft(pos, depth - 1)));
-            pos.undo_move();
-        }
+        perft_with_progress(pos, depth, |m, count| {
+            result.push_str(&format!("{}: {}\n", m, count))
+        });
         result
     }
 
-    const POS_1: &str = utils::fen::STARTING_POSITION;
-    const POS_2: &str = utils::fen::KIWIPETE;
-    const POS_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
-    const POS_4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
-    const POS_5: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
-    const POS_6: &str = "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+    const POS_1: &str = fen::STARTING_FEN;
+    const POS_2: &str = fen::KIWIPETE;
+    const POS_3: &str = fen::POSITION_3;
+    const POS_4: &str = fen::POSITION_4;
+    const POS_5: &str = fen::POSITION_5;
+    const POS_6: &str = fen::POSITION_6;
 
     // data from stockfish (`position fen <fen>\n go perft <depth>`) and https://www.chessprogramming.org/Perft_Results
 
@@ -93,8 +317,16 @@ mod tests {
     #[test_case("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/P1N2Q1p/1PPBBPPP/R3K2R b KQkq - 0 1",  4, 4_627_439; "bug 4")]
     #[test_case("r3k2r/p1ppqpb1/1n2pnp1/3PN3/1pb1P3/P1N2Q1p/1PPBBPPP/R3K2R w KQkq - 1 2", 3,   104_588; "bug 4.1")]
     #[test_case("r3k2r/p1ppqpb1/1n2pnp1/3PN3/Ppb1P3/2N2Q1p/1PPBBPPP/R3K2R b KQkq - 0 2",  2,     2_122; "bug 4.2")]
+    // Chess960 positions from https://www.chessprogramming.org/Chess960_Perft_Results
+    #[test_case("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",   4,   326_672; "chess960 1")]
+    #[test_case("2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9",     4,   667_366; "chess960 2")]
+    #[test_case("b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9",        4,   273_318; "chess960 3")]
+    #[test_case("1rqbkrbn/1ppppp1p/1n6/p1N3p1/8/2P4P/PP1PPPP1/1RQBKRBN w FBfb - 0 9",  4,   287_739; "chess960 4")]
+    #[test_case("rbbqn1kr/pp2p1pp/6n1/2pp1p2/2P4P/P7/BP1PPPP1/R1BQNNKR w HAha - 0 9",  4,   890_435; "chess960 5")]
+    #[test_case("rqbbknr1/1ppp2pp/p5n1/4pp2/P7/1PP5/1Q1PPPPP/R1BBKNRN w GAga - 0 9",  4,   408_207; "chess960 6")]
     fn test_perft(fen: &str, depth: u16, expected: u64) {
         let mut pos = Position::from_fen(fen).expect("valid position");
+        assert!(pos.is_legal_position(), "illegal test position {}", fen);
         let result = perft(&mut pos, depth);
         if result != expected {
             panic!(
@@ -108,4 +340,96 @@ mod tests {
             );
         }
     }
+
+    #[test_case(POS_1, 3; "starting position 3")]
+    #[test_case(POS_2, 2; "kiwipete 2")]
+    #[test_case(POS_4, 3; "position4 3")]
+    fn test_perft_with_progress(fen: &str, depth: u16) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let root_moves = pos.generate_legal_moves();
+        let mut visited = Vec::new();
+        let mut sum = 0;
+
+        let total = perft_with_progress(&mut pos, depth, |m, count| {
+            visited.push(m);
+            sum += count;
+        });
+
+        pretty_assertions::assert_eq!(visited.as_slice(), root_moves.as_slice());
+        pretty_assertions::assert_eq!(sum, total);
+        pretty_assertions::assert_eq!(total, perft(&mut pos, depth));
+    }
+
+    // data from https://www.chessprogramming.org/Perft_Results
+    #[test_case(POS_1, 2, [400, 0, 0, 0, 0, 0]; "starting position 2")]
+    #[test_case(POS_1, 3, [8_902, 34, 0, 0, 0, 12]; "starting position 3")]
+    #[test_case(POS_1, 4, [197_281, 1_576, 0, 0, 0, 469]; "starting position 4")]
+    #[test_case(POS_2, 1, [48, 8, 0, 2, 0, 0]; "kiwipete 1")]
+    #[test_case(POS_2, 2, [2_039, 351, 1, 91, 0, 3]; "kiwipete 2")]
+    #[test_case(POS_2, 3, [97_862, 17_102, 45, 3_162, 0, 993]; "kiwipete 3")]
+    #[test_case(POS_3, 3, [2_812, 209, 2, 0, 0, 267]; "position3 3")]
+    #[test_case(POS_3, 4, [43_238, 3_348, 123, 0, 0, 1_680]; "position3 4")]
+    #[test_case(POS_4, 2, [264, 87, 0, 6, 48, 10]; "position4 2")]
+    #[test_case(POS_4, 3, [9_467, 1_021, 4, 0, 120, 38]; "position4 3")]
+    fn test_perft_detailed(fen: &str, depth: u16, expected: [u64; 6]) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let [nodes, captures, en_passant, castles, promotions, checks] = expected;
+
+        pretty_assertions::assert_eq!(
+            perft_detailed(&mut pos, depth),
+            PerftStats {
+                nodes,
+                captures,
+                en_passant,
+                castles,
+                promotions,
+                checks,
+            }
+        );
+        pretty_assertions::assert_eq!(pos.to_fen(), fen);
+    }
+
+    #[test_case("4k3/8/8/8/8/8/8/4RK2 w - - 0 1"; "rook check")]
+    #[test_case("rnbqkbnr/ppppp1pp/8/5p1Q/8/4P3/PPPP1PPP/RNB1KBNR w KQkq - 0 3"; "queen check")]
+    fn test_perft_fen_illegal_position(fen: &str) {
+        pretty_assertions::assert_eq!(
+            perft_fen(fen, 2),
+            Err(ParseFenError::IllegalPosition(
+                crate::error::InvalidPosition::OpponentInCheck
+            ))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "perft on an illegal position")]
+    fn test_position_perft_illegal_position() {
+        let mut pos = Position::from_fen("4k3/8/8/8/8/8/8/4RK2 w - - 0 1").expect("valid fen");
+        pos.perft(2);
+    }
+
+    #[test]
+    fn test_perft_divide_san() {
+        let mut pos = Position::from_fen(POS_2).expect("valid position");
+        let mut moves: Vec<_> = perft_divide_san(&mut pos, 2)
+            .into_iter()
+            .map(|(m, _)| m)
+            .collect();
+        let mut expected = [
+            "a3", "b3", "g3", "d6", "a4", "g4", "gxh3", "dxe6", "Nb1", "Nd1", "Na4", "Nb5", "Nd3",
+            "Nc4", "Ng4", "Nc6", "Nxg6", "Nxd7", "Nxf7", "Bc1", "Be3", "Bf4", "Bg5", "Bh6", "Bd1",
+            "Bf1", "Bd3", "Bc4", "Bb5", "Bxa6", "Rb1", "Rc1", "Rd1", "Rf1", "Rg1", "Qd3", "Qe3",
+            "Qg3", "Qxh3", "Qf4", "Qg4", "Qf5", "Qh5", "Qxf6", "Kd1", "Kf1", "O-O", "O-O-O",
+        ];
+        moves.sort_unstable();
+        expected.sort_unstable();
+
+        pretty_assertions::assert_eq!(moves, expected);
+        pretty_assertions::assert_eq!(
+            perft_divide_san(&mut pos, 2)
+                .iter()
+                .map(|(_, count)| count)
+                .sum::<u64>(),
+            perft(&mut pos, 2)
+        );
+    }
 }
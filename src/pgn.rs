@@ -0,0 +1,306 @@
+//! Importing games in [PGN] format.
+//!
+//! [PGN]: https://en.wikipedia.org/wiki/Portable_Game_Notation
+
+use crate::error::ParsePgnError;
+use crate::BitMove;
+use crate::Position;
+
+/// A game read from a PGN, consisting of its tag pairs, the starting position and the moves
+/// played from there.
+#[derive(Debug, Clone)]
+pub struct Game {
+    tags: Vec<(String, String)>,
+    start: Position,
+    moves: Vec<BitMove>,
+}
+
+impl Game {
+    /// Parses a single game in [PGN] format.
+    ///
+    /// The game starts from the standard starting position unless there is a `FEN` tag.
+    /// Comments (`{...}` and `; ...`), variations (`(...)`), numeric annotation glyphs (`$1`),
+    /// move numbers and the game result are skipped. Every move is resolved against the running
+    /// position, see [`Position::parse_san`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::pgn::Game;
+    ///
+    /// let game = Game::from_pgn(r#"
+    /// [Event "Casual game"]
+    /// [Result "0-1"]
+    ///
+    /// 1. f3 {A terrible idea.} e5 2. g4?? (2. e4 $1) Qh4# 0-1
+    /// "#).unwrap();
+    ///
+    /// assert_eq!(game.tag("Event"), Some("Casual game"));
+    /// assert_eq!(game.moves().len(), 4);
+    /// assert!(game.position().is_checkmate());
+    /// ```
+    ///
+    /// [PGN]: https://en.wikipedia.org/wiki/Portable_Game_Notation
+    pub fn from_pgn(pgn: &str) -> Result<Self, ParsePgnError<'_>> {
+        let (tags, movetext) = parse_tags(pgn)?;
+
+        let start = match tags.iter().find(|(name, _)| *name == "FEN") {
+            Some((_, fen)) => Position::from_fen(fen).map_err(ParsePgnError::InvalidFen)?,
+            None => Position::new(),
+        };
+
+        let mut pos = start.clone();
+        let mut moves = Vec::new();
+        for san in tokenize_movetext(movetext)? {
+            let m = pos
+                .parse_san(san)
+                .map_err(|error| ParsePgnError::InvalidMove {
+                    move_number: pos.ply.div_ceil(2),
+                    san,
+                    error,
+                })?;
+            pos.make_bit_move(m);
+            moves.push(m);
+        }
+
+        Ok(Self {
+            tags: tags
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), unescape(value)))
+                .collect(),
+            start,
+            moves,
+        })
+    }
+
+    /// Returns the value of a tag pair, e.g. `White` or `Result`.
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns all tag pairs in the order they appear in the PGN.
+    pub fn tags(&self) -> &[(String, String)] {
+        &self.tags
+    }
+
+    /// Returns the position the game started from.
+    pub fn start(&self) -> &Position {
+        &self.start
+    }
+
+    /// Returns the moves of the game.
+    pub fn moves(&self) -> &[BitMove] {
+        &self.moves
+    }
+
+    /// Returns the position at the end of the game.
+    pub fn position(&self) -> Position {
+        let mut pos = self.start.clone();
+        for &m in &self.moves {
+            pos.make_bit_move(m);
+        }
+        pos
+    }
+}
+
+/// A tag pair as written in the PGN, i.e. the value is still escaped.
+type RawTag<'a> = (&'a str, &'a str);
+
+/// Splits the tag pairs off the start of the PGN and returns them together with the remaining
+/// movetext.
+fn parse_tags(pgn: &str) -> Result<(Vec<RawTag<'_>>, &str), ParsePgnError<'_>> {
+    let mut tags = Vec::new();
+    let mut rest = pgn.trim_start();
+
+    while rest.starts_with('[') {
+        let end = rest.find(']').ok_or(ParsePgnError::InvalidTag(rest))?;
+        let tag = &rest[1..end];
+        let invalid = || ParsePgnError::InvalidTag(&rest[..=end]);
+
+        let (name, value) = tag
+            .trim()
+            .split_once(char::is_whitespace)
+            .ok_or_else(invalid)?;
+        let value = value
+            .trim()
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .ok_or_else(invalid)?;
+        tags.push((name, value));
+
+        rest = rest[end + 1..].trim_start();
+    }
+    Ok((tags, rest))
+}
+
+/// Removes the backslash escapes from a tag value.
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.extend(chars.next()),
+            c => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+/// Returns the moves of the movetext in standard algebraic notation without any comments,
+/// variations, annotation glyphs, move numbers or the game result.
+fn tokenize_movetext(movetext: &str) -> Result<Vec<&str>, ParsePgnError<'_>> {
+    let mut tokens = Vec::new();
+    let mut variation_depth = 0;
+    let mut rest = movetext;
+
+    while let Some(c) = rest.chars().next() {
+        match c {
+            '{' => {
+                let end = rest.find('}').ok_or(ParsePgnError::UnterminatedComment)?;
+                rest = &rest[end + 1..];
+            }
+            ';' => rest = rest.find('\n').map_or("", |end| &rest[end + 1..]),
+            '(' => {
+                variation_depth += 1;
+                rest = &rest[1..];
+            }
+            ')' => {
+                if variation_depth == 0 {
+                    return Err(ParsePgnError::UnbalancedVariation);
+                }
+                variation_depth -= 1;
+                rest = &rest[1..];
+            }
+            c if c.is_whitespace() => rest = &rest[c.len_utf8()..],
+            _ => {
+                let end = rest
+                    .find(|c: char| c.is_whitespace() || "{};()".contains(c))
+                    .unwrap_or(rest.len());
+                let (token, remaining) = rest.split_at(end);
+                rest = remaining;
+
+                if variation_depth > 0 {
+                    continue;
+                }
+                // strip move numbers, which may be directly followed by the move
+                let san = match token.rfind('.') {
+                    Some(i) if token.starts_with(|c: char| c.is_ascii_digit()) => &token[i + 1..],
+                    _ => token,
+                };
+                if san.is_empty()
+                    || san.starts_with('$')
+                    || matches!(san, "1-0" | "0-1" | "1/2-1/2" | "*")
+                {
+                    continue;
+                }
+                tokens.push(san);
+            }
+        }
+    }
+
+    if variation_depth != 0 {
+        return Err(ParsePgnError::UnbalancedVariation);
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+    use crate::error::{ParseFenError, ParseSanError};
+
+    const GAME_OF_THE_CENTURY: &str = r#"[Event "Third Rosenwald Trophy"]
+[Site "New York, NY USA"]
+[Date "1956.10.17"]
+[Round "8"]
+[White "Donald Byrne"]
+[Black "Robert James Fischer"]
+[Result "0-1"]
+
+1. Nf3 Nf6 2. c4 g6 3. Nc3 Bg7 4. d4 O-O 5. Bf4 d5 6. Qb3 dxc4 7. Qxc4 c6 8. e4
+Nbd7 9. Rd1 Nb6 10. Qc5 Bg4 11. Bg5 Na4 12. Qa3 Nxc3 13. bxc3 Nxe4 14. Bxe7 Qb6
+15. Bc4 Nxc3 16. Bc5 Rfe8+ 17. Kf1 Be6 18. Bxb6 Bxc4+ 19. Kg1 Ne2+ 20. Kf1 Nxd4+
+21. Kg1 Ne2+ 22. Kf1 Nc3+ 23. Kg1 axb6 24. Qb4 Ra4 25. Qxb6 Nxd1 26. h3 Rxa2 27.
+Kh2 Nxf2 28. Re1 Rxe1 29. Qd8+ Bf8 30. Nxe1 Bd5 31. Nf3 Ne4 32. Qb8 b5 33. h4 h5
+34. Ne5 Kg7 35. Kg1 Bc5+ 36. Kf1 Ng3+ 37. Ke1 Bb4+ 38. Kd1 Bb3+ 39. Kc1 Ne2+ 40.
+Kb1 Nc3+ 41. Kc1 Rc2# 0-1
+"#;
+
+    #[test]
+    fn test_game_from_pgn() {
+        let game = Game::from_pgn(GAME_OF_THE_CENTURY).expect("valid pgn");
+
+        pretty_assertions::assert_eq!(game.tags().len(), 7);
+        pretty_assertions::assert_eq!(game.tag("Black"), Some("Robert James Fischer"));
+        pretty_assertions::assert_eq!(game.tag("ECO"), None);
+        pretty_assertions::assert_eq!(game.start(), &Position::new());
+        pretty_assertions::assert_eq!(game.moves().len(), 82);
+
+        let pos = game.position();
+        pretty_assertions::assert_eq!(
+            pos.to_fen(),
+            "1Q6/5pk1/2p3p1/1p2N2p/1b5P/1bn5/2r3P1/2K5 w - - 16 42"
+        );
+        assert!(pos.is_checkmate());
+    }
+
+    #[test]
+    fn test_game_from_pgn_long_game() {
+        let movetext: String = (1..=75)
+            .map(|n| format!("{}. Nf3 Nf6 {}. Ng1 Ng8 ", 2 * n - 1, 2 * n))
+            .collect();
+        let game = Game::from_pgn(&movetext).expect("valid pgn");
+
+        pretty_assertions::assert_eq!(game.moves().len(), 300);
+        pretty_assertions::assert_eq!(
+            game.position().to_fen(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 300 151"
+        );
+    }
+
+    #[test_case("1. e4 {a comment (with parentheses)} e5", 2; "comment")]
+    #[test_case("1. e4 ; rest of the line\n e5", 2; "line comment")]
+    #[test_case("1. e4 (1. d4 d5 (1... Nf6)) 1... e5", 2; "nested variations")]
+    #[test_case("1. e4! $1 e5 $2 2. Nf3", 3; "annotation glyphs")]
+    #[test_case("1.e4 e5 2.Nf3 1/2-1/2", 3; "result")]
+    #[test_case("", 0; "empty")]
+    fn test_game_from_pgn_movetext(pgn: &str, moves: usize) {
+        let game = Game::from_pgn(pgn).expect("valid pgn");
+        pretty_assertions::assert_eq!(game.moves().len(), moves);
+    }
+
+    #[test]
+    fn test_game_from_pgn_fen_tag() {
+        let game = Game::from_pgn(
+            "[SetUp \"1\"]\n[FEN \"6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\"]\n\n1. Ra8# 1-0",
+        )
+        .expect("valid pgn");
+
+        pretty_assertions::assert_eq!(game.start().to_fen(), "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        assert!(game.position().is_checkmate());
+    }
+
+    #[test]
+    fn test_game_from_pgn_escaped_tag() {
+        let game =
+            Game::from_pgn(r#"[Annotator "The \"Best\" \\ Annotator"] 1. e4"#).expect("valid pgn");
+        pretty_assertions::assert_eq!(game.tag("Annotator"), Some(r#"The "Best" \ Annotator"#));
+    }
+
+    #[test_case("1. e4 e5 2. Ke3", ParsePgnError::InvalidMove { move_number: 2, san: "Ke3", error: ParseSanError::IllegalMove }; "illegal move")]
+    #[test_case("1. e4 e5 2. Nf3 Zz9", ParsePgnError::InvalidMove { move_number: 2, san: "Zz9", error: ParseSanError::InvalidSyntax }; "invalid syntax")]
+    #[test_case("[Event \"x\" 1. e4", ParsePgnError::InvalidTag("[Event \"x\" 1. e4"); "unterminated tag")]
+    #[test_case("[Event x] 1. e4", ParsePgnError::InvalidTag("[Event x]"); "unquoted tag value")]
+    #[test_case("[FEN \"8/8/8\"] 1. e4", ParsePgnError::InvalidFen(ParseFenError::TooShort); "invalid fen")]
+    #[test_case("1. e4 {comment e5", ParsePgnError::UnterminatedComment; "unterminated comment")]
+    #[test_case("1. e4 (1. d4 e5", ParsePgnError::UnbalancedVariation; "unterminated variation")]
+    #[test_case("1. e4 1. d4) e5", ParsePgnError::UnbalancedVariation; "unopened variation")]
+    fn test_game_from_pgn_invalid(pgn: &str, expected: ParsePgnError<'_>) {
+        pretty_assertions::assert_eq!(Game::from_pgn(pgn).map(|_| ()), Err(expected));
+    }
+}
@@ -1,11 +1,13 @@
+use std::convert::TryFrom;
 use std::fmt;
 
-use std::ops::Index;
+use std::ops::{Index, IndexMut};
 
+use crate::error::ParseCharError;
 use crate::Color;
 
 /// The type of a piece.
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
 pub struct PieceType(u8);
 
 impl PieceType {
@@ -71,6 +73,30 @@ impl PieceType {
         }
     }
 
+    /// Returns the material value of the piece type in centipawns. The king can't be captured,
+    /// so its value is just larger than all the other pieces combined.
+    ///
+    /// These are the values used e.g. by the static exchange evaluation. The evaluation uses its
+    /// own values that depend on the game phase.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::PieceType;
+    ///
+    /// assert_eq!(PieceType::PAWN.value(), 100);
+    /// assert_eq!(PieceType::KNIGHT.value(), 320);
+    /// assert_eq!(PieceType::BISHOP.value(), 330);
+    /// assert_eq!(PieceType::ROOK.value(), 500);
+    /// assert_eq!(PieceType::QUEEN.value(), 900);
+    /// assert_eq!(PieceType::KING.value(), 20_000);
+    /// ```
+    #[inline]
+    pub fn value(self) -> i32 {
+        const VALUES: [i32; 6] = [100, 320, 330, 500, 900, 20_000];
+        VALUES[self]
+    }
+
     #[inline]
     pub(crate) fn from_u8(n: u8) -> Self {
         Self(n)
@@ -78,7 +104,7 @@ impl PieceType {
 
     #[inline]
     pub(crate) const fn to_u8(self) -> u8 {
-        self.0 as u8
+        self.0
     }
 }
 
@@ -90,6 +116,27 @@ impl<T> Index<PieceType> for [T; 6] {
     }
 }
 
+impl TryFrom<char> for PieceType {
+    type Error = ParseCharError;
+
+    /// Converts a character to a `PieceType`, see [`PieceType::from_char`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    ///
+    /// use chers::error::ParseCharError;
+    /// use chers::PieceType;
+    ///
+    /// assert_eq!(PieceType::try_from('n'), Ok(PieceType::KNIGHT));
+    /// assert_eq!(PieceType::try_from('x'), Err(ParseCharError::InvalidPiece('x')));
+    /// ```
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        Self::from_char(c).ok_or(ParseCharError::InvalidPiece(c))
+    }
+}
+
 impl fmt::Display for PieceType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.to_char())
@@ -253,10 +300,94 @@ impl Piece {
         self.piece_type() == piece_type
     }
 
+    /// Returns the material value of the piece in centipawns, which is the same for both colors.
+    /// See [`PieceType::value`]. [`Piece::EMPTY`] has a value of 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Piece;
+    ///
+    /// assert_eq!(Piece::W_PAWN.value(), 100);
+    /// assert_eq!(Piece::B_ROOK.value(), 500);
+    /// assert_eq!(Piece::W_QUEEN.value(), Piece::B_QUEEN.value());
+    /// assert_eq!(Piece::EMPTY.value(), 0);
+    /// ```
+    #[inline]
+    pub fn value(self) -> i32 {
+        if self.is_piece() {
+            self.piece_type().value()
+        } else {
+            0
+        }
+    }
+
     #[inline]
     pub(crate) fn is_piece(self) -> bool {
         self.0 < Self::EMPTY.0
     }
+
+    /// Returns a unique index in the range `0..12` for each of the twelve pieces: the white
+    /// pieces from pawn to king are `0..6`, the black ones `6..12`. This is useful for tables
+    /// with an entry per piece, which can also be indexed by a `Piece` directly.
+    ///
+    /// # Panics
+    ///
+    /// Panics in debug builds if called on [`Piece::EMPTY`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Piece;
+    ///
+    /// assert_eq!(Piece::W_PAWN.to_index(), 0);
+    /// assert_eq!(Piece::W_KING.to_index(), 5);
+    /// assert_eq!(Piece::B_PAWN.to_index(), 6);
+    /// assert_eq!(Piece::B_KING.to_index(), 11);
+    ///
+    /// let values = [1, 3, 3, 5, 9, 0, -1, -3, -3, -5, -9, 0];
+    /// assert_eq!(values[Piece::B_ROOK], -5);
+    /// ```
+    #[inline]
+    pub fn to_index(self) -> usize {
+        debug_assert!(self.is_piece());
+        self.color().to_usize() * 6 + self.piece_type().0 as usize
+    }
+}
+
+impl<T> Index<Piece> for [T; 12] {
+    type Output = T;
+
+    fn index(&self, index: Piece) -> &Self::Output {
+        &self[index.to_index()]
+    }
+}
+
+impl<T> IndexMut<Piece> for [T; 12] {
+    fn index_mut(&mut self, index: Piece) -> &mut Self::Output {
+        &mut self[index.to_index()]
+    }
+}
+
+impl TryFrom<char> for Piece {
+    type Error = ParseCharError;
+
+    /// Converts a character to a `Piece`, see [`Piece::from_char`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    ///
+    /// use chers::error::ParseCharError;
+    /// use chers::Piece;
+    ///
+    /// assert_eq!(Piece::try_from('n'), Ok(Piece::B_KNIGHT));
+    /// assert_eq!(Piece::try_from('x'), Err(ParseCharError::InvalidPiece('x')));
+    /// ```
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        Self::from_char(c).ok_or(ParseCharError::InvalidPiece(c))
+    }
 }
 
 impl fmt::Display for Piece {
@@ -272,3 +403,38 @@ impl fmt::Display for Piece {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_piece_to_index() {
+        let mut seen = [false; 12];
+        for color in Color::ALL {
+            for t in 0..6 {
+                let index = Piece::new(PieceType::from_u8(t), color).to_index();
+                assert!(!seen[index], "index {} used twice", index);
+                seen[index] = true;
+            }
+        }
+        assert!(seen.iter().all(|&s| s));
+    }
+
+    #[test]
+    fn test_piece_index_mut() {
+        let mut counts = [0; 12];
+        counts[Piece::W_KNIGHT] += 2;
+        counts[Piece::B_KNIGHT] += 1;
+
+        pretty_assertions::assert_eq!(counts[Piece::W_KNIGHT], 2);
+        pretty_assertions::assert_eq!(counts[Piece::B_KNIGHT], 1);
+        pretty_assertions::assert_eq!(counts.iter().sum::<i32>(), 3);
+    }
+
+    #[test]
+    fn test_piece_value_of_non_pieces() {
+        pretty_assertions::assert_eq!(Piece::EMPTY.value(), 0);
+        pretty_assertions::assert_eq!(Piece::OFF_BOARD.value(), 0);
+    }
+}
@@ -1,8 +1,10 @@
-use arrayvec::ArrayVec;
 use std::fmt;
 
-use crate::utils;
+use crate::error::InvalidPosition;
+use crate::fen;
+use crate::zobrist;
 use crate::BitMove;
+use crate::CastlingRights;
 use crate::Color;
 use crate::File;
 use crate::ParsedMove;
@@ -29,13 +31,178 @@ pub struct Position {
     pub(crate) side_to_move: Color,
     pub(crate) ply: u16,
 
-    pub(crate) state: ArrayVec<PositionState, 256>,
+    /// The state of the current position is the last one, the earlier ones are needed to undo
+    /// moves. It grows with every move, so there is no limit on the length of a game.
+    pub(crate) state: Vec<PositionState>,
+
+    /// The castling rights kept when a piece moves from or to a square, see
+    /// [`CastlingRights::castle_mask`].
+    pub(crate) castle_mask: [u8; 120],
 }
 
 impl Position {
+    /// The number of halfmoves without a capture or pawn move after which the game is drawn.
+    pub const FIFTY_MOVE_LIMIT: u16 = 100;
+
     /// Creates a new position that represents the starting position.
     pub fn new() -> Self {
-        Self::from_fen(utils::fen::STARTING_POSITION).unwrap()
+        Self::from_fen(fen::STARTING_FEN).unwrap()
+    }
+
+    /// Creates a position without any pieces, white to move and no castling rights. Use it
+    /// together with [`Position::set_piece`] to set up a position piece by piece.
+    ///
+    /// The empty board isn't a valid position, see [`Position::is_legal_position`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Piece, Position, Square};
+    ///
+    /// let mut pos = Position::empty();
+    /// assert_eq!(pos.to_fen(), "8/8/8/8/8/8/8/8 w - - 0 1");
+    ///
+    /// pos.set_piece(Square::E1, Piece::W_KING);
+    /// pos.set_piece(Square::E8, Piece::B_KING);
+    /// assert!(pos.is_legal_position());
+    /// ```
+    pub fn empty() -> Self {
+        Self::from_parts(
+            fen::EMPTY_BOARD,
+            Color::WHITE,
+            CastlingRights::new(false, false, false, false),
+            Square::NO_SQ,
+            0,
+            1,
+        )
+    }
+
+    /// Creates a position from its raw parts. The position is not validated.
+    pub(crate) fn from_parts(
+        pieces: [Piece; 120],
+        side_to_move: Color,
+        castling_rights: CastlingRights,
+        ep_square: Square,
+        halfmove_clock: u16,
+        fullmove_number: u16,
+    ) -> Self {
+        let ply = fullmove_number * 2 - side_to_move.map(1, 0);
+
+        let mut state = Vec::with_capacity(256);
+        state.push(PositionState::new(
+            castling_rights,
+            ep_square,
+            halfmove_clock,
+        ));
+
+        let mut pos = Self {
+            pieces,
+            king_square: [Square::A1; 2],
+            side_to_move,
+            ply,
+            state,
+            castle_mask: castling_rights.castle_mask(),
+        };
+        pos.find_kings();
+        pos.state[0].key = pos.zobrist_hash();
+        pos
+    }
+
+    /// Looks up the squares of the kings on the board. If there is more than one king of a color
+    /// the last one from a1 to h8 is used.
+    fn find_kings(&mut self) {
+        for sq in Square::iter() {
+            if self.pieces[sq].is_type(PieceType::KING) {
+                self.king_square[self.pieces[sq].color()] = sq;
+            }
+        }
+    }
+
+    /// Checks that the position could occur in a game. It has to contain exactly one king per
+    /// side, no pawns on the first and last rank, castling rights and the en passant square have
+    /// to match the pieces on the board and the side not to move must not be in check.
+    pub(crate) fn validate(&self) -> Result<(), InvalidPosition> {
+        let mut kings = [0; 2];
+        for sq in Square::iter() {
+            let piece = self.pieces[sq];
+            if !piece.is_piece() {
+                continue;
+            }
+            match piece.piece_type() {
+                PieceType::KING => kings[piece.color()] += 1,
+                PieceType::PAWN if sq.rank() == Rank::FIRST || sq.rank() == Rank::EIGHTH => {
+                    return Err(InvalidPosition::PawnOnBackRank(sq));
+                }
+                _ => {}
+            }
+        }
+        for color in Color::ALL {
+            if kings[color] != 1 {
+                return Err(InvalidPosition::WrongNumberOfKings(color));
+            }
+        }
+
+        let state = &self.state[self.state.len() - 1];
+        let castling_rights = state.castling_rights;
+        for color in Color::ALL {
+            let rank = color.map(Rank::FIRST, Rank::EIGHTH);
+            for kingside in [true, false] {
+                if !castling_rights.has_right(color, kingside) {
+                    continue;
+                }
+                let king_square = Square::new(castling_rights.king_file(color), rank);
+                let (rook_square, _) = castling_rights.rook_squares(color, kingside);
+                if self.pieces[king_square] != Piece::new(PieceType::KING, color)
+                    || self.pieces[rook_square] != Piece::new(PieceType::ROOK, color)
+                {
+                    return Err(InvalidPosition::InvalidCastlingRights(castling_rights));
+                }
+            }
+        }
+
+        let ep_square = state.ep_square;
+        if ep_square != Square::NO_SQ {
+            let us = self.side_to_move;
+            let (ep_rank, pawn_rank) =
+                us.map((Rank::SIXTH, Rank::FIFTH), (Rank::THIRD, Rank::FOURTH));
+            if ep_square.rank() != ep_rank
+                || self.pieces[ep_square] != Piece::EMPTY
+                || self.pieces[Square::new(ep_square.file(), pawn_rank)]
+                    != Piece::new(PieceType::PAWN, !us)
+            {
+                return Err(InvalidPosition::InvalidEnPassantSquare(ep_square));
+            }
+        }
+
+        if self.in_check(!self.side_to_move) {
+            return Err(InvalidPosition::OpponentInCheck);
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if the position could occur in a game, i.e. there is exactly one king per
+    /// side, there are no pawns on the first and last rank, the castling rights and the en passant
+    /// square match the board and the side not to move is not in check, because that would mean
+    /// the previous move was illegal.
+    ///
+    /// Positions created with [`Position::from_fen`] or set up with [`Position::empty`] and
+    /// [`Position::set_piece`] are not validated, so this can be used to reject them before
+    /// searching or counting moves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// assert!(Position::new().is_legal_position());
+    ///
+    /// // white to move, but the black king is attacked by the rook
+    /// let pos = Position::from_fen("4k3/8/8/8/8/8/8/4RK2 w - - 0 1").unwrap();
+    /// assert!(!pos.is_legal_position());
+    /// ```
+    pub fn is_legal_position(&self) -> bool {
+        self.validate().is_ok()
     }
 
     /// Returns who's turn it is
@@ -43,25 +210,540 @@ impl Position {
         self.side_to_move
     }
 
-    /// Returns the `Piece` on a given `Square`
+    /// Returns the number of halfmoves since the last capture or pawn move, which is used for the
+    /// fifty-move rule.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{ParsedMove, Position};
+    ///
+    /// let mut pos = Position::new();
+    /// assert_eq!(pos.halfmove_clock(), 0);
+    ///
+    /// pos.make_move(ParsedMove::from_coordinate_notation("g1f3").unwrap());
+    /// assert_eq!(pos.halfmove_clock(), 1);
+    ///
+    /// pos.make_move(ParsedMove::from_coordinate_notation("e7e5").unwrap());
+    /// assert_eq!(pos.halfmove_clock(), 0);
+    /// ```
+    pub fn halfmove_clock(&self) -> u16 {
+        self.state[self.state.len() - 1].halfmove_clock
+    }
+
+    /// Returns the number of the full move, which starts at 1 and is incremented after black's
+    /// move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{ParsedMove, Position};
+    ///
+    /// let mut pos = Position::new();
+    /// assert_eq!(pos.fullmove_number(), 1);
+    ///
+    /// pos.make_move(ParsedMove::from_coordinate_notation("e2e4").unwrap());
+    /// assert_eq!(pos.fullmove_number(), 1);
+    ///
+    /// pos.make_move(ParsedMove::from_coordinate_notation("e7e5").unwrap());
+    /// assert_eq!(pos.fullmove_number(), 2);
+    /// ```
+    pub fn fullmove_number(&self) -> u16 {
+        self.ply.div_ceil(2)
+    }
+
+    /// Returns the square a pawn can be captured on en passant or `None` if the last move wasn't
+    /// a double pawn push. Like in a FEN, the square is set even if no en passant capture is
+    /// possible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{ParsedMove, Position, Square};
+    ///
+    /// let mut pos = Position::new();
+    /// assert_eq!(pos.en_passant_square(), None);
+    ///
+    /// pos.make_move(ParsedMove::from_coordinate_notation("e2e4").unwrap());
+    /// assert_eq!(pos.en_passant_square(), Some(Square::E3));
+    /// ```
+    pub fn en_passant_square(&self) -> Option<Square> {
+        let ep_square = self.state[self.state.len() - 1].ep_square;
+        if ep_square == Square::NO_SQ {
+            None
+        } else {
+            Some(ep_square)
+        }
+    }
+
+    /// Returns the castling rights of both players.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{CastlingRights, ParsedMove, Position};
+    ///
+    /// let mut pos = Position::new();
+    /// assert_eq!(pos.castling_rights(), CastlingRights::new(true, true, true, true));
+    ///
+    /// pos.make_move(ParsedMove::from_coordinate_notation("e2e4").unwrap());
+    /// pos.make_move(ParsedMove::from_coordinate_notation("e7e5").unwrap());
+    /// pos.make_move(ParsedMove::from_coordinate_notation("e1e2").unwrap());
+    /// assert_eq!(pos.castling_rights(), CastlingRights::new(false, false, true, true));
+    /// ```
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.state[self.state.len() - 1].castling_rights
+    }
+
+    /// Returns the square of the king of `color`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Color, ParsedMove, Position, Square};
+    ///
+    /// let mut pos = Position::new();
+    /// assert_eq!(pos.king_square(Color::WHITE), Square::E1);
+    /// assert_eq!(pos.king_square(Color::BLACK), Square::E8);
+    ///
+    /// pos.make_move(ParsedMove::from_coordinate_notation("e2e4").unwrap());
+    /// pos.make_move(ParsedMove::from_coordinate_notation("e7e5").unwrap());
+    /// pos.make_move(ParsedMove::from_coordinate_notation("e1e2").unwrap());
+    /// assert_eq!(pos.king_square(Color::WHITE), Square::E2);
+    /// ```
+    pub fn king_square(&self, color: Color) -> Square {
+        self.king_square[color]
+    }
+
+    /// Returns the `Piece` on a given `Square`, which is [`Piece::EMPTY`] if there is none. See
+    /// also [`Position::piece_at`].
     pub fn get_square(&self, sq: Square) -> Piece {
         self.pieces[sq]
     }
 
+    /// Returns the `Piece` on a given `Square` or `None` if the square is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Piece, Position, Square};
+    ///
+    /// let pos = Position::new();
+    ///
+    /// assert_eq!(pos.piece_at(Square::D1), Some(Piece::W_QUEEN));
+    /// assert_eq!(pos.piece_at(Square::D4), None);
+    /// ```
+    pub fn piece_at(&self, sq: Square) -> Option<Piece> {
+        let piece = self.pieces[sq];
+        if piece.is_piece() {
+            Some(piece)
+        } else {
+            None
+        }
+    }
+
+    /// Returns an iterator over all occupied squares and the pieces on them, ordered from a1 to
+    /// h8 (a1, b1, ..., h1, a2, ...).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Piece, Position, Square};
+    ///
+    /// let pos = Position::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+    ///
+    /// assert_eq!(
+    ///     pos.iter_pieces().collect::<Vec<_>>(),
+    ///     [
+    ///         (Square::E1, Piece::W_KING),
+    ///         (Square::E2, Piece::W_PAWN),
+    ///         (Square::E8, Piece::B_KING),
+    ///     ]
+    /// );
+    /// ```
+    pub fn iter_pieces(&self) -> impl Iterator<Item = (Square, Piece)> + '_ {
+        (Square::A1.to_usize()..=Square::H8.to_usize()).filter_map(move |index| {
+            let piece = self.pieces[index];
+            piece.is_piece().then(|| (Square::from_index(index), piece))
+        })
+    }
+
+    /// Returns the move that led to the current position or `None` if no move has been made.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{ParsedMove, Position};
+    ///
+    /// let mut pos = Position::new();
+    /// assert_eq!(pos.last_move(), None);
+    ///
+    /// pos.make_move(ParsedMove::from_coordinate_notation("e2e4").unwrap());
+    /// assert_eq!(pos.last_move().unwrap().to_string(), "e2e4");
+    /// ```
+    pub fn last_move(&self) -> Option<BitMove> {
+        let m = self.state[self.state.len() - 1].prev_move;
+        if m.is_null() {
+            None
+        } else {
+            Some(m)
+        }
+    }
+
+    /// Returns the piece captured by the last move or `None` if it wasn't a capture or no move has
+    /// been made.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{ParsedMove, Piece, Position};
+    ///
+    /// let mut pos = Position::from_fen("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1").unwrap();
+    /// assert_eq!(pos.last_captured(), None);
+    ///
+    /// pos.make_move(ParsedMove::from_coordinate_notation("e4d5").unwrap());
+    /// assert_eq!(pos.last_captured(), Some(Piece::B_PAWN));
+    /// ```
+    pub fn last_captured(&self) -> Option<Piece> {
+        let piece = self.state[self.state.len() - 1].captured_piece;
+        if piece.is_piece() {
+            Some(piece)
+        } else {
+            None
+        }
+    }
+
+    /// Returns all squares whose contents differ between `self` and `other`, together with the
+    /// piece on the square in `self` and in `other` (which may be [`Piece::EMPTY`]). The squares
+    /// are ordered from a1 to h8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{ParsedMove, Piece, Position, Square};
+    ///
+    /// let before = Position::new();
+    /// let mut after = before.clone();
+    /// after.make_move(ParsedMove::from_coordinate_notation("g1f3").unwrap());
+    ///
+    /// assert_eq!(
+    ///     before.diff(&after),
+    ///     vec![
+    ///         (Square::G1, Piece::W_KNIGHT, Piece::EMPTY),
+    ///         (Square::F3, Piece::EMPTY, Piece::W_KNIGHT),
+    ///     ]
+    /// );
+    /// ```
+    pub fn diff(&self, other: &Position) -> Vec<(Square, Piece, Piece)> {
+        let mut diff = Vec::new();
+        for square in Square::iter() {
+            if self.pieces[square] != other.pieces[square] {
+                diff.push((square, self.pieces[square], other.pieces[square]));
+            }
+        }
+        diff
+    }
+
+    /// Puts `piece` on `sq`, replacing the piece that was there before.
+    ///
+    /// The position isn't validated, so it is possible to create positions that can't occur in a
+    /// game, see [`Position::is_legal_position`]. This clears the move history, so moves played before can
+    /// no longer be undone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Piece, Position, Square};
+    ///
+    /// let mut pos = Position::new();
+    /// pos.set_piece(Square::D1, Piece::W_KNIGHT);
+    ///
+    /// assert_eq!(pos.to_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBNKBNR w KQkq - 0 1");
+    /// ```
+    pub fn set_piece(&mut self, sq: Square, piece: Piece) {
+        debug_assert!(piece != Piece::OFF_BOARD);
+        debug_assert!(self.pieces[sq] != Piece::OFF_BOARD);
+        self.clear_history();
+        let old = self.pieces[sq];
+        self.pieces[sq] = piece;
+        self.state[0].key ^= zobrist::piece_key(old, sq) ^ zobrist::piece_key(piece, sq);
+        if old.is_type(PieceType::KING) || piece.is_type(PieceType::KING) {
+            self.find_kings();
+        }
+    }
+
+    /// Removes the piece on `sq`, leaving it empty.
+    ///
+    /// This clears the move history, so moves played before can no longer be undone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Position, Square};
+    ///
+    /// let mut pos = Position::new();
+    /// pos.remove_piece(Square::D8);
+    ///
+    /// assert_eq!(pos.to_fen(), "rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    /// ```
+    pub fn remove_piece(&mut self, sq: Square) {
+        self.set_piece(sq, Piece::EMPTY);
+    }
+
+    /// Sets the side to move. The fullmove number is left unchanged.
+    ///
+    /// This clears the move history, so moves played before can no longer be undone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Color, Position};
+    ///
+    /// let mut pos = Position::new();
+    /// pos.set_side_to_move(Color::BLACK);
+    ///
+    /// assert_eq!(pos.to_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1");
+    /// ```
+    pub fn set_side_to_move(&mut self, color: Color) {
+        if self.side_to_move != color {
+            self.ply = color.map(self.ply - 1, self.ply + 1);
+            self.side_to_move = color;
+        }
+        self.clear_history();
+        self.state[0].key = self.zobrist_hash();
+    }
+
+    /// Sets the castling rights.
+    ///
+    /// This clears the move history, so moves played before can no longer be undone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{CastlingRights, Position};
+    ///
+    /// let mut pos = Position::new();
+    /// pos.set_castling_rights(CastlingRights::new(false, false, true, true));
+    ///
+    /// assert_eq!(pos.to_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w kq - 0 1");
+    /// ```
+    pub fn set_castling_rights(&mut self, castling_rights: CastlingRights) {
+        self.clear_history();
+        self.state[0].castling_rights = castling_rights;
+        self.castle_mask = castling_rights.castle_mask();
+        self.state[0].key = self.zobrist_hash();
+    }
+
+    /// Sets the en passant square. `None` means that no en passant capture is possible.
+    ///
+    /// This clears the move history, so moves played before can no longer be undone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Position, Square};
+    ///
+    /// let mut pos = Position::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+    /// pos.set_en_passant(Some(Square::E3));
+    ///
+    /// assert_eq!(pos.to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+    /// ```
+    pub fn set_en_passant(&mut self, sq: Option<Square>) {
+        self.clear_history();
+        self.state[0].ep_square = sq.unwrap_or(Square::NO_SQ);
+        self.state[0].key = self.zobrist_hash();
+    }
+
+    /// Returns a copy of the position where it is the other side's turn. The en passant square is
+    /// cleared and the copy has no move history.
+    ///
+    /// This is useful to look at a position from the opponent's point of view, e.g. to see what
+    /// they would threaten if they were allowed to move again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Color, Position};
+    ///
+    /// let pos = Position::new();
+    /// let flipped = pos.with_flipped_side();
+    ///
+    /// assert_eq!(pos.side_to_move(), Color::WHITE);
+    /// assert_eq!(flipped.side_to_move(), Color::BLACK);
+    /// ```
+    pub fn with_flipped_side(&self) -> Position {
+        let mut pos = self.clone();
+        pos.set_side_to_move(!self.side_to_move);
+        pos.set_en_passant(None);
+        pos
+    }
+
+    /// Takes a [`Snapshot`] of the position which can later be restored with
+    /// [`Position::restore`].
+    ///
+    /// This copies the board (120 bytes) and the current state, so it is cheap enough to be
+    /// used instead of [`Position::undo_move`] when moves and edits are mixed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{ParsedMove, Position};
+    ///
+    /// let mut pos = Position::new();
+    /// let snapshot = pos.snapshot();
+    ///
+    /// pos.make_move(ParsedMove::from_coordinate_notation("e2e4").unwrap());
+    /// pos.restore(snapshot);
+    ///
+    /// assert_eq!(pos, Position::new());
+    /// ```
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            pieces: self.pieces,
+            king_square: self.king_square,
+            side_to_move: self.side_to_move,
+            ply: self.ply,
+            state: self.state.last().expect("at least one state").clone(),
+            history_len: self.state.len(),
+            prev_key: self.state.iter().rev().nth(1).map(|state| state.key),
+        }
+    }
+
+    /// Restores a position from a [`Snapshot`] in constant time.
+    ///
+    /// The move history from before the snapshot is kept if it is still intact (i.e. the position
+    /// before the last move of the snapshot is still at the same place in the history). Otherwise
+    /// only the snapshotted state is kept and the moves before it can no longer be undone.
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        self.pieces = snapshot.pieces;
+        self.king_square = snapshot.king_square;
+        self.side_to_move = snapshot.side_to_move;
+        self.ply = snapshot.ply;
+        let history_intact = self.state.len() >= snapshot.history_len
+            && (snapshot.history_len == 1
+                || Some(self.state[snapshot.history_len - 2].key) == snapshot.prev_key);
+        if history_intact {
+            self.state.truncate(snapshot.history_len);
+            *self.state.last_mut().expect("at least one state") = snapshot.state;
+        } else {
+            self.state.clear();
+            self.state.push(snapshot.state);
+            self.clear_history();
+        }
+        self.castle_mask = self.state[self.state.len() - 1]
+            .castling_rights
+            .castle_mask();
+    }
+
+    /// Returns whether the position is exactly the one from the snapshot, including the length of
+    /// the history and the last move. Used to check that [`Position::undo_move`] reverts
+    /// [`Position::make_bit_move`].
+    #[cfg(debug_assertions)]
+    pub(crate) fn matches_snapshot(&self, snapshot: &Snapshot) -> bool {
+        let state = &self.state[self.state.len() - 1];
+
+        self.pieces == snapshot.pieces
+            && self.king_square == snapshot.king_square
+            && self.side_to_move == snapshot.side_to_move
+            && self.ply == snapshot.ply
+            && self.state.len() == snapshot.history_len
+            && state == &snapshot.state
+            && state.prev_move == snapshot.state.prev_move
+            && state.captured_piece == snapshot.state.captured_piece
+    }
+
+    /// Drops all but the current `PositionState`. Editing the position makes the previous states
+    /// meaningless.
+    fn clear_history(&mut self) {
+        let mut current = self.state.pop().expect("at least one state");
+        current.prev_move = BitMove::NULL;
+        current.captured_piece = Piece::EMPTY;
+        self.state.clear();
+        self.state.push(current);
+    }
+
+    /// Returns the legal [`BitMove`] matching `m` or `None` if the move is illegal.
+    ///
+    /// Like [`Position::make_move`] this also accepts castling written as the king capturing its
+    /// own rook. The position is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// let mut pos = Position::new();
+    ///
+    /// let m = pos.find_move("e2e4".parse().unwrap()).unwrap();
+    /// assert!(m.is_double_push());
+    /// assert_eq!(pos.find_move("e2e5".parse().unwrap()), None);
+    /// ```
+    pub fn find_move(&mut self, m: ParsedMove) -> Option<BitMove> {
+        let castling_rights = self.state[self.state.len() - 1].castling_rights;
+        let us = self.side_to_move;
+        let legal_moves = self.generate_legal_moves();
+        let king_takes_rook = |bm: &&BitMove| {
+            bm.is_castle()
+                && m.promotion_piece.is_none()
+                && bm.origin() == m.origin
+                && castling_rights.rook_squares(us, bm.is_king_side_castle()).0 == m.target
+        };
+        legal_moves
+            .iter()
+            .find(|bm| *bm == &m)
+            .or_else(|| legal_moves.iter().find(king_takes_rook))
+            .copied()
+    }
+
     /// Makes a move on the current position.
     ///
     /// If the move is illegal `false` will be returned and the position is left unchanged.
     /// Otherwise `true` will be returned.
+    ///
+    /// Castling can also be given as the king capturing its own rook (e.g. `e1h1`), which is how
+    /// castling is written in [Chess960] where the king might only move one square or not at all.
+    ///
+    /// [Chess960]: https://en.wikipedia.org/wiki/Fischer_random_chess
     pub fn make_move(&mut self, m: ParsedMove) -> bool {
-        let legal_moves = self.generate_legal_moves();
-        if let Some(bit_move) = legal_moves.iter().find(|bm| *bm == &m) {
-            self.make_bit_move(*bit_move);
+        if let Some(bit_move) = self.find_move(m) {
+            self.make_bit_move(bit_move);
             true
         } else {
             false
         }
     }
 
+    /// Makes the moves in order.
+    ///
+    /// If one of the moves is illegal its index and the move are returned and the position is left
+    /// after the last legal move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{ParsedMove, Position};
+    ///
+    /// let moves: Vec<ParsedMove> = ["e2e4", "e7e5", "e1e3"]
+    ///     .iter()
+    ///     .map(|m| m.parse().unwrap())
+    ///     .collect();
+    /// let mut pos = Position::new();
+    ///
+    /// assert_eq!(pos.apply_moves(&moves), Err((2, moves[2])));
+    /// assert_eq!(
+    ///     pos.to_fen(),
+    ///     "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2"
+    /// );
+    /// ```
+    pub fn apply_moves(&mut self, moves: &[ParsedMove]) -> Result<(), (usize, ParsedMove)> {
+        for (i, &m) in moves.iter().enumerate() {
+            if !self.make_move(m) {
+                return Err((i, m));
+            }
+        }
+        Ok(())
+    }
+
     /// Makes a move on the current position.
     ///
     /// # Saftey
@@ -80,7 +762,8 @@ impl Position {
         } else {
             state.halfmove_clock + 1
         };
-        let mut castling_rights = state.castling_rights;
+        let prev_castling_rights = state.castling_rights;
+        let mut castling_rights = prev_castling_rights;
         let ep_square = if m.is_double_push() {
             Square::new(m.target().file(), p.color().map(Rank::THIRD, Rank::SIXTH))
         } else {
@@ -98,7 +781,12 @@ impl Position {
             m.target()
         };
 
-        let captured_piece = self.pieces[capture_field];
+        // in Chess960 the king can castle onto the square of its own rook
+        let captured_piece = if m.is_castle() {
+            Piece::EMPTY
+        } else {
+            self.pieces[capture_field]
+        };
 
         debug_assert!(
             (captured_piece == Piece::EMPTY || captured_piece == Piece::OFF_BOARD)
@@ -113,54 +801,33 @@ impl Position {
         };
 
         // castling rights
-        castling_rights.update(m.origin());
-        castling_rights.update(m.target());
+        castling_rights.apply_mask(self.castle_mask[m.origin()] & self.castle_mask[m.target()]);
 
-        self.state.push(PositionState {
+        let mut next = PositionState {
             castling_rights,
             ep_square,
             halfmove_clock,
             prev_move: m,
             captured_piece,
-        });
+            key: 0,
+        };
+        next.key =
+            state.key ^ zobrist::move_key(m, p, piece, captured_piece, capture_field, state, &next);
+        self.state.push(next);
 
         if m.origin() == self.king_square[!self.side_to_move] {
             self.king_square[!self.side_to_move] = m.target();
         }
-        // white castling
-        match p.color() {
-            Color::WHITE => {
-                if m.is_king_side_castle() {
-                    self.pieces[Square::F1] = self.pieces[Square::H1];
-                    self.pieces[Square::G1] = p;
-                    self.pieces[Square::E1] = Piece::EMPTY;
-                    self.pieces[Square::H1] = Piece::EMPTY;
-                    return;
-                }
-                if m.is_queen_side_castle() {
-                    self.pieces[Square::D1] = self.pieces[Square::A1];
-                    self.pieces[Square::C1] = p;
-                    self.pieces[Square::E1] = Piece::EMPTY;
-                    self.pieces[Square::A1] = Piece::EMPTY;
-                    return;
-                }
-            }
-            Color::BLACK => {
-                if m.is_king_side_castle() {
-                    self.pieces[Square::F8] = self.pieces[Square::H8];
-                    self.pieces[Square::G8] = p;
-                    self.pieces[Square::E8] = Piece::EMPTY;
-                    self.pieces[Square::H8] = Piece::EMPTY;
-                    return;
-                }
-                if m.is_queen_side_castle() {
-                    self.pieces[Square::D8] = self.pieces[Square::A8];
-                    self.pieces[Square::C8] = p;
-                    self.pieces[Square::E8] = Piece::EMPTY;
-                    self.pieces[Square::A8] = Piece::EMPTY;
-                    return;
-                }
-            }
+        if m.is_castle() {
+            // the king and rook are removed first, since they might land on each other's squares
+            let (rook_origin, rook_target) =
+                prev_castling_rights.rook_squares(p.color(), m.is_king_side_castle());
+            let rook = self.pieces[rook_origin];
+            self.pieces[m.origin()] = Piece::EMPTY;
+            self.pieces[rook_origin] = Piece::EMPTY;
+            self.pieces[rook_target] = rook;
+            self.pieces[m.target()] = p;
+            return;
         }
 
         // normal move
@@ -179,7 +846,7 @@ impl Position {
         self.ply -= 1;
         let state = &self.state[self.state.len() - 1];
         let m = state.prev_move;
-        debug_assert!(m != BitMove::NULL);
+        debug_assert!(!m.is_null());
         let p = self.pieces[m.target()];
         debug_assert!(p != Piece::EMPTY);
         debug_assert!(p != Piece::OFF_BOARD);
@@ -205,64 +872,173 @@ impl Position {
 
         self.state.pop();
 
-        // castling
-        match p.color() {
-            Color::WHITE => {
-                if m.is_king_side_castle() {
-                    self.pieces[Square::H1] = self.pieces[Square::F1];
-                    self.pieces[Square::E1] = p;
-                    self.pieces[Square::F1] = Piece::EMPTY;
-                    self.pieces[Square::G1] = Piece::EMPTY;
-                    return;
-                }
-                if m.is_queen_side_castle() {
-                    self.pieces[Square::A1] = self.pieces[Square::D1];
-                    self.pieces[Square::E1] = p;
-                    self.pieces[Square::C1] = Piece::EMPTY;
-                    self.pieces[Square::D1] = Piece::EMPTY;
-                    return;
-                }
-            }
-            Color::BLACK => {
-                if m.is_king_side_castle() {
-                    self.pieces[Square::H8] = self.pieces[Square::F8];
-                    self.pieces[Square::E8] = p;
-                    self.pieces[Square::F8] = Piece::EMPTY;
-                    self.pieces[Square::G8] = Piece::EMPTY;
-                    return;
-                }
-                if m.is_queen_side_castle() {
-                    self.pieces[Square::A8] = self.pieces[Square::D8];
-                    self.pieces[Square::E8] = p;
-                    self.pieces[Square::C8] = Piece::EMPTY;
-                    self.pieces[Square::D8] = Piece::EMPTY;
-                    return;
-                }
-            }
+        if m.is_castle() {
+            let (rook_origin, rook_target) = self.state[self.state.len() - 1]
+                .castling_rights
+                .rook_squares(p.color(), m.is_king_side_castle());
+            let rook = self.pieces[rook_target];
+            self.pieces[m.target()] = Piece::EMPTY;
+            self.pieces[rook_target] = Piece::EMPTY;
+            self.pieces[rook_origin] = rook;
+            self.pieces[m.origin()] = p;
+            return;
         }
         self.pieces[m.target()] = Piece::EMPTY;
         self.pieces[m.origin()] = piece;
         self.pieces[capture_field] = captured_piece;
     }
 
+    /// Passes the turn to the opponent without moving a piece. This is used by null-move pruning
+    /// in the search and has to be undone with [`Position::undo_null_move`].
+    ///
+    /// The en passant square is cleared and the castling rights stay the same.
+    ///
+    /// # Saftey
+    ///
+    /// This must not be called while the side to move is in check, since the opponent could then
+    /// capture the king.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Color, Position};
+    ///
+    /// let mut pos = Position::new();
+    /// pos.make_null_move();
+    /// assert_eq!(pos.side_to_move(), Color::BLACK);
+    ///
+    /// pos.undo_null_move();
+    /// assert_eq!(pos, Position::new());
+    /// ```
+    pub fn make_null_move(&mut self) {
+        debug_assert!(!self.is_check());
+        let state = &self.state[self.state.len() - 1];
+        let mut next = PositionState::new(
+            state.castling_rights,
+            Square::NO_SQ,
+            state.halfmove_clock + 1,
+        );
+        next.key = state.key ^ zobrist::null_move_key(state, &next);
+        self.state.push(next);
+        self.side_to_move = !self.side_to_move;
+        self.ply += 1;
+    }
+
+    /// Undoes a null move made with [`Position::make_null_move`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if no move has been played yet. Undoing a normal move this way corrupts the
+    /// position.
+    pub fn undo_null_move(&mut self) {
+        let state = self.state.pop().expect("at least one state");
+        debug_assert!(state.prev_move.is_null());
+        self.side_to_move = !self.side_to_move;
+        self.ply -= 1;
+    }
+
     /// Returns wheter the position is a stalemate
     #[inline]
-    pub fn is_stalemate(&mut self) -> bool {
-        !self.is_check() && self.generate_legal_moves().is_empty()
+    pub fn is_stalemate(&self) -> bool {
+        !self.is_check() && !self.has_legal_move()
     }
 
     /// Returns wheter the position is a checkmate
     #[inline]
-    pub fn is_checkmate(&mut self) -> bool {
-        self.is_check() && self.generate_legal_moves().is_empty()
+    pub fn is_checkmate(&self) -> bool {
+        self.is_check() && !self.has_legal_move()
+    }
+
+    /// Returns the first legal move that checkmates the opponent or `None` if there is no mate in
+    /// one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// let mut pos = Position::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    /// let m = pos.checkmate_in_one().unwrap();
+    ///
+    /// assert_eq!(m.to_string(), "a1a8");
+    /// ```
+    pub fn checkmate_in_one(&mut self) -> Option<BitMove> {
+        self.generate_legal_moves()
+            .into_iter()
+            .find(|m| self.gives_checkmate(*m))
+    }
+
+    /// Returns whether a legal move checkmates the opponent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{BitMove, Position, Square};
+    ///
+    /// let mut pos = Position::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    ///
+    /// assert!(pos.gives_checkmate(BitMove::new_quiet(Square::A1, Square::A8)));
+    /// assert!(!pos.gives_checkmate(BitMove::new_quiet(Square::A1, Square::A7)));
+    /// ```
+    pub fn gives_checkmate(&mut self, m: BitMove) -> bool {
+        self.make_bit_move(m);
+        let mate = self.is_checkmate();
+        self.undo_move();
+        mate
+    }
+
+    /// Returns wheter the position is a draw by the fifty move rule, i.e. no capture or pawn
+    /// move happened in the last [`Position::FIFTY_MOVE_LIMIT`] halfmoves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// let pos = Position::from_fen("8/8/4k3/8/8/4K3/4R3/8 w - - 100 80").unwrap();
+    ///
+    /// assert!(pos.is_fifty_move_draw());
+    /// ```
+    #[inline]
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.is_fifty_move_draw_with_limit(Self::FIFTY_MOVE_LIMIT)
+    }
+
+    /// Same as [`Position::is_fifty_move_draw`] but with a custom limit of halfmoves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// let pos = Position::from_fen("8/8/4k3/8/8/4K3/4R3/8 w - - 100 80").unwrap();
+    ///
+    /// assert!(!pos.is_fifty_move_draw_with_limit(150));
+    /// ```
+    #[inline]
+    pub fn is_fifty_move_draw_with_limit(&self, halfmoves: u16) -> bool {
+        self.state[self.state.len() - 1].halfmove_clock >= halfmoves
     }
 
-    /// Returns wheter the position is a draw (fifty move rule or stalemate)
-    pub fn is_draw(&mut self) -> bool {
-        self.state[self.state.len() - 1].halfmove_clock >= 100 || self.is_stalemate()
+    /// Returns wheter the position is a draw (fifty move rule, threefold repetition or stalemate)
+    pub fn is_draw(&self) -> bool {
+        self.is_fifty_move_draw() || self.is_repetition(3) || self.is_stalemate()
     }
 }
 
+/// A copy of a [`Position`] taken with [`Position::snapshot`].
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    pieces: [Piece; 120],
+    king_square: [Square; 2],
+    side_to_move: Color,
+    ply: u16,
+    state: PositionState,
+    history_len: usize,
+    /// The key of the state before `state`, used to check that the history is still the same.
+    prev_key: Option<u64>,
+}
+
 impl Default for Position {
     fn default() -> Self {
         Self::new()
@@ -351,6 +1127,14 @@ mod tests {
     // There was a bug in these positions on commit 31459f2b8cee5d4ab8fd1d3152d1ca432b7df125.
     #[test_case( "r3k2r/p1ppqNb1/1n2pnp1/1b1P4/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 1 2", "f7h8", "r3k2N/p1ppq1b1/1n2pnp1/1b1P4/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQq - 0 2"; "bug 2.4")]
     #[test_case("r3k2r/2ppqNb1/1n2pnp1/pb1P4/1p2P3/2N2Q1p/PPPBBPPP/1R2K2R w Kkq - 0 3", "e1g1", "r3k2r/2ppqNb1/1n2pnp1/pb1P4/1p2P3/2N2Q1p/PPPBBPPP/1R3RK1 b kq - 1 3"; "bug 3.3")]
+    #[test_case("4k3/8/8/8/8/8/8/5KR1 w G - 0 1", "f1g1", "4k3/8/8/8/8/8/8/5RK1 b - - 1 1"; "chess960 king onto rook square")]
+    #[test_case("4k3/8/8/8/8/8/8/6KR w H - 0 1", "g1h1", "4k3/8/8/8/8/8/8/5RK1 b - - 1 1"; "chess960 king stays")]
+    #[test_case("4k3/8/8/8/8/8/8/RK6 w A - 0 1", "b1a1", "4k3/8/8/8/8/8/8/2KR4 b - - 1 1"; "chess960 queenside")]
+    #[test_case("rk6/8/8/8/8/8/8/4K3 b a - 0 1", "b8a8", "2kr4/8/8/8/8/8/8/4K3 w - - 1 2"; "chess960 black")]
+    #[test_case("1rk2r2/8/8/8/8/8/8/4K3 b fb - 0 1", "c8b8", "2kr1r2/8/8/8/8/8/8/4K3 w - - 1 2"; "chess960 king stays queenside")]
+    #[test_case("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1h1", "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1"; "king takes rook in standard chess")]
+    #[test_case("4k3/8/8/8/8/8/8/1R2K1R1 w GB - 0 1", "g1g2", "4k3/8/8/8/8/8/6R1/1R2K3 b B - 1 1"; "chess960 rook moves")]
+    #[test_case("1r2k1r1/8/8/8/8/8/8/1R2K1R1 w GBgb - 0 1", "b1b8", "1R2k1r1/8/8/8/8/8/8/4K1R1 b Gg - 0 1"; "chess960 rook captured")]
     fn test_position_make_move(pos: &str, m: &str, expected: &str) {
         let mut pos = Position::from_fen(pos).expect("valid position");
         let m = ParsedMove::from_coordinate_notation(m).expect("valid move");
@@ -373,6 +1157,9 @@ mod tests {
     #[test_case("8/5P1P/2k5/4b1P1/3p4/3B1K2/8/8 w - - 1 85", "f7f8N"; "promtotion to knight")]
     #[test_case("8/5P1P/2k5/4b1P1/3p4/3B1K2/8/8 w - - 1 85", "f7f8B"; "promotion to bishop")]
     #[test_case("8/5P1P/2k5/4b1P1/3p4/3B1K2/8/8 w - - 1 85", "f7f8R"; "promotion to rook")]
+    #[test_case("4k3/8/8/8/8/8/8/5KR1 w G - 0 1", "f1g1"; "chess960 king onto rook square")]
+    #[test_case("4k3/8/8/8/8/8/8/6KR w H - 0 1", "g1h1"; "chess960 king stays")]
+    #[test_case("rk6/8/8/8/8/8/8/4K3 b a - 0 1", "b8a8"; "chess960 black")]
     fn test_position_undo_move(pos: &str, m: &str) {
         let expected = Position::from_fen(pos).unwrap();
         let mut pos = expected.clone();
@@ -383,6 +1170,421 @@ mod tests {
         pretty_assertions::assert_eq!(pos, expected);
     }
 
+    #[test_case(fen::STARTING_FEN, "e2e4", Some(BitMove::new_pawn_push(Square::E2, Square::E4)); "double pawn push")]
+    #[test_case(fen::STARTING_FEN, "e2e5", None; "illegal")]
+    #[test_case(fen::STARTING_FEN, "e1g1", None; "castling blocked")]
+    #[test_case("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1", "e1h1", Some(BitMove::new_castle_kingside(Square::E1, Square::G1)); "king takes rook")]
+    fn test_position_find_move(fen: &str, m: &str, expected: Option<BitMove>) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let before = pos.clone();
+        let m = ParsedMove::from_coordinate_notation(m).unwrap();
+
+        assert_eq!(pos.find_move(m), expected);
+        assert_eq!(pos, before);
+    }
+
+    fn parse_moves(moves: &[&str]) -> Vec<ParsedMove> {
+        moves
+            .iter()
+            .map(|m| ParsedMove::from_coordinate_notation(m).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_position_apply_moves_scholars_mate() {
+        let mut pos = Position::new();
+        let moves = parse_moves(&["e2e4", "e7e5", "f1c4", "b8c6", "d1h5", "g8f6", "h5f7"]);
+
+        assert_eq!(pos.apply_moves(&moves), Ok(()));
+        assert_eq!(
+            pos.to_fen(),
+            "r1bqkb1r/pppp1Qpp/2n2n2/4p3/2B1P3/8/PPPP1PPP/RNB1K1NR b KQkq - 0 4"
+        );
+        assert!(pos.is_checkmate());
+    }
+
+    #[test]
+    fn test_position_apply_moves_long_game() {
+        let mut pos = Position::new();
+        let moves = parse_moves(&["g1f3", "g8f6", "f3g1", "f6g8"].repeat(80));
+
+        assert_eq!(pos.apply_moves(&moves), Ok(()));
+        assert_eq!(
+            pos.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 320 161"
+        );
+        for _ in &moves {
+            pos.undo_move();
+        }
+        assert_eq!(pos, Position::new());
+    }
+
+    #[test]
+    fn test_position_apply_moves_illegal() {
+        let mut pos = Position::new();
+        let moves = parse_moves(&["e2e4", "e7e5", "f1c4", "f8c4", "d1h5"]);
+
+        assert_eq!(pos.apply_moves(&moves), Err((3, moves[3])));
+        assert_eq!(
+            pos.to_fen(),
+            "rnbqkbnr/pppp1ppp/8/4p3/2B1P3/8/PPPP1PPP/RNBQK1NR b KQkq - 1 2"
+        );
+    }
+
+    #[test_case(fen::STARTING_FEN, "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 1 1"; "starting position")]
+    #[test_case("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR b KQkq - 1 3"; "en passant square")]
+    #[test_case("r3k2r/8/8/8/8/8/8/R3K2R b Kq - 7 40", "r3k2r/8/8/8/8/8/8/R3K2R w Kq - 8 41"; "black")]
+    fn test_position_null_move(fen: &str, expected: &str) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let snapshot = pos.snapshot();
+
+        pos.make_null_move();
+        assert_eq!(pos.to_fen(), expected);
+        assert_eq!(pos.zobrist_key(), pos.zobrist_hash());
+        assert_eq!(pos.last_move(), None);
+
+        pos.undo_null_move();
+        assert!(pos.matches_snapshot(&snapshot));
+        assert_eq!(pos.to_fen(), fen);
+    }
+
+    #[test]
+    fn test_position_null_move_between_moves() {
+        let mut pos = Position::from_fen(fen::KIWIPETE).expect("valid position");
+        let snapshot = pos.snapshot();
+
+        pos.make_null_move();
+        let m = pos.generate_legal_moves()[0];
+        pos.make_bit_move(m);
+        pos.make_null_move();
+        assert_eq!(pos.zobrist_key(), pos.zobrist_hash());
+        assert!(!pos.is_repetition(2));
+
+        pos.undo_null_move();
+        pos.undo_move();
+        pos.undo_null_move();
+        assert!(pos.matches_snapshot(&snapshot));
+    }
+
+    #[test_case("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", Some("a1a8"); "back rank mate")]
+    #[test_case("6k1/5pp1/8/8/8/8/8/R5K1 w - - 0 1", None; "back rank with luft")]
+    #[test_case(fen::STARTING_FEN, None; "starting position")]
+    fn test_position_checkmate_in_one(fen: &str, expected: Option<&str>) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let before = pos.clone();
+
+        let m = pos.checkmate_in_one().map(|m| m.to_string());
+
+        assert_eq!(m.as_deref(), expected);
+        assert_eq!(pos, before);
+    }
+
+    #[test_case(fen::KIWIPETE, Square::E1, Some(Piece::W_KING); "white king")]
+    #[test_case(fen::KIWIPETE, Square::A6, Some(Piece::B_BISHOP); "black bishop")]
+    #[test_case(fen::KIWIPETE, Square::H3, Some(Piece::B_PAWN); "black pawn")]
+    #[test_case(fen::KIWIPETE, Square::B1, None; "empty back rank square")]
+    #[test_case(fen::KIWIPETE, Square::D4, None; "empty center square")]
+    fn test_position_piece_at(fen: &str, sq: Square, expected: Option<Piece>) {
+        let pos = Position::from_fen(fen).unwrap();
+        assert_eq!(pos.piece_at(sq), expected);
+        assert_eq!(pos.get_square(sq), expected.unwrap_or(Piece::EMPTY));
+    }
+
+    #[test]
+    fn test_position_iter_pieces_starting_position() {
+        let pieces: Vec<_> = Position::new().iter_pieces().collect();
+
+        assert_eq!(pieces.len(), 32);
+        let back_rank = [
+            PieceType::ROOK,
+            PieceType::KNIGHT,
+            PieceType::BISHOP,
+            PieceType::QUEEN,
+            PieceType::KING,
+            PieceType::BISHOP,
+            PieceType::KNIGHT,
+            PieceType::ROOK,
+        ];
+        for (i, &piece_type) in back_rank.iter().enumerate() {
+            let file = File::new(i as u8);
+            assert_eq!(
+                pieces[i],
+                (
+                    Square::new(file, Rank::FIRST),
+                    Piece::new(piece_type, Color::WHITE)
+                )
+            );
+            assert_eq!(
+                pieces[8 + i],
+                (Square::new(file, Rank::SECOND), Piece::W_PAWN)
+            );
+            assert_eq!(
+                pieces[16 + i],
+                (Square::new(file, Rank::SEVENTH), Piece::B_PAWN)
+            );
+            assert_eq!(
+                pieces[24 + i],
+                (
+                    Square::new(file, Rank::EIGHTH),
+                    Piece::new(piece_type, Color::BLACK)
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_position_iter_pieces_empty() {
+        assert_eq!(Position::empty().iter_pieces().count(), 0);
+    }
+
+    #[test]
+    fn test_position_set_piece_kiwipete() {
+        let mut expected = Position::from_fen(fen::KIWIPETE).unwrap();
+        let mut pos = Position::empty();
+        for i in 0..8 {
+            for j in 0..8 {
+                let sq = Square::new(File::new(i), Rank::new(j));
+                if expected.get_square(sq) != Piece::EMPTY {
+                    pos.set_piece(sq, expected.get_square(sq));
+                }
+            }
+        }
+        assert_ne!(pos, expected, "castling rights are still missing");
+        pos.set_castling_rights(CastlingRights::default());
+
+        assert!(pos.is_legal_position());
+        assert_eq!(pos, expected);
+        assert_eq!(pos.zobrist_key(), expected.zobrist_key());
+        assert_eq!(pos.generate_legal_moves(), expected.generate_legal_moves());
+    }
+
+    #[test]
+    fn test_position_set_piece_replaces_and_removes() {
+        let mut pos = Position::empty();
+        pos.set_piece(Square::E1, Piece::W_KING);
+        pos.set_piece(Square::E8, Piece::B_KING);
+        pos.set_piece(Square::A8, Piece::W_ROOK);
+        pos.set_side_to_move(Color::BLACK);
+        assert!(pos.is_check());
+
+        pos.set_piece(Square::A8, Piece::B_KNIGHT);
+        assert!(!pos.is_check());
+        pos.set_piece(Square::D8, Piece::B_KING);
+        assert!(!pos.is_legal_position(), "two black kings");
+        pos.remove_piece(Square::E8);
+        assert!(pos.is_legal_position());
+        assert_eq!(pos.to_fen(), "n2k4/8/8/8/8/8/8/4K3 b - - 0 1");
+        assert_eq!(pos.zobrist_key(), pos.zobrist_hash());
+        assert_eq!(pos.generate_legal_moves().len(), 5 + 2);
+
+        pos.set_piece(Square::A1, Piece::W_PAWN);
+        assert!(!pos.is_legal_position(), "pawn on the first rank");
+    }
+
+    #[test]
+    fn test_position_set_castling_rights() {
+        let mut pos = Position::new();
+        pos.set_castling_rights(CastlingRights::new(false, false, true, true));
+
+        assert_eq!(
+            pos.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w kq - 0 1"
+        );
+        assert!(pos.generate_legal_moves().iter().all(|m| !m.is_castle()));
+    }
+
+    #[test]
+    fn test_position_set_castling_rights_chess960() {
+        let mut pos = Position::from_fen("4k3/8/8/8/8/8/8/1R2K1R1 w - - 0 1").unwrap();
+        pos.set_castling_rights(
+            CastlingRights::new(false, false, false, false)
+                .with_rook(Color::WHITE, File::E, File::G)
+                .with_rook(Color::WHITE, File::E, File::B),
+        );
+        assert!(pos.make_move(ParsedMove::from_coordinate_notation("g1g2").unwrap()));
+
+        assert_eq!(pos.to_fen(), "4k3/8/8/8/8/8/6R1/1R2K3 b B - 1 1");
+    }
+
+    #[test]
+    fn test_position_set_side_to_move() {
+        let mut pos = Position::new();
+        assert!(pos.make_move(ParsedMove::from_coordinate_notation("e2e4").unwrap()));
+        pos.set_side_to_move(Color::WHITE);
+        pos.set_en_passant(None);
+
+        let expected = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(pos.to_fen(), expected);
+        assert_eq!(pos, Position::from_fen(expected).unwrap());
+
+        pos.set_side_to_move(Color::BLACK);
+        pos.set_en_passant(Some(Square::E3));
+
+        let expected = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+        assert_eq!(pos.to_fen(), expected);
+        assert_eq!(pos, Position::from_fen(expected).unwrap());
+    }
+
+    #[test_case(fen::STARTING_FEN, "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1"; "starting position")]
+    #[test_case("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1", "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 1"; "clears en passant")]
+    #[test_case(fen::KIWIPETE, "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1"; "kiwipete")]
+    fn test_position_with_flipped_side(fen: &str, expected: &str) {
+        let pos = Position::from_fen(fen).expect("valid position");
+        let before = pos.clone();
+
+        let flipped = pos.with_flipped_side();
+
+        assert_eq!(pos, before);
+        assert_eq!(flipped.to_fen(), expected);
+        assert_eq!(flipped.evaluate(), -pos.evaluate());
+    }
+
+    #[test_case(99, false; "99 halfmoves")]
+    #[test_case(100, true; "100 halfmoves")]
+    #[test_case(101, true; "101 halfmoves")]
+    fn test_position_is_fifty_move_draw(halfmove_clock: u16, expected: bool) {
+        let fen = format!("8/8/4k3/8/8/4K3/4R3/8 w - - {} 80", halfmove_clock);
+        let pos = Position::from_fen(&fen).expect("valid position");
+
+        assert_eq!(pos.is_fifty_move_draw(), expected);
+        assert_eq!(pos.is_draw(), expected);
+        assert!(!pos.is_fifty_move_draw_with_limit(150));
+        assert!(pos.is_fifty_move_draw_with_limit(99));
+    }
+
+    #[test]
+    fn test_position_last_move() {
+        let mut pos = Position::new();
+        assert_eq!(pos.last_move(), None);
+
+        pos.make_move(ParsedMove::from_coordinate_notation("e2e4").unwrap());
+        assert_eq!(
+            pos.last_move(),
+            Some(BitMove::new_pawn_push(Square::E2, Square::E4))
+        );
+
+        pos.make_move(ParsedMove::from_coordinate_notation("g8f6").unwrap());
+        assert_eq!(
+            pos.last_move(),
+            Some(BitMove::new_quiet(Square::G8, Square::F6))
+        );
+
+        pos.undo_move();
+        pos.undo_move();
+        assert_eq!(pos.last_move(), None);
+    }
+
+    #[test_case("rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3", "e5d6", Some(Piece::B_PAWN); "en passant white")]
+    #[test_case("rnbqkbnr/pppp1ppp/8/8/3Pp3/8/PPP1PPPP/RNBQKBNR b KQkq d3 0 3", "e4d3", Some(Piece::W_PAWN); "en passant black")]
+    #[test_case("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2", "e4d5", Some(Piece::B_PAWN); "capture")]
+    #[test_case("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1", "b2a1q", Some(Piece::W_ROOK); "promotion capture")]
+    #[test_case(fen::STARTING_FEN, "e2e4", None; "quiet")]
+    fn test_position_last_captured(fen: &str, m: &str, expected: Option<Piece>) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        assert!(pos.make_move(ParsedMove::from_coordinate_notation(m).unwrap()));
+
+        assert_eq!(pos.last_captured(), expected);
+    }
+
+    #[test_case(fen::KIWIPETE, "e1g1", &[
+        (Square::E1, Piece::W_KING, Piece::EMPTY),
+        (Square::F1, Piece::EMPTY, Piece::W_ROOK),
+        (Square::G1, Piece::EMPTY, Piece::W_KING),
+        (Square::H1, Piece::W_ROOK, Piece::EMPTY),
+    ]; "king side castle")]
+    #[test_case(fen::KIWIPETE, "e1c1", &[
+        (Square::A1, Piece::W_ROOK, Piece::EMPTY),
+        (Square::C1, Piece::EMPTY, Piece::W_KING),
+        (Square::D1, Piece::EMPTY, Piece::W_ROOK),
+        (Square::E1, Piece::W_KING, Piece::EMPTY),
+    ]; "queen side castle")]
+    #[test_case("rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3", "e5d6", &[
+        (Square::D5, Piece::B_PAWN, Piece::EMPTY),
+        (Square::E5, Piece::W_PAWN, Piece::EMPTY),
+        (Square::D6, Piece::EMPTY, Piece::W_PAWN),
+    ]; "en passant")]
+    #[test_case("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1", "b2a1q", &[
+        (Square::A1, Piece::W_ROOK, Piece::B_QUEEN),
+        (Square::B2, Piece::B_PAWN, Piece::EMPTY),
+    ]; "promotion capture")]
+    fn test_position_diff(fen: &str, m: &str, expected: &[(Square, Piece, Piece)]) {
+        let before = Position::from_fen(fen).expect("valid position");
+        let mut after = before.clone();
+        assert!(after.make_move(ParsedMove::from_coordinate_notation(m).unwrap()));
+
+        assert_eq!(before.diff(&after), expected);
+        assert!(before.diff(&before).is_empty());
+    }
+
+    #[test_case(fen::STARTING_FEN, true; "starting position")]
+    #[test_case(fen::KIWIPETE, true; "kiwipete")]
+    #[test_case("4k3/8/8/8/8/8/8/4RK2 w - - 0 1", false; "side not to move in check")]
+    #[test_case("4k3/8/8/8/8/8/8/4RK2 b - - 0 1", true; "side to move in check")]
+    #[test_case("8/8/8/8/8/8/8/4K3 w - - 0 1", false; "missing king")]
+    #[test_case("P3k3/8/8/8/8/8/8/4K3 w - - 0 1", false; "pawn on back rank")]
+    fn test_position_is_legal_position(fen: &str, expected: bool) {
+        let pos = Position::from_fen(fen).expect("valid fen");
+        assert_eq!(pos.is_legal_position(), expected);
+    }
+
+    #[test_case("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8", true; "back rank mate")]
+    #[test_case("6k1/5pp1/8/8/8/8/8/R5K1 w - - 0 1", "a1a8", false; "check with luft")]
+    #[test_case("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a7", false; "quiet move")]
+    #[test_case("7k/8/5KQ1/8/8/8/8/8 w - - 0 1", "g6g7", true; "queen mate")]
+    #[test_case("7k/8/5KQ1/8/8/8/8/8 w - - 0 1", "g6g5", false; "no check")]
+    fn test_position_gives_checkmate(fen: &str, m: &str, expected: bool) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let m = pos
+            .find_move(m.parse().expect("valid move"))
+            .expect("legal move");
+
+        assert_eq!(pos.gives_checkmate(m), expected);
+        assert_eq!(pos.to_fen(), fen);
+    }
+
+    #[test]
+    fn test_position_snapshot_restore() {
+        let mut pos = Position::from_fen(fen::KIWIPETE).expect("valid position");
+        pos.make_move(ParsedMove::from_coordinate_notation("e1g1").unwrap());
+        let before = pos.clone();
+        let snapshot = pos.snapshot();
+
+        for m in ["a6e2", "c3e2", "h3g2", "g1g2"] {
+            assert!(pos.make_move(ParsedMove::from_coordinate_notation(m).unwrap()));
+        }
+        pos.restore(snapshot.clone());
+
+        assert_eq!(pos, before);
+        assert_eq!(pos.to_fen(), before.to_fen());
+
+        // the history before the snapshot is still there
+        pos.undo_move();
+        assert_eq!(pos.to_fen(), fen::KIWIPETE);
+
+        // restoring after undoing moves from before the snapshot
+        pos.restore(snapshot);
+        assert_eq!(pos, before);
+
+        // restoring after the history before the snapshot was replaced by a different one
+        let mut pos = Position::new();
+        for m in ["e2e4", "e7e5"] {
+            assert!(pos.make_move(ParsedMove::from_coordinate_notation(m).unwrap()));
+        }
+        let before = pos.clone();
+        let snapshot = pos.snapshot();
+        pos.undo_move();
+        pos.undo_move();
+        for m in ["d2d4", "d7d5"] {
+            assert!(pos.make_move(ParsedMove::from_coordinate_notation(m).unwrap()));
+        }
+        pos.restore(snapshot);
+
+        assert_eq!(pos, before);
+        assert_eq!(pos.zobrist_key(), pos.zobrist_hash());
+        // the history no longer leads to the snapshot, so it is dropped
+        assert_eq!(pos.last_move(), None);
+    }
+
     #[test]
     fn test_position_display() {
         let expected = r"
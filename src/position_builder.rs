@@ -0,0 +1,183 @@
+use crate::error::InvalidPosition;
+use crate::CastlingRights;
+use crate::Color;
+use crate::Piece;
+use crate::Position;
+use crate::Square;
+
+/// A builder to set up a [`Position`] piece by piece.
+///
+/// All squares are empty at the beginning, white is to move, no castling is allowed and there is
+/// no en passant square.
+///
+/// # Examples
+///
+/// ```
+/// use chers::{Color, Piece, PositionBuilder, Square};
+///
+/// let pos = PositionBuilder::new()
+///     .piece(Square::E1, Piece::W_KING)
+///     .piece(Square::D1, Piece::W_QUEEN)
+///     .piece(Square::E8, Piece::B_KING)
+///     .side_to_move(Color::BLACK)
+///     .fullmove(40)
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(pos.to_fen(), "4k3/8/8/8/8/8/8/3QK3 b - - 0 40");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct PositionBuilder {
+    pieces: [Piece; 120],
+    side_to_move: Color,
+    castling_rights: CastlingRights,
+    en_passant: Option<Square>,
+    halfmove_clock: u16,
+    fullmove_number: u16,
+}
+
+impl PositionBuilder {
+    /// Creates a new `PositionBuilder` with an empty board.
+    pub fn new() -> Self {
+        let mut pieces = [Piece::OFF_BOARD; 120];
+        for square in Square::iter() {
+            pieces[square] = Piece::EMPTY;
+        }
+
+        Self {
+            pieces,
+            side_to_move: Color::WHITE,
+            castling_rights: CastlingRights::new(false, false, false, false),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+        }
+    }
+
+    /// Puts a piece on a square. [`Piece::EMPTY`] clears the square.
+    pub fn piece(mut self, sq: Square, piece: Piece) -> Self {
+        self.pieces[sq] = piece;
+        self
+    }
+
+    /// Sets the side to move.
+    pub fn side_to_move(mut self, color: Color) -> Self {
+        self.side_to_move = color;
+        self
+    }
+
+    /// Sets the castling rights.
+    pub fn castling(mut self, castling_rights: CastlingRights) -> Self {
+        self.castling_rights = castling_rights;
+        self
+    }
+
+    /// Sets the en passant square.
+    pub fn en_passant(mut self, sq: Square) -> Self {
+        self.en_passant = Some(sq);
+        self
+    }
+
+    /// Sets the halfmove clock.
+    pub fn halfmove(mut self, halfmove_clock: u16) -> Self {
+        self.halfmove_clock = halfmove_clock;
+        self
+    }
+
+    /// Sets the fullmove number. It has to be in the range `1..=32767`, otherwise [`build`] fails.
+    ///
+    /// [`build`]: PositionBuilder::build
+    pub fn fullmove(mut self, fullmove_number: u16) -> Self {
+        self.fullmove_number = fullmove_number;
+        self
+    }
+
+    /// Builds the position or returns an error if it could not occur in a game.
+    pub fn build(self) -> Result<Position, InvalidPosition> {
+        // the ply is twice the fullmove number and has to fit into an u16
+        if !(1..=u16::MAX / 2).contains(&self.fullmove_number) {
+            return Err(InvalidPosition::InvalidFullmoveNumber(self.fullmove_number));
+        }
+        let pos = Position::from_parts(
+            self.pieces,
+            self.side_to_move,
+            self.castling_rights,
+            self.en_passant.unwrap_or(Square::NO_SQ),
+            self.halfmove_clock,
+            self.fullmove_number,
+        );
+        pos.validate()?;
+        Ok(pos)
+    }
+}
+
+impl Default for PositionBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+    use crate::fen;
+    use crate::File;
+    use crate::Rank;
+
+    #[test]
+    fn test_position_builder_kqk() {
+        let pos = PositionBuilder::new()
+            .piece(Square::E1, Piece::W_KING)
+            .piece(Square::D1, Piece::W_QUEEN)
+            .piece(Square::E8, Piece::B_KING)
+            .halfmove(12)
+            .fullmove(60)
+            .build()
+            .expect("valid position");
+
+        pretty_assertions::assert_eq!(pos.to_fen(), "4k3/8/8/8/8/8/8/3QK3 w - - 12 60");
+    }
+
+    #[test]
+    fn test_position_builder_starting_position() {
+        let mut builder = PositionBuilder::new().castling(CastlingRights::default());
+        let back_rank = [
+            Piece::W_ROOK,
+            Piece::W_KNIGHT,
+            Piece::W_BISHOP,
+            Piece::W_QUEEN,
+            Piece::W_KING,
+            Piece::W_BISHOP,
+            Piece::W_KNIGHT,
+            Piece::W_ROOK,
+        ];
+        for (i, &piece) in back_rank.iter().enumerate() {
+            let file = File::new(i as u8);
+            builder = builder
+                .piece(Square::new(file, Rank::FIRST), piece)
+                .piece(Square::new(file, Rank::SECOND), Piece::W_PAWN)
+                .piece(Square::new(file, Rank::SEVENTH), Piece::B_PAWN)
+                .piece(
+                    Square::new(file, Rank::EIGHTH),
+                    Piece::new(piece.piece_type(), Color::BLACK),
+                );
+        }
+
+        pretty_assertions::assert_eq!(builder.build(), Ok(Position::new()));
+        pretty_assertions::assert_eq!(builder.build().unwrap().to_fen(), fen::STARTING_FEN);
+    }
+
+    #[test_case(PositionBuilder::new().piece(Square::E1, Piece::W_KING), InvalidPosition::WrongNumberOfKings(Color::BLACK); "missing king")]
+    #[test_case(PositionBuilder::new().piece(Square::E1, Piece::W_KING).piece(Square::E8, Piece::B_KING).piece(Square::A8, Piece::B_KING), InvalidPosition::WrongNumberOfKings(Color::BLACK); "two kings")]
+    #[test_case(PositionBuilder::new().piece(Square::E1, Piece::W_KING).piece(Square::E8, Piece::B_KING).piece(Square::A8, Piece::W_PAWN), InvalidPosition::PawnOnBackRank(Square::A8); "pawn on back rank")]
+    #[test_case(PositionBuilder::new().piece(Square::E1, Piece::W_KING).piece(Square::E8, Piece::B_KING).piece(Square::E2, Piece::W_ROOK), InvalidPosition::OpponentInCheck; "opponent in check")]
+    #[test_case(PositionBuilder::new().piece(Square::E1, Piece::W_KING).piece(Square::E8, Piece::B_KING).castling(CastlingRights::new(true, false, false, false)), InvalidPosition::InvalidCastlingRights(CastlingRights::new(true, false, false, false)); "castling without rook")]
+    #[test_case(PositionBuilder::new().piece(Square::E1, Piece::W_KING).piece(Square::E8, Piece::B_KING).en_passant(Square::D6), InvalidPosition::InvalidEnPassantSquare(Square::D6); "en passant without pawn")]
+    #[test_case(PositionBuilder::new().piece(Square::E1, Piece::W_KING).piece(Square::E8, Piece::B_KING).fullmove(0), InvalidPosition::InvalidFullmoveNumber(0); "fullmove number 0")]
+    #[test_case(PositionBuilder::new().piece(Square::E1, Piece::W_KING).piece(Square::E8, Piece::B_KING).fullmove(40000), InvalidPosition::InvalidFullmoveNumber(40000); "fullmove number too large")]
+    fn test_position_builder_invalid(builder: PositionBuilder, err: InvalidPosition) {
+        pretty_assertions::assert_eq!(builder.build(), Err(err));
+    }
+}
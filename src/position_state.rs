@@ -9,6 +9,11 @@ pub(crate) struct PositionState {
 
     pub(crate) prev_move: BitMove,
     pub(crate) captured_piece: Piece,
+
+    /// The Zobrist hash of the position this state belongs to, see [`Position::zobrist_key`].
+    ///
+    /// [`Position::zobrist_key`]: crate::Position::zobrist_key
+    pub(crate) key: u64,
 }
 
 impl PositionState {
@@ -23,6 +28,7 @@ impl PositionState {
             halfmove_clock,
             prev_move: BitMove::NULL,
             captured_piece: Piece::EMPTY,
+            key: 0,
         }
     }
 }
@@ -33,5 +39,6 @@ impl PartialEq for PositionState {
         self.castling_rights == other.castling_rights
             && self.ep_square == other.ep_square
             && self.halfmove_clock == other.halfmove_clock
+            && self.key == other.key
     }
 }
@@ -1,7 +1,10 @@
+use std::convert::TryFrom;
 use std::fmt;
 use std::ops::Add;
 use std::ops::Sub;
 
+use crate::error::ParseSquareError;
+
 /// A rank (otherwise known as row) on the board.
 #[derive(Clone, Copy, PartialEq)]
 pub struct Rank(u8);
@@ -64,6 +67,58 @@ impl Rank {
     pub fn to_i32(self) -> i32 {
         self.0 as i32
     }
+
+    /// Returns an iterator over all ranks in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Rank;
+    ///
+    /// assert_eq!(Rank::iter().count(), 8);
+    /// assert_eq!(Rank::iter().next(), Some(Rank::FIRST));
+    /// assert_eq!(Rank::iter().last(), Some(Rank::EIGHTH));
+    /// ```
+    pub fn iter() -> impl Iterator<Item = Self> {
+        (0..8).map(Self)
+    }
+
+    /// Returns the absolute distance between two ranks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Rank;
+    ///
+    /// assert_eq!(Rank::FIRST.distance(Rank::EIGHTH), 7);
+    /// assert_eq!(Rank::EIGHTH.distance(Rank::FIRST), 7);
+    /// assert_eq!(Rank::FIFTH.distance(Rank::FIFTH), 0);
+    /// ```
+    #[inline]
+    pub fn distance(self, other: Self) -> u8 {
+        self.0.abs_diff(other.0)
+    }
+}
+
+impl TryFrom<char> for Rank {
+    type Error = ParseSquareError;
+
+    /// Converts a character to a `Rank`, see [`Rank::from_char`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    ///
+    /// use chers::error::ParseSquareError;
+    /// use chers::Rank;
+    ///
+    /// assert_eq!(Rank::try_from('4'), Ok(Rank::FOURTH));
+    /// assert_eq!(Rank::try_from('9'), Err(ParseSquareError::InvalidRank('9')));
+    /// ```
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        Self::from_char(c).ok_or(ParseSquareError::InvalidRank(c))
+    }
 }
 
 impl fmt::Display for Rank {
@@ -124,6 +179,14 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_rank_iter() {
+        let ranks: Vec<_> = Rank::iter().collect();
+        assert_eq!(ranks.len(), 8);
+        assert_eq!(ranks[0], Rank::FIRST);
+        assert_eq!(ranks[7], Rank::EIGHTH);
+    }
+
     #[test]
     fn test_rank_new() {
         for i in 0..8 {
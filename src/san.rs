@@ -0,0 +1,398 @@
+use crate::error::{ParseMoveError, ParseSanError};
+use crate::BitMove;
+use crate::File;
+use crate::ParsedMove;
+use crate::PieceType;
+use crate::Position;
+use crate::Rank;
+use crate::Square;
+
+impl Position {
+    /// Returns the move in [standard algebraic notation][SAN].
+    ///
+    /// The move has to be legal in the current position. The position is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{ParsedMove, Position};
+    ///
+    /// let mut pos = Position::new();
+    /// let m = pos
+    ///     .generate_legal_moves()
+    ///     .into_iter()
+    ///     .find(|m| *m == ParsedMove::from_coordinate_notation("g1f3").unwrap())
+    ///     .unwrap();
+    ///
+    /// assert_eq!(pos.san(m), "Nf3");
+    /// ```
+    ///
+    /// [SAN]: https://en.wikipedia.org/wiki/Algebraic_notation_(chess)
+    pub fn san(&mut self, m: BitMove) -> String {
+        let mut san = String::new();
+        let piece = self.pieces[m.origin()];
+
+        if m.is_king_side_castle() {
+            san.push_str("O-O");
+        } else if m.is_queen_side_castle() {
+            san.push_str("O-O-O");
+        } else if piece.is_type(PieceType::PAWN) {
+            if m.is_capture() {
+                san.push(m.origin().file().to_char());
+                san.push('x');
+            }
+            san.push_str(&m.target().to_string());
+            if m.is_promotion() {
+                san.push('=');
+                san.push(m.promotion_piece().to_char().to_ascii_uppercase());
+            }
+        } else {
+            san.push(piece.piece_type().to_char().to_ascii_uppercase());
+
+            // Disambiguate between identical pieces which can move to the same square. The file
+            // is preferred over the rank and both are used only if neither is sufficient.
+            let mut ambiguous = false;
+            let mut same_file = false;
+            let mut same_rank = false;
+            for other in self.generate_legal_moves() {
+                if other.target() == m.target()
+                    && other.origin() != m.origin()
+                    && self.pieces[other.origin()] == piece
+                {
+                    ambiguous = true;
+                    same_file |= other.origin().file() == m.origin().file();
+                    same_rank |= other.origin().rank() == m.origin().rank();
+                }
+            }
+            if ambiguous {
+                if !same_file {
+                    san.push(m.origin().file().to_char());
+                } else if !same_rank {
+                    san.push(m.origin().rank().to_char());
+                } else {
+                    san.push_str(&m.origin().to_string());
+                }
+            }
+
+            if m.is_capture() {
+                san.push('x');
+            }
+            san.push_str(&m.target().to_string());
+        }
+
+        self.make_bit_move(m);
+        if self.is_checkmate() {
+            san.push('#');
+        } else if self.is_check() {
+            san.push('+');
+        }
+        self.undo_move();
+
+        san
+    }
+
+    /// Parses a move in [standard algebraic notation][SAN] and returns the matching legal move.
+    ///
+    /// Check and mate suffixes (`+`, `#`) as well as annotations (`!`, `?`) are ignored. Castling
+    /// may also be written with zeros (`0-0`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{error::ParseSanError, Position};
+    ///
+    /// let mut pos = Position::new();
+    ///
+    /// assert_eq!(pos.parse_san("Nf3").unwrap().to_string(), "g1f3");
+    /// assert_eq!(pos.parse_san("Nf6"), Err(ParseSanError::IllegalMove));
+    /// ```
+    ///
+    /// [SAN]: https://en.wikipedia.org/wiki/Algebraic_notation_(chess)
+    pub fn parse_san(&mut self, san: &str) -> Result<BitMove, ParseSanError> {
+        let san = san.trim_end_matches(['+', '#', '!', '?']);
+        if !san.is_ascii() {
+            return Err(ParseSanError::InvalidSyntax);
+        }
+        let legal_moves = self.generate_legal_moves();
+
+        let castle = |kingside: bool| {
+            legal_moves
+                .iter()
+                .copied()
+                .find(|m| m.is_castle() && m.is_king_side_castle() == kingside)
+                .ok_or(ParseSanError::IllegalMove)
+        };
+        match san {
+            "O-O" | "0-0" => return castle(true),
+            "O-O-O" | "0-0-0" => return castle(false),
+            _ => {}
+        }
+
+        let (piece_type, rest) = match san.chars().next() {
+            Some(c @ ('N' | 'B' | 'R' | 'Q' | 'K')) => (PieceType::from_char(c), &san[1..]),
+            _ => (Some(PieceType::PAWN), san),
+        };
+        let piece_type = piece_type.ok_or(ParseSanError::InvalidSyntax)?;
+
+        let (rest, promotion) = match rest.find('=') {
+            Some(i) => {
+                let mut chars = rest[i + 1..].chars();
+                match (chars.next().and_then(PieceType::from_char), chars.next()) {
+                    (Some(p), None) => (&rest[..i], Some(p)),
+                    _ => return Err(ParseSanError::InvalidSyntax),
+                }
+            }
+            None => (rest, None),
+        };
+
+        if rest.len() < 2 {
+            return Err(ParseSanError::InvalidSyntax);
+        }
+        let (prefix, target) = rest.split_at(rest.len() - 2);
+        let target =
+            Square::from_algebraic_notation(target).map_err(|_| ParseSanError::InvalidSyntax)?;
+
+        let prefix = prefix.strip_suffix('x').unwrap_or(prefix);
+        let mut file = None;
+        let mut rank = None;
+        for c in prefix.chars() {
+            if let (Some(f), None, None) = (File::from_char(c), file, rank) {
+                file = Some(f);
+            } else if let (Some(r), None) = (Rank::from_char(c), rank) {
+                rank = Some(r);
+            } else {
+                return Err(ParseSanError::InvalidSyntax);
+            }
+        }
+
+        let mut candidates = legal_moves.iter().copied().filter(|m| {
+            !m.is_castle()
+                && m.target() == target
+                && self.pieces[m.origin()].is_type(piece_type)
+                && file.is_none_or(|f| m.origin().file() == f)
+                && rank.is_none_or(|r| m.origin().rank() == r)
+                && (m.is_promotion().then(|| m.promotion_piece())) == promotion
+        });
+        match (candidates.next(), candidates.next()) {
+            (Some(m), None) => Ok(m),
+            (None, _) => Err(ParseSanError::IllegalMove),
+            (Some(_), Some(_)) => Err(ParseSanError::AmbiguousMove),
+        }
+    }
+
+    /// Parses a sequence of moves in [standard algebraic notation][SAN] starting from the current
+    /// position, like the movetext of a PGN file without comments.
+    ///
+    /// Move numbers (`1.`, `1...`) and the game result (`1-0`, `0-1`, `1/2-1/2`, `*`) are
+    /// skipped. The position itself is left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// let pos = Position::new();
+    /// let moves = pos.parse_san_line("1. e4 e5 2. Nf3 Nc6 3. Bc4").unwrap();
+    ///
+    /// assert_eq!(moves.len(), 5);
+    /// assert_eq!(moves[4].to_string(), "f1c4");
+    /// ```
+    ///
+    /// [SAN]: https://en.wikipedia.org/wiki/Algebraic_notation_(chess)
+    pub fn parse_san_line(&self, text: &str) -> Result<Vec<BitMove>, ParseSanError> {
+        let mut pos = self.clone();
+        let mut moves = Vec::new();
+
+        for token in text.split_whitespace() {
+            // strip move numbers, which may be directly followed by the move
+            let san = match token.rfind('.') {
+                Some(i) if token.starts_with(|c: char| c.is_ascii_digit()) => &token[i + 1..],
+                _ => token,
+            };
+            if san.is_empty() || matches!(token, "1-0" | "0-1" | "1/2-1/2" | "*") {
+                continue;
+            }
+            let m = pos.parse_san(san)?;
+            pos.make_bit_move(m);
+            moves.push(m);
+        }
+        Ok(moves)
+    }
+
+    /// Converts a move from [standard algebraic notation][SAN] to pure coordinate notation, as
+    /// used by the UCI protocol. See [`Position::uci`] for how castling is written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// let mut pos = Position::new();
+    ///
+    /// assert_eq!(pos.san_to_uci("Nf3").unwrap(), "g1f3");
+    /// ```
+    ///
+    /// [SAN]: https://en.wikipedia.org/wiki/Algebraic_notation_(chess)
+    pub fn san_to_uci(&mut self, san: &str) -> Result<String, ParseSanError> {
+        let m = self.parse_san(san)?;
+        Ok(self.uci(m))
+    }
+
+    /// Converts a move from pure coordinate notation, as used by the UCI protocol, to
+    /// [standard algebraic notation][SAN].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// let mut pos = Position::new();
+    ///
+    /// assert_eq!(pos.uci_to_san("g1f3").unwrap(), "Nf3");
+    /// ```
+    ///
+    /// [SAN]: https://en.wikipedia.org/wiki/Algebraic_notation_(chess)
+    pub fn uci_to_san(&mut self, uci: &str) -> Result<String, ParseMoveError> {
+        let m = ParsedMove::from_coordinate_notation(uci)?;
+        let m = self.find_move(m).ok_or(ParseMoveError::IllegalMove)?;
+        Ok(self.san(m))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+    use crate::fen;
+
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "e2e4", "e4"; "pawn push")]
+    #[test_case("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2", "e4d5", "exd5"; "pawn capture")]
+    #[test_case("rnbqkbnr/1pp1pppp/p7/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3", "e5d6", "exd6"; "en passant")]
+    #[test_case("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "g1f3", "Nf3"; "knight")]
+    #[test_case("r1bqkb1r/pppp1ppp/2n2n2/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4", "b5c6", "Bxc6"; "piece capture")]
+    #[test_case("r1bqkb1r/pppp1ppp/2n2n2/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4", "e1g1", "O-O"; "kingside castling")]
+    #[test_case("r3kbnr/pppqpppp/2n1b3/3pN3/2PP4/2N5/PP2PPPP/R1BQKB1R b KQkq - 6 5", "e8c8", "O-O-O"; "queenside castling")]
+    #[test_case("8/4P1k1/8/8/8/8/8/4K3 w - - 0 1", "e7e8q", "e8=Q"; "promotion")]
+    #[test_case("3r2k1/4P3/8/8/8/8/8/4K3 w - - 0 1", "e7d8n", "exd8=N"; "promotion capture")]
+    #[test_case("4k3/8/8/8/8/4K3/8/R6R w - - 0 1", "a1d1", "Rad1"; "file disambiguation")]
+    #[test_case("4k3/8/8/R7/8/4K3/8/R7 w - - 0 1", "a1a3", "R1a3"; "rank disambiguation")]
+    #[test_case("4k3/8/8/8/8/Q3K3/8/Q1Q5 w - - 0 1", "a1b2", "Qa1b2"; "file and rank disambiguation")]
+    #[test_case("4k3/8/8/8/1b6/8/3N1N2/4K3 w - - 0 1", "f2e4", "Ne4"; "pinned piece needs no disambiguation")]
+    #[test_case("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2", "f1b5", "Bb5+"; "check")]
+    #[test_case("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8", "Ra8#"; "checkmate")]
+    fn test_position_san(fen: &str, m: &str, expected: &str) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let m = pos
+            .find_move(m.parse().expect("valid move"))
+            .expect("legal move");
+        let before = pos.clone();
+
+        pretty_assertions::assert_eq!(pos.san(m), expected);
+        pretty_assertions::assert_eq!(pos, before);
+    }
+
+    #[test_case(fen::STARTING_FEN; "starting position")]
+    #[test_case(fen::KIWIPETE; "kiwipete")]
+    #[test_case("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"; "position 3")]
+    #[test_case("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1"; "position 4")]
+    #[test_case("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1"; "position 4 black")]
+    #[test_case("4k3/8/8/8/8/Q3K3/8/Q1Q5 w - - 0 1"; "three queens")]
+    fn test_position_parse_san_round_trip(fen: &str) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        for m in pos.generate_legal_moves() {
+            let san = pos.san(m);
+            pretty_assertions::assert_eq!(pos.parse_san(&san), Ok(m), "{}", san);
+        }
+    }
+
+    #[test_case(fen::STARTING_FEN, "e4", "e2e4"; "pawn push")]
+    #[test_case(fen::STARTING_FEN, "Nf3+!?", "g1f3"; "suffixes")]
+    #[test_case("r1bqkb1r/pppp1ppp/2n2n2/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4", "0-0", "e1g1"; "castling with zeros")]
+    #[test_case("8/4P1k1/8/8/8/8/8/4K3 w - - 0 1", "e8=N", "e7e8n"; "underpromotion")]
+    #[test_case("4k3/8/8/8/8/4K3/8/R6R w - - 0 1", "Rhd1", "h1d1"; "disambiguation")]
+    #[test_case("4k3/8/8/8/8/4K3/8/R6R w - - 0 1", "Rh1d1", "h1d1"; "overspecified")]
+    fn test_position_parse_san(fen: &str, san: &str, expected: &str) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+
+        pretty_assertions::assert_eq!(
+            pos.parse_san(san).map(|m| m.to_string()),
+            Ok(expected.to_string())
+        );
+    }
+
+    #[test_case(fen::STARTING_FEN, "", ParseSanError::InvalidSyntax; "empty")]
+    #[test_case(fen::STARTING_FEN, "Nz3", ParseSanError::InvalidSyntax; "invalid square")]
+    #[test_case(fen::STARTING_FEN, "e8=X", ParseSanError::InvalidSyntax; "invalid promotion piece")]
+    #[test_case(fen::STARTING_FEN, "Nf6", ParseSanError::IllegalMove; "illegal")]
+    #[test_case(fen::STARTING_FEN, "O-O", ParseSanError::IllegalMove; "illegal castling")]
+    #[test_case("4k3/8/8/8/8/4K3/8/R6R w - - 0 1", "Rd1", ParseSanError::AmbiguousMove; "ambiguous")]
+    #[test_case("8/4P1k1/8/8/8/8/8/4K3 w - - 0 1", "e8", ParseSanError::IllegalMove; "missing promotion piece")]
+    fn test_position_parse_san_invalid(fen: &str, san: &str, expected: ParseSanError) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+
+        pretty_assertions::assert_eq!(pos.parse_san(san), Err(expected));
+    }
+
+    #[test_case("1. e4 e5 2. Nf3 Nc6", &["e2e4", "e7e5", "g1f3", "b8c6"]; "italian game")]
+    #[test_case("1.e4 e5 2.Nf3 Nc6 3.Bc4 Bc5 1-0", &["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "f8c5"]; "no spaces after move numbers")]
+    #[test_case("1. f3 e5 2. g4 Qh4# 0-1", &["f2f3", "e7e5", "g2g4", "d8h4"]; "fools mate")]
+    #[test_case("1. e4 e5 2. Nf3 Nc6 3. Bc4 Nf6 4. 0-0", &["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6", "e1g1"]; "castling with zeros")]
+    #[test_case("1. e4 e5 2. Nf3 Nc6 3. Bc4 Nf6 4. O-O Bc5 5. d3 d6 6. Bg5 O-O", &["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6", "e1g1", "f8c5", "d2d3", "d7d6", "c1g5", "e8g8"]; "castling")]
+    #[test_case("1. e4 1... e5", &["e2e4", "e7e5"]; "ellipsis")]
+    #[test_case("", &[]; "empty")]
+    fn test_position_parse_san_line(text: &str, expected: &[&str]) {
+        let pos = Position::new();
+        let moves: Vec<_> = pos
+            .parse_san_line(text)
+            .expect("valid movetext")
+            .into_iter()
+            .map(|m| m.to_string())
+            .collect();
+
+        pretty_assertions::assert_eq!(moves, expected);
+        pretty_assertions::assert_eq!(pos, Position::new());
+    }
+
+    #[test]
+    fn test_position_parse_san_line_invalid() {
+        let pos = Position::new();
+
+        pretty_assertions::assert_eq!(
+            pos.parse_san_line("1. e4 e5 2. Ke3"),
+            Err(ParseSanError::IllegalMove)
+        );
+    }
+
+    #[test_case("1. e4 e5 2. Nf3 d6 3. d4 Bg4 4. dxe5 Bxf3 5. Qxf3 dxe5 6. Bc4 Nf6 7. Qb3 Qe7 8. Nc3 c6 9. Bg5 b5 10. Nxb5 cxb5 11. Bxb5+ Nbd7 12. O-O-O Rd8 13. Rxd7 Rxd7 14. Rd1 Qe6 15. Bxd7+ Nxd7 16. Qb8+ Nxb8 17. Rd8#"; "opera game")]
+    #[test_case("1. e4 e5 2. f4 exf4 3. Bc4 Qh4+ 4. Kf1 b5 5. Bxb5 Nf6 6. Nf3 Qh6 7. d3 Nh5 8. Nh4 Qg5 9. Nf5 c6 10. g4 Nf6 11. Rg1 cxb5 12. h4 Qg6 13. h5 Qg5 14. Qf3 Ng8 15. Bxf4 Qf6 16. Nc3 Bc5 17. Nd5 Qxb2 18. Bd6 Bxg1 19. e5 Qxa1+ 20. Ke2 Na6 21. Nxg7+ Kd8 22. Qf6+ Nxf6 23. Be7#"; "immortal game")]
+    #[test_case("1. e4 Nf6 2. e5 d5 3. exd6 e5 4. dxc7 Qxd2+ 5. Nxd2 Bb4 6. cxb8=Q Rxb8 7. c3 O-O 8. cxb4 Re8"; "en passant and promotion")]
+    fn test_position_san_games(movetext: &str) {
+        let mut pos = Position::new();
+        let moves = pos.parse_san_line(movetext).expect("valid movetext");
+        let sans: Vec<_> = movetext
+            .split_whitespace()
+            .filter(|token| !token.ends_with('.'))
+            .collect();
+        pretty_assertions::assert_eq!(moves.len(), sans.len());
+
+        for (m, san) in moves.into_iter().zip(sans) {
+            pretty_assertions::assert_eq!(pos.san(m), san);
+            pos.make_bit_move(m);
+        }
+    }
+
+    #[test]
+    fn test_position_san_uci_conversion() {
+        let mut pos = Position::new();
+
+        pretty_assertions::assert_eq!(pos.san_to_uci("Nf3"), Ok("g1f3".to_string()));
+        pretty_assertions::assert_eq!(pos.uci_to_san("g1f3"), Ok("Nf3".to_string()));
+        pretty_assertions::assert_eq!(pos.uci_to_san("g1f4"), Err(ParseMoveError::IllegalMove));
+        pretty_assertions::assert_eq!(pos, Position::new());
+
+        let mut pos = Position::from_fen("4k3/8/8/8/8/8/8/1RK5 w B - 0 1").unwrap();
+        pretty_assertions::assert_eq!(pos.san_to_uci("O-O-O"), Ok("c1b1".to_string()));
+        pretty_assertions::assert_eq!(pos.uci_to_san("c1b1"), Ok("O-O-O".to_string()));
+    }
+}
@@ -1,15 +1,279 @@
+use std::cmp::Reverse;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
 use crate::utils::INF;
 use crate::BitMove;
+use crate::Color;
+use crate::MoveList;
+use crate::Outcome;
 use crate::Position;
 
+/// Information about a completed iteration of [`Position::search_with_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchInfo {
+    /// The depth of the iteration
+    pub depth: u32,
+    /// The score of the best move in centipawns from the point of view of the side to move
+    pub score: i32,
+    /// The number of nodes searched so far, including previous iterations
+    pub nodes: u64,
+    /// The time elapsed since the search started
+    pub time: Duration,
+    /// The principal variation, i.e. the best move followed by the expected replies
+    pub pv: Vec<BitMove>,
+}
+
+impl SearchInfo {
+    /// Returns the number of moves until mate if the score is a mate score, see
+    /// [`SearchResult::mate_in`].
+    pub fn mate_in(&self) -> Option<i32> {
+        mate_in(self.score)
+    }
+}
+
+/// The result of [`Position::search_full`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    /// The best move found
+    pub best_move: BitMove,
+    /// The score of the best move in centipawns from the point of view of the side to move.
+    /// Getting mated in `n` plies scores `-INF + n`, mating `INF - n`.
+    pub score: i32,
+    /// The principal variation, i.e. the best move followed by the expected replies
+    pub pv: Vec<BitMove>,
+    /// The number of nodes searched, including all iterations
+    pub nodes: u64,
+    /// The depth of the last completed iteration
+    pub depth: u32,
+}
+
+impl SearchResult {
+    /// Returns the number of moves until mate if the score is a mate score: positive if the side
+    /// to move mates, negative if it gets mated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// let mut pos = Position::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    /// assert_eq!(pos.search_full(2).unwrap().mate_in(), Some(1));
+    ///
+    /// // every move gets mated
+    /// let mut pos = Position::from_fen("k7/8/1K6/8/8/8/8/7Q b - - 0 1").unwrap();
+    /// assert_eq!(pos.search_full(2).unwrap().mate_in(), Some(-1));
+    ///
+    /// assert_eq!(Position::new().search_full(2).unwrap().mate_in(), None);
+    /// ```
+    pub fn mate_in(&self) -> Option<i32> {
+        mate_in(self.score)
+    }
+}
+
+/// The longest mate that can be found, i.e. every score this close to `INF` is a mate score.
+const MAX_MATE_PLY: i32 = 1000;
+
+/// Converts a mate score into the number of moves until mate.
+fn mate_in(score: i32) -> Option<i32> {
+    let plies = INF - score.abs();
+    if plies > MAX_MATE_PLY {
+        return None;
+    }
+    let moves = (plies + 1) / 2;
+    Some(if score > 0 { moves } else { -moves })
+}
+
+/// Options to enable or disable parts of the search, e.g. to compare the results with and without
+/// a pruning technique.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchParams {
+    /// Whether to prune subtrees in which passing the turn already fails high, see
+    /// [`Position::make_null_move`]
+    pub null_move_pruning: bool,
+    /// Whether to try quiet moves that caused a beta cutoff at the same ply elsewhere in the tree
+    /// right after the captures
+    pub killer_moves: bool,
+}
+
+impl Default for SearchParams {
+    fn default() -> Self {
+        Self {
+            null_move_pruning: true,
+            killer_moves: true,
+        }
+    }
+}
+
+/// The depth a null move search is reduced by in addition to the null move itself.
+const NULL_MOVE_REDUCTION: u32 = 2;
+
+/// The number of nodes searched between two checks of the stop flag. Loading the atomic flag at
+/// every node would slow the search down, while a few thousand nodes take well below a
+/// millisecond.
+const STOP_CHECK_INTERVAL: u64 = 2048;
+
+/// The number of plies from the root killer moves and principal variations are stored for.
+const MAX_PLY: usize = 128;
+
+/// State shared by all nodes of a search.
+#[derive(Debug)]
+struct SearchContext {
+    params: SearchParams,
+    nodes: u64,
+    /// Set by another thread to abort the search.
+    stop: Option<Arc<AtomicBool>>,
+    /// Whether the search has been aborted. The scores of an aborted search are meaningless.
+    stopped: bool,
+    next_stop_check: u64,
+    /// The last two quiet moves that caused a beta cutoff for every ply, the most recent first.
+    killers: [[BitMove; 2]; MAX_PLY],
+    /// The principal variation of every ply as a triangular table: row `ply` holds the moves from
+    /// `ply` up to `pv_end[ply]`, the entries before `ply` are unused.
+    pv: [[BitMove; MAX_PLY]; MAX_PLY],
+    pv_end: [usize; MAX_PLY],
+}
+
+impl Default for SearchContext {
+    fn default() -> Self {
+        Self::new(SearchParams::default(), None)
+    }
+}
+
+impl SearchContext {
+    fn new(params: SearchParams, stop: Option<Arc<AtomicBool>>) -> Self {
+        Self {
+            params,
+            nodes: 0,
+            stop,
+            stopped: false,
+            next_stop_check: 0,
+            killers: [[BitMove::NULL; 2]; MAX_PLY],
+            pv: [[BitMove::NULL; MAX_PLY]; MAX_PLY],
+            pv_end: [0; MAX_PLY],
+        }
+    }
+
+    /// Returns the killer moves for a ply, which are [`BitMove::NULL`] if there are none.
+    fn killers(&self, ply: i32) -> [BitMove; 2] {
+        match self.killers.get(ply as usize) {
+            Some(&killers) if self.params.killer_moves => killers,
+            _ => [BitMove::NULL; 2],
+        }
+    }
+
+    /// Remembers a quiet move that caused a beta cutoff.
+    fn store_killer(&mut self, ply: i32, m: BitMove) {
+        if let Some(killers) = self.killers.get_mut(ply as usize) {
+            if killers[0] != m {
+                killers[1] = killers[0];
+                killers[0] = m;
+            }
+        }
+    }
+
+    /// Clears the principal variation of a ply.
+    fn clear_pv(&mut self, ply: i32) {
+        if let Some(end) = self.pv_end.get_mut(ply as usize) {
+            *end = ply as usize;
+        }
+    }
+
+    /// Replaces the principal variation of `ply` with `m` followed by the one of `ply + 1`. Moves
+    /// more than [`MAX_PLY`] plies from the root are dropped.
+    fn update_pv(&mut self, ply: i32, m: BitMove) {
+        let ply = ply as usize;
+        if ply >= MAX_PLY {
+            return;
+        }
+        let end = self.pv_end.get(ply + 1).copied().unwrap_or(MAX_PLY);
+        let (rows, child_rows) = self.pv.split_at_mut(ply + 1);
+        rows[ply][ply] = m;
+        if let Some(child) = child_rows.first() {
+            rows[ply][ply + 1..end].copy_from_slice(&child[ply + 1..end]);
+        }
+        self.pv_end[ply] = end;
+    }
+
+    /// Returns the principal variation of the root.
+    fn root_pv(&self) -> &[BitMove] {
+        &self.pv[0][..self.pv_end[0]]
+    }
+
+    /// Returns whether the search should be aborted, checking the stop flag every
+    /// [`STOP_CHECK_INTERVAL`] nodes.
+    fn should_stop(&mut self) -> bool {
+        if !self.stopped && self.nodes >= self.next_stop_check {
+            self.next_stop_check = self.nodes + STOP_CHECK_INTERVAL;
+            self.stopped = self
+                .stop
+                .as_ref()
+                .is_some_and(|stop| stop.load(Ordering::Relaxed));
+        }
+        self.stopped
+    }
+}
+
 impl Position {
-    fn negamax(&mut self, depth: u32, mut alpha: i32, beta: i32) -> i32 {
+    /// Orders the moves so that the most promising ones are searched first: `pv_move` (usually
+    /// the best move of the previous iteration) first, then the captures by
+    /// [`Position::mvv_lva`], the `killers` and the remaining moves by [`BitMove::order_key`].
+    fn order_moves(&self, moves: &mut MoveList, pv_move: Option<BitMove>, killers: [BitMove; 2]) {
+        moves.sort_unstable_by_key(|m| {
+            (
+                Some(*m) != pv_move,
+                Reverse(self.mvv_lva(*m)),
+                killers.iter().position(|k| k == m).unwrap_or(killers.len()),
+                Reverse(m.order_key()),
+            )
+        });
+    }
+
+    /// Searches the position `depth` plies deep. `ply` is the distance from the root, which is
+    /// used to prefer shorter mates.
+    fn negamax(
+        &mut self,
+        depth: u32,
+        ply: i32,
+        mut alpha: i32,
+        beta: i32,
+        ctx: &mut SearchContext,
+    ) -> i32 {
+        ctx.nodes += 1;
+        ctx.clear_pv(ply);
+        if ctx.should_stop() {
+            return 0;
+        }
         if depth == 0 {
-            return self.quiescence_search(alpha, beta);
+            return self.quiescence_search(alpha, beta, ply, ctx);
+        }
+
+        // If passing the turn already fails high, a real move most likely does as well. This
+        // doesn't hold in zugzwang, which is common in endgames with only pawns left. Two null
+        // moves in a row would just search the same position with less depth.
+        if ctx.params.null_move_pruning
+            && depth > NULL_MOVE_REDUCTION
+            && self.last_move().is_some()
+            && !self.is_check()
+            && self.has_non_pawn_material(self.side_to_move)
+        {
+            self.make_null_move();
+            let evaluation = -self.negamax(
+                depth - 1 - NULL_MOVE_REDUCTION,
+                ply + 1,
+                -beta,
+                -beta + 1,
+                ctx,
+            );
+            self.undo_null_move();
+            if evaluation >= beta {
+                return beta;
+            }
         }
 
         let mut moves = self.generate_pseudo_legal_moves(false);
-        moves.sort();
+        self.order_moves(&mut moves, None, ctx.killers(ply));
 
         let mut any_legal_move = false;
         for m in moves {
@@ -19,18 +283,27 @@ impl Position {
                 continue;
             }
             any_legal_move = true;
-            let evaluation = -self.negamax(depth - 1, -beta, -alpha);
+            let evaluation = -self.negamax(depth - 1, ply + 1, -beta, -alpha, ctx);
             self.undo_move();
+            if ctx.stopped {
+                return 0;
+            }
             if evaluation >= beta {
+                if !m.is_capture() {
+                    ctx.store_killer(ply, m);
+                }
                 return beta;
             }
-            alpha = alpha.max(evaluation);
+            if evaluation > alpha {
+                alpha = evaluation;
+                ctx.update_pv(ply, m);
+            }
         }
 
         if !any_legal_move {
             if self.is_check() {
                 // checkmate
-                return -INF;
+                return -INF + ply;
             }
             // stalemate
             return 0;
@@ -38,7 +311,29 @@ impl Position {
         alpha
     }
 
-    fn quiescence_search(&mut self, mut alpha: i32, beta: i32) -> i32 {
+    /// Returns whether `color` has any pieces other than pawns and the king.
+    fn has_non_pawn_material(&self, color: Color) -> bool {
+        let count = self.material_count();
+        count.knights[color] + count.bishops[color] + count.rooks[color] + count.queens[color] > 0
+    }
+
+    /// Improves the static evaluation at the horizon by also evaluating the position after each
+    /// capture, so a hanging piece isn't missed.
+    ///
+    /// The stop flag isn't checked here: this is only called by [`Position::negamax`] right after
+    /// it checked the flag, and the captures are evaluated statically without searching deeper.
+    fn quiescence_search(
+        &mut self,
+        mut alpha: i32,
+        beta: i32,
+        ply: i32,
+        ctx: &mut SearchContext,
+    ) -> i32 {
+        // Standing pat is not possible if we are checkmated.
+        if self.is_check() && !self.has_legal_move() {
+            return -INF + ply;
+        }
+
         let evaluation = self.evaluate();
         if evaluation >= beta {
             return beta;
@@ -46,7 +341,7 @@ impl Position {
         alpha = alpha.max(evaluation);
 
         let mut capture_moves = self.generate_pseudo_legal_moves(true);
-        capture_moves.sort();
+        self.order_moves(&mut capture_moves, None, [BitMove::NULL; 2]);
 
         for m in capture_moves {
             self.make_bit_move(m);
@@ -54,6 +349,7 @@ impl Position {
                 self.undo_move();
                 continue;
             }
+            ctx.nodes += 1;
             let evaluation = -self.evaluate();
             self.undo_move();
             if evaluation >= beta {
@@ -64,23 +360,627 @@ impl Position {
         alpha
     }
 
-    /// Searches for the best move with a given depth
+    /// Searches for the best move with a given depth.
     ///
-    /// # Saftey
+    /// The search is iteratively deepened, so the best move of each iteration is searched first
+    /// in the next one. Returns `None` if the game is already over (see [`Position::outcome`]) or the
+    /// position is not legal (see [`Position::is_legal_position`]).
     ///
-    /// This function will panic with an invalid board (stalemate, checkmate etc.)
-    pub fn search(&mut self, depth: u32) -> BitMove {
-        let mut best_move = BitMove::NULL;
-        let mut max = -INF;
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// let mut pos = Position::new();
+    /// assert!(pos.search(1).is_some());
+    ///
+    /// let mut mate = Position::from_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3").unwrap();
+    /// assert_eq!(mate.search(1), None);
+    /// ```
+    pub fn search(&mut self, depth: u32) -> Option<BitMove> {
+        self.search_scored(depth).map(|(m, _)| m)
+    }
+
+    /// Same as [`Position::search`], but also returns the score of the best move in centipawns
+    /// from the point of view of the side to move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// // white can take a free queen
+    /// let mut pos = Position::from_fen("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1").unwrap();
+    /// let (m, score) = pos.search_scored(2).unwrap();
+    ///
+    /// assert_eq!(m.to_string(), "d1d5");
+    /// assert!(score > 0);
+    /// ```
+    pub fn search_scored(&mut self, depth: u32) -> Option<(BitMove, i32)> {
+        self.iterative_deepening(depth, SearchContext::default(), |_| {})
+            .map(|result| (result.best_move, result.score))
+    }
+
+    /// Same as [`Position::search`], but returns the score, the principal variation and some
+    /// statistics as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// let mut pos = Position::from_fen("7k/8/8/8/8/8/1R6/R5K1 w - - 0 1").unwrap();
+    /// let result = pos.search_full(2).unwrap();
+    ///
+    /// assert_eq!(result.mate_in(), Some(2));
+    /// assert_eq!(result.pv.len(), 3);
+    /// assert_eq!(result.pv[0], result.best_move);
+    /// ```
+    pub fn search_full(&mut self, depth: u32) -> Option<SearchResult> {
+        self.iterative_deepening(depth, SearchContext::default(), |_| {})
+    }
+
+    /// Same as [`Position::search`], but with the given [`SearchParams`] instead of the default
+    /// ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{Position, SearchParams};
+    ///
+    /// let mut pos = Position::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    /// let params = SearchParams {
+    ///     null_move_pruning: false,
+    ///     ..SearchParams::default()
+    /// };
+    ///
+    /// assert_eq!(pos.search_with_params(3, params).unwrap().to_string(), "a1a8");
+    /// ```
+    pub fn search_with_params(&mut self, depth: u32, params: SearchParams) -> Option<BitMove> {
+        self.iterative_deepening(depth, SearchContext::new(params, None), |_| {})
+            .map(|result| result.best_move)
+    }
+
+    /// Same as [`Position::search`], but the search can be aborted from another thread by setting
+    /// `stop` to `true`, e.g. when a GUI sends the UCI `stop` command. In that case the best move
+    /// of the last completed iteration is returned, so there is always a move unless the game is
+    /// over. The first iteration is never aborted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use std::sync::Arc;
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// use chers::Position;
+    ///
+    /// let stop = Arc::new(AtomicBool::new(false));
+    /// let stopper = {
+    ///     let stop = Arc::clone(&stop);
+    ///     thread::spawn(move || {
+    ///         thread::sleep(Duration::from_millis(10));
+    ///         stop.store(true, Ordering::Relaxed);
+    ///     })
+    /// };
+    ///
+    /// // would take ages without the stop flag
+    /// assert!(Position::new().search_with_stop(100, stop).is_some());
+    /// stopper.join().unwrap();
+    /// ```
+    pub fn search_with_stop(&mut self, max_depth: u32, stop: Arc<AtomicBool>) -> Option<BitMove> {
+        let ctx = SearchContext::new(SearchParams::default(), Some(stop));
+        self.iterative_deepening(max_depth, ctx, |_| {})
+            .map(|result| result.best_move)
+    }
+
+    /// Same as [`Position::search`], but calls `on_info` after every completed iteration, i.e.
+    /// once for each depth from `0` to `depth`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// let mut pos = Position::new();
+    /// let mut depths = Vec::new();
+    /// let m = pos.search_with_info(2, |info| depths.push(info.depth));
+    ///
+    /// assert!(m.is_some());
+    /// assert_eq!(depths, [0, 1, 2]);
+    /// ```
+    pub fn search_with_info(
+        &mut self,
+        depth: u32,
+        on_info: impl FnMut(&SearchInfo),
+    ) -> Option<BitMove> {
+        self.iterative_deepening(depth, SearchContext::default(), on_info)
+            .map(|result| result.best_move)
+    }
+
+    fn iterative_deepening(
+        &mut self,
+        depth: u32,
+        mut ctx: SearchContext,
+        mut on_info: impl FnMut(&SearchInfo),
+    ) -> Option<SearchResult> {
+        if !self.is_legal_position() || self.outcome() != Outcome::Ongoing {
+            return None;
+        }
+
+        let start = Instant::now();
+        let mut best: Option<SearchResult> = None;
+        // the first iteration can't be aborted, so there is always a move to return
+        let stop = ctx.stop.take();
+        for depth in 0..=depth {
+            let pv_move = best.as_ref().map(|b| b.best_move);
+            let result = self.search_root(depth, pv_move, &mut ctx);
+            if ctx.stopped {
+                break;
+            }
+            ctx.stop.clone_from(&stop);
+            if let Some((best_move, score)) = result {
+                let pv = ctx.root_pv().to_vec();
+                on_info(&SearchInfo {
+                    depth,
+                    score,
+                    nodes: ctx.nodes,
+                    time: start.elapsed(),
+                    pv: pv.clone(),
+                });
+                best = Some(SearchResult {
+                    best_move,
+                    score,
+                    pv,
+                    nodes: ctx.nodes,
+                    depth,
+                });
+            }
+        }
+        if let Some(best) = &mut best {
+            best.nodes = ctx.nodes;
+        }
+        best
+    }
+
+    /// Returns the best move at the root and its score, searching `pv_move` first.
+    fn search_root(
+        &mut self,
+        depth: u32,
+        pv_move: Option<BitMove>,
+        ctx: &mut SearchContext,
+    ) -> Option<(BitMove, i32)> {
+        ctx.nodes += 1;
+        ctx.clear_pv(0);
+        let mut moves = self.generate_pseudo_legal_moves(false);
+        self.order_moves(&mut moves, pv_move, [BitMove::NULL; 2]);
+
+        let mut best_move = None;
+        let mut alpha = -INF;
+        for m in moves {
+            self.make_bit_move(m);
+            if self.in_check(!self.side_to_move) {
+                self.undo_move();
+                continue;
+            }
+            let score = -self.negamax(depth, 1, -INF, -alpha, ctx);
+            self.undo_move();
+            if ctx.stopped {
+                return None;
+            }
+            if best_move.is_none() || score > alpha {
+                alpha = score;
+                best_move = Some(m);
+                ctx.update_pv(0, m);
+            }
+        }
+        best_move.map(|m| (m, alpha))
+    }
+
+    /// Searches for a forced checkmate for the side to move within `depth` plies and returns the
+    /// mating line, i.e. a mate in `n` moves needs a depth of `2 * n - 1`.
+    ///
+    /// The opponent's moves in the line are the ones delaying the mate the longest. Returns
+    /// `None` if there is no forced mate within `depth` plies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// let mut pos = Position::from_fen("7k/8/8/8/8/8/1R6/R5K1 w - - 0 1").unwrap();
+    ///
+    /// assert_eq!(pos.is_forced_mate(1), None);
+    /// assert_eq!(pos.is_forced_mate(3).unwrap().len(), 3);
+    /// ```
+    pub fn is_forced_mate(&mut self, depth: u32) -> Option<Vec<BitMove>> {
+        if depth == 0 {
+            return None;
+        }
+
         for m in self.generate_legal_moves() {
             self.make_bit_move(m);
-            let score = -self.negamax(depth, -INF, INF);
+            let line = if self.is_checkmate() {
+                Some(Vec::new())
+            } else if depth >= 3 {
+                self.forced_mate_after_any_reply(depth - 1)
+            } else {
+                None
+            };
             self.undo_move();
-            if score > max {
+
+            if let Some(mut line) = line {
+                line.insert(0, m);
+                return Some(line);
+            }
+        }
+        None
+    }
+
+    /// Returns the longest mating line if every legal move of the side to move gets mated within
+    /// `depth` plies, `None` otherwise.
+    fn forced_mate_after_any_reply(&mut self, depth: u32) -> Option<Vec<BitMove>> {
+        let mut longest: Option<Vec<BitMove>> = None;
+
+        for reply in self.generate_legal_moves() {
+            self.make_bit_move(reply);
+            let line = self.is_forced_mate(depth - 1);
+            self.undo_move();
+
+            let mut line = line?;
+            if longest.as_ref().is_none_or(|l| line.len() >= l.len()) {
+                line.insert(0, reply);
+                longest = Some(line);
+            }
+        }
+        longest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use std::thread;
+
+    use super::*;
+    use crate::fen;
+    use crate::ParsedMove;
+    use crate::Square;
+
+    #[test_case("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3"; "checkmate")]
+    #[test_case("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1"; "stalemate")]
+    fn test_search_no_legal_moves(fen: &str) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        pretty_assertions::assert_eq!(pos.search(2), None);
+    }
+
+    #[test]
+    fn test_search_illegal_position() {
+        let mut pos = Position::from_fen("4k3/8/8/8/8/8/8/4RK2 w - - 0 1").expect("valid fen");
+        pretty_assertions::assert_eq!(pos.search(2), None);
+    }
+
+    #[test_case(fen::STARTING_FEN, 3; "starting position")]
+    #[test_case(fen::KIWIPETE, 2; "kiwipete")]
+    #[test_case("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1", 3; "free piece")]
+    fn test_search_with_info(fen: &str, depth: u32) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let mut infos = Vec::new();
+        let m = pos.search_with_info(depth, |info| infos.push(info.clone()));
+
+        let depths: Vec<_> = infos.iter().map(|info| info.depth).collect();
+        pretty_assertions::assert_eq!(depths, (0..=depth).collect::<Vec<_>>());
+        assert!(infos.windows(2).all(|w| w[0].nodes < w[1].nodes));
+        assert!(infos.windows(2).all(|w| w[0].time <= w[1].time));
+
+        let last = infos.last().unwrap();
+        pretty_assertions::assert_eq!(m, last.pv.first().copied());
+        pretty_assertions::assert_eq!(pos.search_scored(depth), Some((m.unwrap(), last.score)));
+        for info in &infos {
+            let mut line = pos.clone();
+            for &m in &info.pv {
+                assert!(
+                    line.generate_legal_moves().contains(&m),
+                    "illegal pv move {}",
+                    m
+                );
+                line.make_bit_move(m);
+            }
+        }
+    }
+
+    #[test]
+    fn test_search_with_info_game_over() {
+        let mut pos = Position::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").expect("valid position");
+        let mut calls = 0;
+        pretty_assertions::assert_eq!(pos.search_with_info(2, |_| calls += 1), None);
+        pretty_assertions::assert_eq!(calls, 0);
+    }
+
+    #[test]
+    fn test_search_threefold_repetition() {
+        let mut pos = Position::new();
+        for m in [
+            "g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8",
+        ] {
+            assert!(pos.make_move(ParsedMove::from_coordinate_notation(m).unwrap()));
+        }
+
+        pretty_assertions::assert_eq!(pos.search(2), None);
+    }
+
+    #[test]
+    fn test_position_is_forced_mate() {
+        let mut pos =
+            Position::from_fen("7k/8/8/8/8/8/1R6/R5K1 w - - 0 1").expect("valid position");
+        let fen = pos.to_fen();
+
+        pretty_assertions::assert_eq!(pos.is_forced_mate(1), None);
+        pretty_assertions::assert_eq!(pos.is_forced_mate(2), None);
+
+        let line = pos.is_forced_mate(3).expect("mate in two");
+        pretty_assertions::assert_eq!(line.len(), 3);
+        pretty_assertions::assert_eq!(pos.to_fen(), fen);
+
+        for m in line {
+            pos.make_bit_move(m);
+        }
+        assert!(pos.is_checkmate());
+    }
+
+    #[test_case("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 1; "back rank mate")]
+    #[test_case("7k/8/8/8/8/8/1R6/R5K1 w - - 0 1", 3; "mate in two")]
+    fn test_position_is_forced_mate_length(fen: &str, depth: u32) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+
+        pretty_assertions::assert_eq!(
+            pos.is_forced_mate(depth).map(|line| line.len()),
+            Some(depth as usize)
+        );
+    }
+
+    #[test]
+    fn test_search_mate_at_horizon() {
+        // Rxc1 wins a knight, but Ra8 is mate.
+        let mut pos =
+            Position::from_fen("6k1/5ppp/8/8/8/8/8/R1n3K1 w - - 0 1").expect("valid position");
+
+        let score = pos.negamax(1, 0, -INF, INF, &mut SearchContext::default());
+        pretty_assertions::assert_eq!(score, INF - 1);
+        pretty_assertions::assert_eq!(
+            pos.search(0),
+            Some(BitMove::new_quiet(Square::A1, Square::A8))
+        );
+    }
+
+    #[test]
+    fn test_search_context_pv_beyond_max_ply() {
+        let mut ctx = SearchContext::default();
+        let m = BitMove::new_quiet(Square::A1, Square::A8);
+        let last = MAX_PLY as i32 - 1;
+
+        ctx.clear_pv(last + 1);
+        ctx.update_pv(last + 1, m);
+        ctx.clear_pv(last);
+        ctx.update_pv(last, m);
+        ctx.clear_pv(last - 1);
+        ctx.update_pv(last - 1, m);
+
+        pretty_assertions::assert_eq!(ctx.pv_end[MAX_PLY - 2], MAX_PLY);
+        pretty_assertions::assert_eq!(&ctx.pv[MAX_PLY - 2][MAX_PLY - 2..], [m, m]);
+    }
+
+    #[test]
+    fn test_search_mated_anyway() {
+        // Every move gets mated, the search still has to return one of them.
+        let mut pos = Position::from_fen("k7/8/1K6/8/8/8/8/7Q b - - 0 1").expect("valid position");
+        assert!(pos.search(2).is_some());
+    }
+
+    /// The search without move ordering and pruning at the root.
+    fn reference_search(pos: &mut Position, depth: u32) -> Option<BitMove> {
+        let mut best_move = None;
+        let mut max = -INF;
+        for m in pos.generate_legal_moves() {
+            pos.make_bit_move(m);
+            let score = -pos.negamax(depth, 1, -INF, INF, &mut SearchContext::default());
+            pos.undo_move();
+            if best_move.is_none() || score > max {
                 max = score;
-                best_move = m;
+                best_move = Some(m);
             }
         }
         best_move
     }
+
+    #[test_case("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 1; "back rank mate")]
+    #[test_case("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1", 2; "hanging queen")]
+    #[test_case("r3k3/8/8/3N4/8/8/8/4K3 w - - 0 1", 2; "knight fork")]
+    #[test_case("6k1/5ppp/8/8/8/8/8/R1n3K1 w - - 0 1", 2; "mate instead of material")]
+    #[test_case(fen::KIWIPETE, 2; "kiwipete")]
+    fn test_search_matches_reference(fen: &str, depth: u32) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+
+        let best_move = pos.search(depth);
+
+        assert!(best_move.is_some());
+        pretty_assertions::assert_eq!(best_move, reference_search(&mut pos, depth));
+    }
+
+    #[test_case("6k1/5ppp/8/8/8/8/8/R1n3K1 w - - 0 1", "a1a8"; "mate instead of material")]
+    #[test_case("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1", "d1d5"; "hanging queen")]
+    #[test_case("r3k3/8/8/3N4/8/8/8/4K3 w - - 0 1", "d5c7"; "knight fork")]
+    #[test_case("6k1/5ppp/8/8/8/1q6/5PPP/3R2K1 w - - 0 1", "d1d8"; "back rank mate with queen up")]
+    #[test_case("r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5Q2/PPPP1PPP/RNB1K1NR w KQkq - 4 4", "f3f7"; "scholar's mate")]
+    #[test_case("4k3/8/8/1N6/8/8/r4PPP/6K1 b - - 0 1", "a2a1"; "back rank mate for black")]
+    fn test_search_null_move_pruning(fen: &str, expected: &str) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let without = SearchParams {
+            null_move_pruning: false,
+            ..SearchParams::default()
+        };
+
+        for depth in 3..=4 {
+            let with = pos.search_with_params(depth, SearchParams::default());
+            pretty_assertions::assert_eq!(with.map(|m| m.to_string()), Some(expected.to_string()));
+            pretty_assertions::assert_eq!(with, pos.search_with_params(depth, without));
+        }
+    }
+
+    #[test]
+    fn test_order_moves_captures() {
+        let pos = Position::from_fen(fen::KIWIPETE).expect("valid position");
+        let mut moves = pos.generate_captures();
+        pos.order_moves(&mut moves, None, [BitMove::NULL; 2]);
+        let moves: Vec<_> = moves.iter().map(|m| m.to_string()).collect();
+        let index = |m: &str| moves.iter().position(|o| o == m).expect("capture");
+
+        // bishop takes bishop, queen takes knight, then the pawns with the pawns first
+        pretty_assertions::assert_eq!(&moves[..2], ["e2a6", "f3f6"]);
+        assert!(index("d5e6") < index("e5g6"));
+        assert!(index("g2h3") < index("f3h3"));
+        assert!(index("e5d7") < index("f3h3"));
+    }
+
+    #[test]
+    fn test_order_moves_queen_takes_queen_first() {
+        let pos =
+            Position::from_fen("4k3/8/8/4q3/8/3p4/2P1Q3/4K3 w - - 0 1").expect("valid position");
+        let mut moves = pos.generate_pseudo_legal_moves(false);
+        pos.order_moves(&mut moves, None, [BitMove::NULL; 2]);
+        let moves: Vec<_> = moves.iter().map(|m| m.to_string()).collect();
+
+        pretty_assertions::assert_eq!(&moves[..3], ["e2e5", "c2d3", "e2d3"]);
+    }
+
+    #[test]
+    fn test_search_killer_moves() {
+        let suite = [
+            fen::KIWIPETE,
+            fen::POSITION_3,
+            fen::POSITION_4,
+            fen::POSITION_5,
+            "r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5Q2/PPPP1PPP/RNB1K1NR w KQkq - 4 4",
+            "r3k3/8/8/3N4/8/8/8/4K3 w - - 0 1",
+        ];
+        let without = SearchParams {
+            killer_moves: false,
+            ..SearchParams::default()
+        };
+
+        let mut nodes_with = 0;
+        let mut nodes_without = 0;
+        for fen in suite {
+            let mut pos = Position::from_fen(fen).expect("valid position");
+            let with = pos
+                .iterative_deepening(3, SearchContext::default(), |_| {})
+                .expect("legal moves");
+            let reference = pos
+                .iterative_deepening(3, SearchContext::new(without, None), |_| {})
+                .expect("legal moves");
+
+            pretty_assertions::assert_eq!(with.best_move, reference.best_move, "{}", fen);
+            nodes_with += with.nodes;
+            nodes_without += reference.nodes;
+        }
+        assert!(
+            nodes_with <= nodes_without,
+            "{} nodes with killer moves, {} without",
+            nodes_with,
+            nodes_without
+        );
+    }
+
+    #[test]
+    fn test_search_full_mate_in_two() {
+        let mut pos =
+            Position::from_fen("7k/8/8/8/8/8/1R6/R5K1 w - - 0 1").expect("valid position");
+        let result = pos.search_full(2).expect("legal moves");
+
+        pretty_assertions::assert_eq!(result.score, INF - 3);
+        pretty_assertions::assert_eq!(result.mate_in(), Some(2));
+        pretty_assertions::assert_eq!(result.depth, 2);
+        pretty_assertions::assert_eq!(result.pv.len(), 3);
+        pretty_assertions::assert_eq!(result.pv[0], result.best_move);
+
+        for m in result.pv {
+            pos.make_bit_move(m);
+        }
+        assert!(pos.is_checkmate());
+    }
+
+    #[test]
+    fn test_search_full_prefers_shorter_mate() {
+        // there are lots of ways to mate in two, but Ra1 mates right away
+        let mut pos =
+            Position::from_fen("6k1/8/8/8/8/r7/1r6/7K b - - 0 1").expect("valid position");
+        let result = pos.search_full(3).expect("legal moves");
+
+        pretty_assertions::assert_eq!(result.best_move.to_string(), "a3a1");
+        pretty_assertions::assert_eq!(result.mate_in(), Some(1));
+    }
+
+    #[test_case(INF - 1, Some(1); "mate in one")]
+    #[test_case(INF - 3, Some(2); "mate in two")]
+    #[test_case(-INF + 2, Some(-1); "mated in one")]
+    #[test_case(-INF + 4, Some(-2); "mated in two")]
+    #[test_case(0, None; "draw")]
+    #[test_case(-900, None; "queen down")]
+    fn test_mate_in(score: i32, expected: Option<i32>) {
+        pretty_assertions::assert_eq!(mate_in(score), expected);
+    }
+
+    #[test_case(fen::STARTING_FEN; "starting position")]
+    #[test_case(fen::KIWIPETE; "kiwipete")]
+    fn test_search_with_stop(fen: &str) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopper = {
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(50));
+                stop.store(true, Ordering::Relaxed);
+                Instant::now()
+            })
+        };
+
+        // a depth of 100 would never finish, so returning at all means that the search stopped
+        let m = pos.search_with_stop(100, stop).expect("legal moves");
+        let stopped_at = stopper.join().expect("stopper thread");
+
+        // generous, so that a slow or busy machine doesn't make the test fail
+        assert!(stopped_at.elapsed() < Duration::from_secs(5));
+        assert!(pos.generate_legal_moves().contains(&m));
+        pretty_assertions::assert_eq!(pos.to_fen(), fen);
+    }
+
+    #[test]
+    fn test_search_with_stop_already_stopped() {
+        let mut pos = Position::new();
+        let m = pos.search_with_stop(100, Arc::new(AtomicBool::new(true)));
+
+        // the first iteration isn't aborted
+        pretty_assertions::assert_eq!(m, pos.search(0));
+    }
+
+    #[test_case("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1"; "white can take a queen")]
+    #[test_case("3rk3/8/8/8/3Q4/8/8/4K3 b - - 0 1"; "black can take a queen")]
+    fn test_search_scored_free_piece(fen: &str) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let (m, score) = pos.search_scored(2).expect("legal moves");
+
+        assert!(m.is_capture());
+        assert!(score > 500, "score {}", score);
+    }
+
+    #[test_case("4k3/8/8/2q5/8/8/8/4K2R w - - 0 1", false; "white is behind")]
+    #[test_case("4k3/8/8/2q5/8/8/8/4K2R b - - 0 1", true; "black is ahead")]
+    fn test_search_scored_side_to_move_view(fen: &str, positive: bool) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let (_, score) = pos.search_scored(2).expect("legal moves");
+
+        pretty_assertions::assert_eq!(score > 0, positive, "score {}", score);
+    }
 }
@@ -0,0 +1,288 @@
+use crate::position::{
+    BISHOP_OFFSETS, BLACK_PAWN_CAPTURE_OFFSETS, KING_OFFSETS, KNIGHT_OFFSETS, ROOK_OFFSETS,
+    WHITE_PAWN_CAPTURE_OFFSETS,
+};
+use crate::BitMove;
+use crate::Color;
+use crate::MoveList;
+use crate::Piece;
+use crate::PieceType;
+use crate::Position;
+use crate::Square;
+
+impl Position {
+    /// Returns the [static exchange evaluation] of a move, i.e. the material the side to move
+    /// wins (or loses if negative) when both sides keep capturing on the target square with their
+    /// least valuable attacker for as long as it pays off.
+    ///
+    /// Pieces behind other attackers (x-rays) are taken into account. For quiet moves this is the
+    /// material lost if the moved piece gets captured.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{BitMove, Position, Square};
+    ///
+    /// let pos = Position::from_fen("4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1").unwrap();
+    ///
+    /// // the pawn on d5 is defended
+    /// assert_eq!(pos.see(BitMove::new_capture(Square::D1, Square::D5)), -800);
+    /// ```
+    ///
+    /// [static exchange evaluation]: https://www.chessprogramming.org/Static_Exchange_Evaluation
+    pub fn see(&self, m: BitMove) -> i32 {
+        let target = m.target();
+        let mut board = self.pieces;
+        let mut gain = [0; 32];
+        let mut depth = 0;
+
+        gain[0] = self.material_gain(m);
+        if m.is_en_passant() {
+            board[Square::new(target.file(), m.origin().rank())] = Piece::EMPTY;
+        }
+
+        let mover = board[m.origin()];
+        let mut on_target = if m.is_promotion() {
+            m.promotion_piece()
+        } else {
+            mover.piece_type()
+        };
+        board[m.origin()] = Piece::EMPTY;
+
+        let mut side = !mover.color();
+        while let Some(attacker) = least_valuable_attacker(&board, target, side) {
+            depth += 1;
+            gain[depth] = on_target.value() - gain[depth - 1];
+            on_target = board[attacker].piece_type();
+            board[attacker] = Piece::EMPTY;
+            side = !side;
+        }
+
+        while depth > 0 {
+            gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+            depth -= 1;
+        }
+        gain[0]
+    }
+
+    /// Returns whether the piece moved by `m` is safe on its target square, i.e. the opponent
+    /// can't win material by capturing on that square afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{BitMove, Position, Square};
+    ///
+    /// let pos = Position::from_fen("4k3/8/4p3/8/8/8/8/3QK3 w - - 0 1").unwrap();
+    ///
+    /// assert!(!pos.moves_piece_to_safe_square(BitMove::new_quiet(Square::D1, Square::D5)));
+    /// assert!(pos.moves_piece_to_safe_square(BitMove::new_quiet(Square::D1, Square::D4)));
+    /// ```
+    pub fn moves_piece_to_safe_square(&self, m: BitMove) -> bool {
+        self.see(m) >= self.material_gain(m)
+    }
+
+    /// Returns the value of the piece captured by a move or 0 if it isn't a capture.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{BitMove, Position, Square};
+    ///
+    /// let pos = Position::from_fen("4k3/8/3r4/4Pp2/8/8/8/4K3 w - f6 0 1").unwrap();
+    ///
+    /// assert_eq!(pos.capture_value(BitMove::new_capture(Square::E5, Square::D6)), 500);
+    /// assert_eq!(pos.capture_value(BitMove::new_en_passant(Square::E5, Square::F6)), 100);
+    /// assert_eq!(pos.capture_value(BitMove::new_quiet(Square::E5, Square::E6)), 0);
+    /// ```
+    pub fn capture_value(&self, m: BitMove) -> i32 {
+        if m.is_en_passant() {
+            PieceType::PAWN.value()
+        } else if m.is_capture() {
+            self.pieces[m.target()].value()
+        } else {
+            0
+        }
+    }
+
+    /// Returns the Most Valuable Victim / Least Valuable Attacker score of a move, which orders
+    /// captures by the value of the captured piece and captures of equally valuable pieces by
+    /// the value of the capturing piece, the least valuable first. Non-captures score 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{BitMove, Position, Square};
+    ///
+    /// let pos = Position::from_fen("4k3/8/8/2pq4/1P2P3/8/Q7/4K3 w - - 0 1").unwrap();
+    /// let pawn_takes_queen = BitMove::new_capture(Square::E4, Square::D5);
+    /// let pawn_takes_pawn = BitMove::new_capture(Square::B4, Square::C5);
+    /// let queen_takes_queen = BitMove::new_capture(Square::A2, Square::D5);
+    ///
+    /// assert!(pos.mvv_lva(pawn_takes_queen) > pos.mvv_lva(queen_takes_queen));
+    /// assert!(pos.mvv_lva(queen_takes_queen) > pos.mvv_lva(pawn_takes_pawn));
+    /// assert_eq!(pos.mvv_lva(BitMove::new_quiet(Square::A2, Square::A3)), 0);
+    /// ```
+    pub fn mvv_lva(&self, m: BitMove) -> i32 {
+        if !m.is_capture() {
+            return 0;
+        }
+        let attacker = self.pieces[m.origin()].piece_type();
+        // the victim values differ by more than the number of piece types
+        self.capture_value(m) * 8 - attacker.to_u8() as i32
+    }
+
+    /// Returns the material won by a move before any recaptures.
+    fn material_gain(&self, m: BitMove) -> i32 {
+        let mut gain = self.capture_value(m);
+        if m.is_promotion() {
+            gain += m.promotion_piece().value() - PieceType::PAWN.value();
+        }
+        gain
+    }
+
+    /// Returns all legal captures that don't lose material according to [`Position::see`], the
+    /// best ones first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// let mut pos = Position::from_fen("4k3/8/2p5/3p1q2/4P3/8/8/3QK3 w - - 0 1").unwrap();
+    /// let captures: Vec<_> = pos.good_captures().iter().map(|m| m.to_string()).collect();
+    ///
+    /// assert_eq!(captures, ["e4f5", "e4d5"]);
+    /// ```
+    pub fn good_captures(&mut self) -> MoveList {
+        let mut captures: Vec<_> = self
+            .generate_legal_moves()
+            .into_iter()
+            .filter(|m| m.is_capture())
+            .map(|m| (self.see(m), m))
+            .filter(|&(see, _)| see >= 0)
+            .collect();
+        captures.sort_by_key(|&(see, _)| -see);
+        captures.into_iter().map(|(_, m)| m).collect()
+    }
+}
+
+/// Returns the square of the least valuable piece of a given color attacking a square.
+fn least_valuable_attacker(board: &[Piece; 120], square: Square, side: Color) -> Option<Square> {
+    let index = square.to_i8();
+    let step = |offsets: &[i8], piece: Piece| {
+        offsets
+            .iter()
+            .map(|offset| (index + offset) as usize)
+            .find(|&target| board[target] == piece)
+            .map(Square::from_index)
+    };
+    let slide = |offsets: &[i8], piece: Piece| {
+        offsets.iter().find_map(|offset| {
+            let mut target = (index + offset) as usize;
+            while board[target] == Piece::EMPTY {
+                target = (target as i8 + offset) as usize;
+            }
+            if board[target] == piece {
+                Some(Square::from_index(target))
+            } else {
+                None
+            }
+        })
+    };
+
+    step(
+        &side.map(BLACK_PAWN_CAPTURE_OFFSETS, WHITE_PAWN_CAPTURE_OFFSETS),
+        Piece::new(PieceType::PAWN, side),
+    )
+    .or_else(|| step(&KNIGHT_OFFSETS, Piece::new(PieceType::KNIGHT, side)))
+    .or_else(|| slide(&BISHOP_OFFSETS, Piece::new(PieceType::BISHOP, side)))
+    .or_else(|| slide(&ROOK_OFFSETS, Piece::new(PieceType::ROOK, side)))
+    .or_else(|| slide(&BISHOP_OFFSETS, Piece::new(PieceType::QUEEN, side)))
+    .or_else(|| slide(&ROOK_OFFSETS, Piece::new(PieceType::QUEEN, side)))
+    .or_else(|| step(&KING_OFFSETS, Piece::new(PieceType::KING, side)))
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case("4k3/8/8/3p4/8/8/8/3QK3 w - - 0 1", "d1d5", 100; "undefended pawn")]
+    #[test_case("4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1", "d1d5", -800; "defended pawn")]
+    #[test_case("4k3/8/2p5/3p1q2/4P3/8/8/3QK3 w - - 0 1", "e4f5", 900; "pawn takes queen")]
+    #[test_case("4k3/8/2p5/3p1q2/4P3/8/8/3QK3 w - - 0 1", "e4d5", 0; "pawn trade")]
+    #[test_case("4k3/3r4/8/3p4/8/8/3R4/3RK3 w - - 0 1", "d2d5", 100; "x-ray rook behind rook")]
+    #[test_case("4k3/8/2b5/3p4/4Q3/5B2/8/4K3 w - - 0 1", "e4d5", -470; "x-ray bishop behind queen")]
+    #[test_case("3rk3/3r4/8/3p4/8/8/3R4/3QK3 w - - 0 1", "d2d5", -400; "more defenders than attackers")]
+    #[test_case("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6", 100; "en passant")]
+    fn test_position_see(fen: &str, m: &str, expected: i32) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let m = pos
+            .find_move(m.parse().expect("valid move"))
+            .expect("legal move");
+
+        pretty_assertions::assert_eq!(pos.see(m), expected);
+    }
+
+    #[test_case("4k3/8/4p3/8/8/8/8/3QK3 w - - 0 1", "d1d5", false; "queen attacked by pawn")]
+    #[test_case("4k3/8/4p3/8/8/8/8/3QK3 w - - 0 1", "d1d4", true; "queen not attacked")]
+    #[test_case("4k3/8/8/3r4/8/8/3R4/3RK3 w - - 0 1", "d2d4", true; "defended rook attacked by rook")]
+    #[test_case("4k3/8/4p3/3q4/8/8/8/3QK3 w - - 0 1", "d1d5", false; "queen trade")]
+    #[test_case("4k3/8/8/3q4/8/8/8/3QK3 w - - 0 1", "d1d5", true; "undefended queen")]
+    fn test_position_moves_piece_to_safe_square(fen: &str, m: &str, expected: bool) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let m = pos
+            .find_move(m.parse().expect("valid move"))
+            .expect("legal move");
+
+        pretty_assertions::assert_eq!(pos.moves_piece_to_safe_square(m), expected);
+    }
+
+    #[test_case("4k3/8/3r4/4Pp2/8/8/8/4K3 w - f6 0 1", "e5f6", 100; "en passant")]
+    #[test_case("4k3/8/3r4/4Pp2/8/8/8/4K3 w - f6 0 1", "e5d6", 500; "rook")]
+    #[test_case("4k3/8/3r4/4Pp2/8/8/8/4K3 w - f6 0 1", "e5e6", 0; "quiet")]
+    #[test_case("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7b8q", 320; "promotion capture")]
+    #[test_case("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q", 0; "promotion")]
+    fn test_position_capture_value(fen: &str, m: &str, expected: i32) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let m = pos
+            .find_move(m.parse().expect("valid move"))
+            .expect("legal move");
+
+        pretty_assertions::assert_eq!(pos.capture_value(m), expected);
+    }
+
+    #[test_case("4k3/8/8/2pq4/1P2P3/8/Q7/4K3 w - - 0 1", &["e4d5", "a2d5", "b4c5"]; "victims")]
+    #[test_case("4k3/8/8/3q4/2P2N2/8/3Q4/4K3 w - - 0 1", &["c4d5", "f4d5", "d2d5"]; "attackers")]
+    #[test_case("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", &["e5d6", "e5e6"]; "en passant")]
+    fn test_position_mvv_lva(fen: &str, expected: &[&str]) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        let scores: Vec<_> = expected
+            .iter()
+            .map(|m| {
+                let m = pos
+                    .find_move(m.parse().expect("valid move"))
+                    .expect("legal move");
+                pos.mvv_lva(m)
+            })
+            .collect();
+
+        assert!(scores.windows(2).all(|w| w[0] > w[1]), "{:?}", scores);
+    }
+
+    #[test]
+    fn test_position_good_captures() {
+        let mut pos =
+            Position::from_fen("4k3/8/2p5/3p1q2/4P3/8/8/3QK3 w - - 0 1").expect("valid position");
+        let captures: Vec<_> = pos
+            .good_captures()
+            .into_iter()
+            .map(|m| m.to_string())
+            .collect();
+
+        // Qxd5 loses the queen.
+        pretty_assertions::assert_eq!(captures, ["e4f5", "e4d5"]);
+    }
+}
@@ -1,11 +1,12 @@
 use std::fmt;
 use std::ops;
+use std::str::FromStr;
 
 use crate::error::ParseSquareError;
 use crate::{File, Rank};
 
 /// A square on the board.
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Square(u8);
 
 #[allow(missing_docs)]
@@ -77,12 +78,39 @@ impl Square {
     pub const G8: Self = Self(97);
     pub const H8: Self = Self(98);
 
+    /// All 64 squares ordered from a1 to h8 (a1, b1, ..., h1, a2, ...).
+    pub const ALL: [Self; 64] = {
+        let mut squares = [Self::A1; 64];
+        let mut i = 0;
+        while i < 64 {
+            squares[i] = Self(21 + (i % 8) as u8 + 10 * (i / 8) as u8);
+            i += 1;
+        }
+        squares
+    };
+
     /// Creates a `Square` from file and rank.
     #[inline]
     pub fn new(file: File, rank: Rank) -> Self {
         Self(21 + file.to_u8() + 10 * rank.to_u8())
     }
 
+    /// Returns an iterator over all squares ordered from a1 to h8, see [`Square::ALL`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Square;
+    ///
+    /// assert_eq!(Square::iter().count(), 64);
+    /// assert_eq!(Square::iter().next(), Some(Square::A1));
+    /// assert_eq!(Square::iter().nth(8), Some(Square::A2));
+    /// assert_eq!(Square::iter().last(), Some(Square::H8));
+    /// ```
+    pub fn iter() -> impl Iterator<Item = Self> {
+        Self::ALL.iter().copied()
+    }
+
     /// Flip the square horizontally. Black starting squares become white squares.
     ///
     /// # Examples
@@ -161,7 +189,7 @@ impl Square {
     }
 
     #[inline]
-    pub(crate) fn to_usize(self) -> usize {
+    pub(crate) const fn to_usize(self) -> usize {
         self.0 as usize
     }
 
@@ -169,6 +197,79 @@ impl Square {
     pub(crate) fn to_i8(self) -> i8 {
         self.0 as i8
     }
+
+    /// Returns whether the square is a light square.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Square;
+    ///
+    /// assert!(!Square::A1.is_light());
+    /// assert!(Square::A8.is_light());
+    /// assert!(Square::D1.is_light());
+    /// assert!(!Square::D8.is_light());
+    /// ```
+    #[inline]
+    pub fn is_light(self) -> bool {
+        (self.file().to_u8() + self.rank().to_u8()) % 2 == 1
+    }
+
+    /// Returns the index of the square in the range `0..64` (a1 = 0, b1 = 1, ..., h8 = 63).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Square;
+    ///
+    /// assert_eq!(Square::A1.to_u8_0_63(), 0);
+    /// assert_eq!(Square::A2.to_u8_0_63(), 8);
+    /// assert_eq!(Square::H8.to_u8_0_63(), 63);
+    /// ```
+    #[inline]
+    pub fn to_u8_0_63(self) -> u8 {
+        self.rank().to_u8() * 8 + self.file().to_u8()
+    }
+
+    /// Creates a `Square` from its index in the range `0..64`, see [`Square::to_u8_0_63`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not in the range `0..64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Square;
+    ///
+    /// assert_eq!(Square::from_index_0_63(0), Square::A1);
+    /// assert_eq!(Square::from_index_0_63(12), Square::E2);
+    /// assert_eq!(Square::from_index_0_63(63), Square::H8);
+    /// ```
+    #[inline]
+    pub fn from_index_0_63(index: u8) -> Self {
+        assert!(index < 64, "square index out of range: {}", index);
+        Self::new(File::new(index % 8), Rank::new(index / 8))
+    }
+}
+
+impl FromStr for Square {
+    type Err = ParseSquareError;
+
+    /// Parses a square in algebraic notation, see [`Square::from_algebraic_notation`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::error::ParseSquareError;
+    /// use chers::Square;
+    ///
+    /// assert_eq!("e4".parse::<Square>(), Ok(Square::E4));
+    /// assert_eq!("i4".parse::<Square>(), Err(ParseSquareError::InvalidFile('i')));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_algebraic_notation(s)
+    }
 }
 
 impl fmt::Display for Square {
@@ -252,6 +353,42 @@ mod tests {
         assert_eq!(Square::H8.rank(), Rank::EIGHTH);
     }
 
+    #[test]
+    fn test_square_all() {
+        assert_eq!(Square::ALL.len(), 64);
+        assert_eq!(Square::ALL[0], Square::A1);
+        assert_eq!(Square::ALL[63], Square::H8);
+        for (index, &square) in Square::ALL.iter().enumerate() {
+            assert_eq!(square.to_u8_0_63() as usize, index);
+        }
+        assert!(Square::iter().eq(Square::ALL.iter().copied()));
+    }
+
+    #[test]
+    fn test_square_index_0_63() {
+        assert_eq!(Square::A1.to_u8_0_63(), 0);
+        assert_eq!(Square::H8.to_u8_0_63(), 63);
+        for index in 0..64 {
+            assert_eq!(Square::from_index_0_63(index).to_u8_0_63(), index);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_square_from_index_0_63_out_of_range() {
+        Square::from_index_0_63(64);
+    }
+
+    #[test]
+    fn test_square_is_light() {
+        assert!(!Square::A1.is_light());
+        assert!(!Square::H8.is_light());
+        assert!(Square::A8.is_light());
+        assert!(Square::H1.is_light());
+        assert!(Square::E4.is_light());
+        assert!(!Square::D4.is_light());
+    }
+
     #[test]
     fn test_square_display() {
         assert_eq!(format!("{}", Square::A1), "a1");
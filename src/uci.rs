@@ -0,0 +1,271 @@
+//! A minimal implementation of the [UCI] protocol, so the engine can be used with chess GUIs.
+//!
+//! `go` searches synchronously and only returns once the search is finished, so `stop` is not
+//! supported and any other parameters of `go` besides `depth` are ignored.
+//!
+//! [UCI]: https://www.shredderchess.com/chess-features/uci-universal-chess-interface.html
+
+use std::io::{self, BufRead, Write};
+use std::str::SplitWhitespace;
+
+use crate::BitMove;
+use crate::ParsedMove;
+use crate::Position;
+
+/// The depth used by `go` if no depth is given.
+const DEFAULT_DEPTH: u32 = 4;
+
+/// Reads UCI commands from `input` and writes the responses to `output` until `quit` is received
+/// or the input ends.
+///
+/// The supported commands are `uci`, `isready`, `ucinewgame`, `position` and `go depth <n>`.
+/// Unknown commands are ignored as required by the protocol.
+///
+/// # Examples
+///
+/// ```
+/// use chers::uci::run_uci;
+///
+/// let mut output = Vec::new();
+/// run_uci("position startpos moves e2e4\ngo depth 1\n".as_bytes(), &mut output).unwrap();
+///
+/// let output = String::from_utf8(output).unwrap();
+/// assert!(output.lines().last().unwrap().starts_with("bestmove "));
+/// ```
+pub fn run_uci<R: BufRead, W: Write>(input: R, mut output: W) -> io::Result<()> {
+    let mut pos = Position::new();
+
+    for line in input.lines() {
+        let line = line?;
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("uci") => {
+                writeln!(output, "id name chers {}", env!("CARGO_PKG_VERSION"))?;
+                writeln!(output, "id author {}", env!("CARGO_PKG_AUTHORS"))?;
+                writeln!(output, "uciok")?;
+            }
+            Some("isready") => writeln!(output, "readyok")?,
+            Some("ucinewgame") => pos = Position::new(),
+            Some("position") => match parse_position(tokens) {
+                Ok(new_pos) => pos = new_pos,
+                Err(e) => writeln!(output, "info string {}", e)?,
+            },
+            Some("go") => go(&mut pos, tokens, &mut output)?,
+            Some("quit") => break,
+            _ => {}
+        }
+        output.flush()?;
+    }
+    Ok(())
+}
+
+impl Position {
+    /// Returns the move in pure coordinate notation as used by the UCI protocol.
+    ///
+    /// Castling is written as the king moving two squares, unless a king or castling rook isn't
+    /// on its standard file ([Chess960]). Then it is written as the king capturing its own rook,
+    /// since the king might move only one square or not at all.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::Position;
+    ///
+    /// let mut pos = Position::from_fen("4k3/8/8/8/8/8/8/1RK5 w B - 0 1").unwrap();
+    /// let castle = pos
+    ///     .generate_legal_moves()
+    ///     .into_iter()
+    ///     .find(|m| m.is_castle())
+    ///     .unwrap();
+    ///
+    /// assert_eq!(pos.uci(castle), "c1b1");
+    /// ```
+    ///
+    /// [Chess960]: https://en.wikipedia.org/wiki/Fischer_random_chess
+    pub fn uci(&self, m: BitMove) -> String {
+        let castling_rights = self.state[self.state.len() - 1].castling_rights;
+        if m.is_castle() && castling_rights.is_chess960() {
+            let (rook, _) =
+                castling_rights.rook_squares(self.side_to_move, m.is_king_side_castle());
+            return format!("{}{}", m.origin(), rook);
+        }
+        m.to_string()
+    }
+}
+
+/// Parses the arguments of the `position` command, i.e. `startpos` or `fen <fen>` optionally
+/// followed by `moves <move>...`.
+fn parse_position(mut tokens: SplitWhitespace<'_>) -> Result<Position, String> {
+    let mut pos = match tokens.next() {
+        Some("startpos") => Position::new(),
+        Some("fen") => {
+            let fen: Vec<_> = tokens.by_ref().take_while(|t| *t != "moves").collect();
+            let fen = fen.join(" ");
+            Position::from_fen(&fen).map_err(|e| format!("invalid fen {} ({})", fen, e))?
+        }
+        _ => return Err("expected startpos or fen".to_string()),
+    };
+
+    let moves = tokens
+        .skip_while(|t| *t == "moves")
+        .map(|m| {
+            ParsedMove::from_coordinate_notation(m)
+                .map_err(|e| format!("invalid move {} ({})", m, e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    pos.apply_moves(&moves)
+        .map_err(|(_, m)| format!("illegal move {}", m))?;
+    Ok(pos)
+}
+
+/// Handles the `go` command by searching the position and writing an `info` line for every
+/// iteration followed by the `bestmove`.
+fn go(
+    pos: &mut Position,
+    mut tokens: SplitWhitespace<'_>,
+    output: &mut impl Write,
+) -> io::Result<()> {
+    let mut depth = DEFAULT_DEPTH;
+    while let Some(token) = tokens.next() {
+        if token == "depth" {
+            if let Some(Ok(d)) = tokens.next().map(str::parse) {
+                depth = d;
+            }
+        }
+    }
+
+    let root = pos.clone();
+    let mut result = Ok(());
+    let best = pos.search_with_info(depth, |info| {
+        if result.is_ok() {
+            let mut pos = root.clone();
+            let pv: Vec<_> = info
+                .pv
+                .iter()
+                .map(|&m| {
+                    let uci = pos.uci(m);
+                    pos.make_bit_move(m);
+                    uci
+                })
+                .collect();
+            let score = match info.mate_in() {
+                Some(moves) => format!("mate {}", moves),
+                None => format!("cp {}", info.score),
+            };
+            result = writeln!(
+                output,
+                "info depth {} score {} nodes {} time {} pv {}",
+                info.depth,
+                score,
+                info.nodes,
+                info.time.as_millis(),
+                pv.join(" ")
+            );
+        }
+    });
+    result?;
+
+    match best {
+        Some(m) => writeln!(output, "bestmove {}", root.uci(m)),
+        // the game is already over, the protocol expects a null move
+        None => writeln!(output, "bestmove 0000"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    fn run(input: &str) -> Vec<String> {
+        let mut output = Vec::new();
+        run_uci(input.as_bytes(), &mut output).expect("writing to a vec does not fail");
+        String::from_utf8(output)
+            .expect("valid utf-8")
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_uci_handshake() {
+        let output = run("uci\nisready\n");
+
+        assert!(output[0].starts_with("id name chers"));
+        assert!(output[1].starts_with("id author"));
+        pretty_assertions::assert_eq!(&output[2..], ["uciok", "readyok"]);
+    }
+
+    #[test_case("position startpos\ngo depth 2\n"; "start position")]
+    #[test_case("ucinewgame\nposition startpos moves e2e4 e7e5 g1f3\ngo depth 2\n"; "start position with moves")]
+    #[test_case("position fen r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1 moves e1g1\ngo depth 1\n"; "fen with moves")]
+    fn test_uci_go(input: &str) {
+        let output = run(input);
+
+        let bestmove = output.last().expect("output");
+        assert!(bestmove.starts_with("bestmove "), "{}", bestmove);
+        let infos = output
+            .iter()
+            .filter(|l| l.starts_with("info depth"))
+            .count();
+        assert!(infos > 0);
+    }
+
+    #[test_case("go infinite depth 1\n"; "after infinite")]
+    #[test_case("go ponder depth 1\n"; "after ponder")]
+    #[test_case("go movetime 100 depth 1\n"; "after movetime")]
+    fn test_uci_go_depth(input: &str) {
+        let output = run(input);
+
+        let last_info = output
+            .iter()
+            .rfind(|l| l.starts_with("info depth"))
+            .expect("info line");
+        assert!(last_info.starts_with("info depth 1 "), "{}", last_info);
+    }
+
+    #[test]
+    fn test_uci_long_game() {
+        let moves = ["g1f3", "g8f6", "f3g1", "f6g8"].repeat(75).join(" ");
+        let output = run(&format!(
+            "position startpos moves {} e2e4\ngo depth 1\n",
+            moves
+        ));
+
+        assert!(output.iter().all(|l| !l.starts_with("info string")));
+        assert!(output.last().unwrap().starts_with("bestmove "));
+    }
+
+    #[test]
+    fn test_uci_finds_mate() {
+        let output = run("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\ngo depth 1\nquit\ngo\n");
+        pretty_assertions::assert_eq!(output.last().unwrap(), "bestmove a1a8");
+        assert!(output[output.len() - 2].starts_with("info depth 1 score mate 1 "));
+    }
+
+    #[test]
+    fn test_uci_chess960_castling() {
+        let output = run("position fen 2rkr3/2p1p3/8/8/8/8/8/1RK5 w B - 0 1\ngo depth 1\n");
+        pretty_assertions::assert_eq!(output.last().unwrap(), "bestmove c1b1");
+        assert!(output[output.len() - 2].ends_with(" pv c1b1"));
+
+        let output =
+            run("position fen 2rkr3/2p1p3/8/8/8/8/8/1RK5 w B - 0 1 moves c1b1\ngo depth 1\n");
+        pretty_assertions::assert_eq!(output, ["bestmove 0000"]);
+    }
+
+    #[test]
+    fn test_uci_game_over() {
+        let output = run("position fen 7k/5Q2/6K1/8/8/8/8/8 b - - 0 1\ngo depth 2\n");
+        pretty_assertions::assert_eq!(output, ["bestmove 0000"]);
+    }
+
+    #[test_case("position startpos moves e2e5\n", "info string illegal move e2e5"; "illegal move")]
+    #[test_case("position startpos moves e2\n", "info string invalid move e2 (too short)"; "invalid move")]
+    #[test_case("position fen 8/8/8 w - - 0 1\n", "info string invalid fen 8/8/8 w - - 0 1 (too short)"; "invalid fen")]
+    #[test_case("position\n", "info string expected startpos or fen"; "missing position")]
+    fn test_uci_invalid_position(input: &str, expected: &str) {
+        pretty_assertions::assert_eq!(run(input), [expected]);
+    }
+}
@@ -3,9 +3,6 @@ use std::io::BufRead;
 use std::io::BufReader;
 use std::path::Path;
 
-pub const STARTING_POSITION: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
-pub const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
-
 #[cfg(test)]
 type Decoder = BufReader<zstd::Decoder<'static, BufReader<File>>>;
 
@@ -0,0 +1,248 @@
+//! Zobrist hashing of positions.
+//!
+//! Every feature of a position (a piece on a square, the side to move, the castling rights and the
+//! en passant file) gets a random 64-bit key and the hash is the XOR of the keys of all features
+//! present. This way a move only has to XOR out the keys of the features it removes and XOR in
+//! the ones it adds.
+
+use crate::{BitMove, CastlingRights, Color, Piece, PieceType, Position, PositionState, Square};
+
+/// Generates `N` pseudo random numbers with splitmix64, so the keys are the same on every run.
+const fn random_keys<const N: usize>(seed: u64) -> [u64; N] {
+    let mut keys = [0; N];
+    let mut state = seed;
+    let mut i = 0;
+    while i < N {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        keys[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    keys
+}
+
+const PIECE_SQUARE_KEYS: [u64; 12 * 120] = random_keys(1);
+const CASTLING_KEYS: [u64; 16] = random_keys(2);
+const EN_PASSANT_KEYS: [u64; 8] = random_keys(3);
+const SIDE_TO_MOVE_KEY: u64 = random_keys::<1>(4)[0];
+
+/// Returns the key of `piece` standing on `square`. Empty squares don't have a key.
+#[inline]
+pub(crate) fn piece_key(piece: Piece, square: Square) -> u64 {
+    if piece.is_piece() {
+        PIECE_SQUARE_KEYS[piece.to_index() * 120 + square.to_usize()]
+    } else {
+        0
+    }
+}
+
+/// Returns the key of the castling rights.
+#[inline]
+pub(crate) fn castling_key(castling_rights: CastlingRights) -> u64 {
+    CASTLING_KEYS[castling_rights.to_usize()]
+}
+
+/// Returns the key of the en passant square, which only depends on its file.
+#[inline]
+pub(crate) fn en_passant_key(ep_square: Square) -> u64 {
+    if ep_square == Square::NO_SQ {
+        0
+    } else {
+        EN_PASSANT_KEYS[ep_square.file().to_u8() as usize]
+    }
+}
+
+/// Returns the key that is present if black is to move.
+#[inline]
+pub(crate) fn side_to_move_key(side_to_move: Color) -> u64 {
+    side_to_move.map(0, SIDE_TO_MOVE_KEY)
+}
+
+/// Returns the key of the castling rights and en passant square stored in `state`.
+#[inline]
+pub(crate) fn state_key(state: &PositionState) -> u64 {
+    castling_key(state.castling_rights) ^ en_passant_key(state.ep_square)
+}
+
+/// Returns the value the hash changes by when `m` is made (or undone, since XOR is its own
+/// inverse).
+///
+/// `piece` is the piece that moves and `placed` the piece that ends up on the target square,
+/// which is only different for promotions. `captured` is the piece on `capture_square` before the
+/// move.
+pub(crate) fn move_key(
+    m: BitMove,
+    piece: Piece,
+    placed: Piece,
+    captured: Piece,
+    capture_square: Square,
+    before: &PositionState,
+    after: &PositionState,
+) -> u64 {
+    let mut key = SIDE_TO_MOVE_KEY ^ state_key(before) ^ state_key(after);
+    key ^= piece_key(piece, m.origin()) ^ piece_key(placed, m.target());
+    key ^= piece_key(captured, capture_square);
+
+    if m.is_castle() {
+        let rook = Piece::new(PieceType::ROOK, piece.color());
+        let (rook_origin, rook_target) = before
+            .castling_rights
+            .rook_squares(piece.color(), m.is_king_side_castle());
+        key ^= piece_key(rook, rook_origin) ^ piece_key(rook, rook_target);
+    }
+    key
+}
+
+/// Returns the value the hash changes by when a null move is made or undone.
+#[inline]
+pub(crate) fn null_move_key(before: &PositionState, after: &PositionState) -> u64 {
+    SIDE_TO_MOVE_KEY ^ state_key(before) ^ state_key(after)
+}
+
+impl Position {
+    /// Computes the Zobrist hash of the position from scratch. It covers the pieces, the side to
+    /// move, the castling rights and the en passant square, but not the move counters.
+    ///
+    /// The hash is also kept up to date while moves are made and undone, see
+    /// [`Position::zobrist_key`], which is much cheaper.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{ParsedMove, Position};
+    ///
+    /// let mut a = Position::new();
+    /// let mut b = Position::new();
+    /// for m in ["g1f3", "g8f6", "b1c3"] {
+    ///     a.make_move(ParsedMove::from_coordinate_notation(m).unwrap());
+    /// }
+    /// for m in ["b1c3", "g8f6", "g1f3"] {
+    ///     b.make_move(ParsedMove::from_coordinate_notation(m).unwrap());
+    /// }
+    ///
+    /// assert_eq!(a.zobrist_hash(), b.zobrist_hash());
+    /// assert_ne!(a.zobrist_hash(), Position::new().zobrist_hash());
+    /// ```
+    pub fn zobrist_hash(&self) -> u64 {
+        let mut hash =
+            side_to_move_key(self.side_to_move) ^ state_key(&self.state[self.state.len() - 1]);
+        for square in Square::iter() {
+            hash ^= piece_key(self.pieces[square], square);
+        }
+        hash
+    }
+
+    /// Returns the Zobrist hash of the position in constant time. It is always equal to
+    /// [`Position::zobrist_hash`].
+    #[inline]
+    pub fn zobrist_key(&self) -> u64 {
+        self.state[self.state.len() - 1].key
+    }
+
+    /// Returns a key for detecting repeated positions. It only depends on the pieces, the side to
+    /// move, the castling rights and the en passant square, but not on the halfmove clock or the
+    /// fullmove number. Currently it is the same as [`Position::zobrist_key`].
+    ///
+    /// Note that `Position`'s [`PartialEq`] also compares the move counters, so two positions
+    /// with the same repetition key are usually not equal. That's why `Position` doesn't implement
+    /// [`Hash`](std::hash::Hash); use this key in a hash map instead. Like every hash, different
+    /// positions can in rare cases share a key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chers::{ParsedMove, Position};
+    ///
+    /// let mut pos = Position::new();
+    /// for m in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+    ///     pos.make_move(ParsedMove::from_coordinate_notation(m).unwrap());
+    /// }
+    ///
+    /// assert_eq!(pos.repetition_key(), Position::new().repetition_key());
+    /// assert_ne!(pos, Position::new()); // the move counters differ
+    /// ```
+    #[inline]
+    pub fn repetition_key(&self) -> u64 {
+        self.state[self.state.len() - 1].key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+    use crate::fen;
+    use crate::ParsedMove;
+
+    /// Walks the move tree and checks the incremental hash after every move and undo.
+    fn check_hash(pos: &mut Position, depth: u16) {
+        pretty_assertions::assert_eq!(pos.zobrist_key(), pos.zobrist_hash(), "{}", pos.to_fen());
+        if depth == 0 {
+            return;
+        }
+        for m in pos.generate_legal_moves() {
+            let before = pos.zobrist_key();
+            pos.make_bit_move(m);
+            check_hash(pos, depth - 1);
+            pos.undo_move();
+            pretty_assertions::assert_eq!(pos.zobrist_key(), before, "undoing {}", m);
+        }
+    }
+
+    #[test_case(fen::KIWIPETE, 3; "kiwipete")]
+    #[test_case(fen::POSITION_3, 4; "position 3")]
+    #[test_case(fen::POSITION_4, 3; "position 4")]
+    #[test_case(fen::POSITION_5, 3; "position 5")]
+    fn test_zobrist_incremental(fen: &str, depth: u16) {
+        let mut pos = Position::from_fen(fen).expect("valid position");
+        check_hash(&mut pos, depth);
+    }
+
+    #[test_case("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1", "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"; "en passant square")]
+    #[test_case(fen::STARTING_FEN, "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w Kkq - 0 1"; "castling rights")]
+    #[test_case(fen::STARTING_FEN, "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1"; "side to move")]
+    #[test_case(fen::STARTING_FEN, "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBRN w KQkq - 0 1"; "pieces")]
+    fn test_zobrist_hash_differs(a: &str, b: &str) {
+        let a = Position::from_fen(a).expect("valid position");
+        let b = Position::from_fen(b).expect("valid position");
+        assert_ne!(a.zobrist_hash(), b.zobrist_hash());
+    }
+
+    #[test]
+    fn test_zobrist_hash_ignores_move_counters() {
+        let a = Position::from_fen("8/8/4k3/8/8/4K3/4R3/8 w - - 0 1").expect("valid position");
+        let b = Position::from_fen("8/8/4k3/8/8/4K3/4R3/8 w - - 37 60").expect("valid position");
+        pretty_assertions::assert_eq!(a.zobrist_hash(), b.zobrist_hash());
+    }
+
+    #[test_case("8/8/4k3/8/8/4K3/4R3/8 w - - 0 1", "8/8/4k3/8/8/4K3/4R3/8 w - - 37 1"; "halfmove clock")]
+    #[test_case("8/8/4k3/8/8/4K3/4R3/8 w - - 0 1", "8/8/4k3/8/8/4K3/4R3/8 w - - 0 60"; "fullmove number")]
+    fn test_repetition_key_ignores_move_counters(a: &str, b: &str) {
+        let a = Position::from_fen(a).expect("valid position");
+        let b = Position::from_fen(b).expect("valid position");
+        pretty_assertions::assert_eq!(a.repetition_key(), b.repetition_key());
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_zobrist_key_after_edits() {
+        let mut pos = Position::new();
+        assert!(pos.make_move(ParsedMove::from_coordinate_notation("e2e4").unwrap()));
+
+        pos.set_en_passant(None);
+        pretty_assertions::assert_eq!(pos.zobrist_key(), pos.zobrist_hash());
+        pos.set_castling_rights(CastlingRights::new(true, false, true, false));
+        pretty_assertions::assert_eq!(pos.zobrist_key(), pos.zobrist_hash());
+        pos.set_side_to_move(Color::WHITE);
+        pretty_assertions::assert_eq!(pos.zobrist_key(), pos.zobrist_hash());
+
+        let snapshot = pos.snapshot();
+        let hash = pos.zobrist_key();
+        assert!(pos.make_move(ParsedMove::from_coordinate_notation("d2d4").unwrap()));
+        pos.restore(snapshot);
+        pretty_assertions::assert_eq!(pos.zobrist_key(), hash);
+    }
+}
@@ -0,0 +1,54 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use chers::{fen, Position};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// There is only one test in this file, so no other test can allocate concurrently.
+#[test]
+fn test_terminal_checks_do_not_allocate() {
+    let positions: Vec<_> = [
+        fen::STARTING_FEN,
+        fen::KIWIPETE,
+        "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3",
+        "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1",
+    ]
+    .iter()
+    .map(|fen| Position::from_fen(fen).unwrap())
+    .collect();
+
+    let before = ALLOCATIONS.load(Ordering::SeqCst);
+    let results: [(bool, bool, bool); 4] = [0, 1, 2, 3].map(|i| {
+        let pos = &positions[i];
+        (pos.is_checkmate(), pos.is_stalemate(), pos.has_legal_move())
+    });
+    let after = ALLOCATIONS.load(Ordering::SeqCst);
+
+    assert_eq!(after, before);
+    assert_eq!(
+        results,
+        [
+            (false, false, true),
+            (false, false, true),
+            (true, false, false),
+            (false, true, false),
+        ]
+    );
+}